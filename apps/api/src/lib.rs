@@ -3,7 +3,6 @@ use common::modules::Module;
 use common::{config::Config, server::create_app};
 use companies::CompaniesModule;
 use tokio::net::TcpListener;
-use tracing_subscriber::{fmt, EnvFilter};
 use worktypes::WorktypesModule;
 
 pub struct AppModules {
@@ -15,10 +14,11 @@ pub struct AppModules {
 
 impl AppModules {
     pub async fn init(config: &Config) -> Self {
-        let c = config.database_url.clone();
-        tracing::info!(c);
+        tracing::info!(storage = ?config.storage);
         let companies: CompaniesModule = CompaniesModule::create(config).await.unwrap();
         let worktypes: WorktypesModule = WorktypesModule::create(config).await.unwrap();
+        // Los tipos de trabajo propios de una compañia se copian y se borran con ella
+        let companies = companies.with_hooks(worktypes.company_hooks());
         // more modules here:
         // let new_module = NewModule::create(config).await.unwrap();
 
@@ -50,7 +50,6 @@ pub async fn create_routes(config: &Config) -> Router {
 pub async fn run() {
     // Inicializar el logger
 
-
     // Cargar configuración
     let config: Config = Config::from_env();
     let router: Router = create_routes(&config).await;
@@ -1,62 +1,1219 @@
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
+    use api::AppModules;
     use axum::{
-        body::Body,
+        body::{to_bytes, Body},
         http::{Request, StatusCode},
         Router,
     };
-    use companies_api::{
-        models::CompanyRequest, repository::CompanyRepository, routes::create_routes,
-    };
+    use companies::CompaniesModule;
     use serde_json::{json, Value};
     use tower::ServiceExt;
+    use worktypes::WorktypesModule;
 
+    // Los modulos usan sus repositorios en memoria, asi que no hace falta base de datos
     async fn setup() -> Router {
-        let repository = Arc::new(CompanyRepository::new());
-        create_routes(repository)
+        let companies = CompaniesModule::with_provider(companies::RepositoryProvider::Memory)
+            .await
+            .unwrap();
+        let worktypes = WorktypesModule::with_provider(worktypes::RepositoryProvider::Memory)
+            .await
+            .unwrap();
+        let companies = companies.with_hooks(worktypes.company_hooks());
+        AppModules {
+            companies,
+            worktypes,
+        }
+        .combined_routes()
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json");
+        let request = match body {
+            Some(body) => request.body(Body::from(body.to_string())).unwrap(),
+            None => request.body(Body::empty()).unwrap(),
+        };
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    fn bug_worktype() -> Value {
+        json!({
+            "title": "Bug",
+            "description": "A software bug",
+            "attributes": [
+                { "name": "Summary", "data_type": "string", "is_required": true, "is_hidden": false },
+                { "name": "Severity", "data_type": "numeric", "is_required": false, "is_hidden": false,
+                  "min_value": 1.0, "max_value": 5.0 }
+            ]
+        })
     }
 
     #[tokio::test]
     async fn test_list_companies() {
         let app = setup().await;
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/companies")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let (status, _) = send(&app, "GET", "/companies", None).await;
 
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(status, StatusCode::OK);
     }
 
     #[tokio::test]
     async fn test_create_company() {
         let app = setup().await;
 
-        let company_request = CompanyRequest {
-            name: "Test Company".to_string(),
-        };
+        let (status, body) = send(
+            &app,
+            "POST",
+            "/companies",
+            Some(json!({ "name": "Test Company" })),
+        )
+        .await;
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .method("POST")
-                    .uri("/companies")
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(serde_json::to_string(&company_request).unwrap()))
-                    .unwrap(),
-            )
-            .await
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["name"], "Test Company");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_company_has_no_worktypes() {
+        let app = setup().await;
+
+        let (status, _) = send(&app, "GET", "/companies/unknown/worktypes", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let uri = "/companies/unknown/worktypes";
+        let (status, _) = send(&app, "POST", uri, Some(bug_worktype())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_company_worktypes_follow_the_company() {
+        let app = setup().await;
+        let (_, company) = send(&app, "POST", "/companies", Some(json!({ "name": "Acme" }))).await;
+        let company_uri = format!("/companies/{}", company["id"].as_str().unwrap());
+        let worktypes_uri = format!("{}/worktypes", company_uri);
+        let (status, parent) = send(&app, "POST", &worktypes_uri, Some(bug_worktype())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let child = json!({ "title": "UI Bug", "description": null, "attributes": [],
+                            "parent_id": parent["id"] });
+        let (status, child) = send(&app, "POST", &worktypes_uri, Some(child)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, copy) = send(&app, "POST", &format!("{}/duplicate", company_uri), None).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let copy_uri = format!("/companies/{}/worktypes", copy["id"].as_str().unwrap());
+        let (_, copies) = send(&app, "GET", &copy_uri, None).await;
+        let copies = copies.as_array().unwrap();
+        assert_eq!(copies.len(), 2);
+        let parent_copy = copies.iter().find(|wt| wt["title"] == "Bug").unwrap();
+        let child_copy = copies.iter().find(|wt| wt["title"] == "UI Bug").unwrap();
+        assert_ne!(parent_copy["id"], parent["id"]);
+        assert_eq!(child_copy["parent_id"], parent_copy["id"]);
+
+        let (status, _) = send(&app, "DELETE", &company_uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        for work_type in [&parent, &child] {
+            let uri = format!("/worktypes/{}", work_type["id"].as_str().unwrap());
+            let (status, _) = send(&app, "GET", &uri, None).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+        }
+        let (_, copies) = send(&app, "GET", &copy_uri, None).await;
+        assert_eq!(copies.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_worktype() {
+        let app = setup().await;
+
+        let (status, created) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["version"], 1);
+
+        let uri = format!("/worktypes/{}", created["id"].as_str().unwrap());
+        let (status, fetched) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(fetched, created);
+    }
+
+    #[tokio::test]
+    async fn test_get_unknown_worktype_is_not_found() {
+        let app = setup().await;
+
+        let (status, _) = send(
+            &app,
+            "GET",
+            "/worktypes/00000000-0000-0000-0000-000000000000",
+            None,
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_update_worktype_creates_a_new_version() {
+        let app = setup().await;
+        let (_, created) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = created["id"].as_str().unwrap();
+
+        let mut request = bug_worktype();
+        request["title"] = json!("Defect");
+        let (status, updated) = send(
+            &app,
+            "PUT",
+            &format!("/worktypes/{}", id),
+            Some(request.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["version"], 1);
+
+        request["attributes"][1]["max_value"] = json!(10.0);
+        let (_, updated) = send(&app, "PUT", &format!("/worktypes/{}", id), Some(request)).await;
+        assert_eq!(updated["version"], 2);
+        assert_eq!(
+            updated["attributes"][1]["id"],
+            created["attributes"][1]["id"]
+        );
+
+        let (_, versions) = send(&app, "GET", &format!("/worktypes/{}/versions", id), None).await;
+        assert_eq!(versions.as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_worktype_with_children_cannot_be_deleted() {
+        let app = setup().await;
+        let (_, parent) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let parent_id = parent["id"].as_str().unwrap();
+
+        let child = json!({
+            "title": "Security Bug",
+            "description": null,
+            "parent_id": parent_id,
+            "attributes": []
+        });
+        let (status, child) = send(&app, "POST", "/worktypes", Some(child)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(child["attributes"][1]["inherited_from"], parent["id"]);
+
+        let (status, _) = send(&app, "DELETE", &format!("/worktypes/{}", parent_id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let child_uri = format!("/worktypes/{}", child["id"].as_str().unwrap());
+        let (status, _) = send(&app, "DELETE", &child_uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send(&app, "DELETE", &format!("/worktypes/{}", parent_id), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_list_worktypes_filters_and_sorts_by_title() {
+        let app = setup().await;
+        for title in ["Task", "Bug", "Security Bug"] {
+            let mut request = bug_worktype();
+            request["title"] = json!(title);
+            send(&app, "POST", "/worktypes", Some(request)).await;
+        }
+
+        let (status, listed) = send(
+            &app,
+            "GET",
+            "/worktypes?title=bug&sort=title&order=desc",
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let titles: Vec<&str> = listed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|wt| wt["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Security Bug", "Bug"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_work_item_validates_values() {
+        let app = setup().await;
+        let (_, work_type) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let items_uri = format!("/worktypes/{}/items", work_type["id"].as_str().unwrap());
+
+        let invalid = json!({ "values": { "summary": "Login fails", "severity": "9" } });
+        let (status, _) = send(&app, "POST", &items_uri, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let valid = json!({ "values": { "summary": "Login fails", "severity": "3" } });
+        let (status, item) = send(&app, "POST", &items_uri, Some(valid)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(item["schema_version"], 1);
+
+        let (_, items) = send(&app, "GET", &items_uri, None).await;
+        assert_eq!(items.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_work_item_prepares_values_like_create() {
+        let app = setup().await;
+        let mut request = bug_worktype();
+        request["attributes"][1]["default_value"] = json!("3");
+        let (_, work_type) = send(&app, "POST", "/worktypes", Some(request)).await;
+        let items_uri = format!("/worktypes/{}/items", work_type["id"].as_str().unwrap());
+
+        let values = json!({ "values": { "summary": "Login fails", "severity": "5" } });
+        let (_, item) = send(&app, "POST", &items_uri, Some(values)).await;
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+
+        let values = json!({ "values": { "summary": "Login fails again" } });
+        let (status, updated) = send(&app, "PUT", &item_uri, Some(values)).await;
+        assert_eq!(status, StatusCode::OK);
+        let severity = updated["work_attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["key"] == "severity")
             .unwrap();
+        assert_eq!(severity["value"], "3");
 
-        assert_eq!(response.status(), StatusCode::CREATED);
+        let invalid = json!({ "values": { "summary": "Login fails", "severity": "9" } });
+        let (status, _) = send(&app, "PUT", &item_uri, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_work_item_follows_the_workflow() {
+        let app = setup().await;
+        let (_, work_type) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = work_type["id"].as_str().unwrap();
+
+        let workflow = json!({
+            "initial_status": "Open",
+            "statuses": [
+                { "name": "Open", "category": "todo", "transitions": ["In Progress"] },
+                { "name": "In Progress", "category": "in_progress", "transitions": ["Done"] },
+                { "name": "Done", "category": "done", "transitions": [] }
+            ]
+        });
+        let (status, _) = send(
+            &app,
+            "PUT",
+            &format!("/worktypes/{}/workflow", id),
+            Some(workflow),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let values = json!({ "values": { "summary": "Login fails" } });
+        let (_, item) = send(
+            &app,
+            "POST",
+            &format!("/worktypes/{}/items", id),
+            Some(values),
+        )
+        .await;
+        assert_eq!(item["status"], "Open");
+
+        let transition_uri = format!("/items/{}/transition", item["id"].as_str().unwrap());
+        let (status, _) = send(&app, "POST", &transition_uri, Some(json!({ "to": "Done" }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, item) = send(
+            &app,
+            "POST",
+            &transition_uri,
+            Some(json!({ "to": "In Progress" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(item["status"], "In Progress");
     }
 
-    // Más tests aquí...
+    #[tokio::test]
+    async fn test_duplicate_worktype() {
+        let app = setup().await;
+        let (_, original) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+
+        let uri = format!("/worktypes/{}/duplicate", original["id"].as_str().unwrap());
+        let (status, copy) = send(&app, "POST", &uri, None).await;
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(copy["title"], "Bug (copia)");
+        assert_ne!(copy["id"], original["id"]);
+        assert_ne!(copy["attributes"][0]["id"], original["attributes"][0]["id"]);
+    }
+
+    #[tokio::test]
+    async fn test_computed_attributes_are_recalculated() {
+        let app = setup().await;
+        let request = json!({
+            "title": "Risk",
+            "description": null,
+            "attributes": [
+                { "name": "Severity", "data_type": "numeric", "is_required": true, "is_hidden": false },
+                { "name": "Impact", "data_type": "numeric", "is_required": true, "is_hidden": false },
+                { "name": "Score", "data_type": "numeric", "is_required": false, "is_hidden": false,
+                  "formula": "severity * impact" }
+            ]
+        });
+        let (status, work_type) = send(&app, "POST", "/worktypes", Some(request)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let items_uri = format!("/worktypes/{}/items", work_type["id"].as_str().unwrap());
+
+        let values = json!({ "values": { "severity": "3", "impact": "4", "score": "1" } });
+        let (status, _) = send(&app, "POST", &items_uri, Some(values)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let values = json!({ "values": { "severity": "3", "impact": "4" } });
+        let (_, item) = send(&app, "POST", &items_uri, Some(values)).await;
+        assert_eq!(item["work_attributes"][2]["value"], "12");
+
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+        let values = json!({ "values": { "severity": "5", "impact": "4" } });
+        let (_, item) = send(&app, "PUT", &item_uri, Some(values)).await;
+        assert_eq!(item["work_attributes"][2]["value"], "20");
+    }
+
+    #[tokio::test]
+    async fn test_formula_references_are_checked() {
+        let app = setup().await;
+        let request = json!({
+            "title": "Risk",
+            "description": null,
+            "attributes": [
+                { "name": "Title", "data_type": "string", "is_required": true, "is_hidden": false },
+                { "name": "Score", "data_type": "numeric", "is_required": false, "is_hidden": false,
+                  "formula": "title * missing" }
+            ]
+        });
+
+        let (status, body) = send(&app, "POST", "/worktypes", Some(request)).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("Score"));
+    }
+
+    #[tokio::test]
+    async fn test_formulas_survive_attribute_renames() {
+        let app = setup().await;
+        let request = json!({
+            "title": "Story",
+            "description": null,
+            "attributes": [
+                { "name": "Story Points", "data_type": "numeric", "is_required": false, "is_hidden": false },
+                { "name": "Effort", "data_type": "numeric", "is_required": false, "is_hidden": false,
+                  "formula": "Story_Points * 2" }
+            ]
+        });
+        let (status, body) = send(&app, "POST", "/worktypes", Some(request.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("'story_points'"));
+
+        let mut request = request;
+        request["attributes"][1]["formula"] = json!("story_points * 2");
+        let (status, work_type) = send(&app, "POST", "/worktypes", Some(request)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = work_type["id"].as_str().unwrap();
+        let points_id = work_type["attributes"][0]["id"].as_str().unwrap();
+
+        let renamed = json!({ "name": "Estimate", "data_type": "numeric",
+                              "is_required": false, "is_hidden": false });
+        let uri = format!("/worktypes/{}/attributes/{}", id, points_id);
+        let (status, attribute) = send(&app, "PUT", &uri, Some(renamed)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(attribute["key"], "story_points");
+
+        let values = json!({ "values": { "story_points": "3" } });
+        let uri = format!("/worktypes/{}/items", id);
+        let (status, item) = send(&app, "POST", &uri, Some(values)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let effort = item["work_attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|value| value["key"] == "effort")
+            .unwrap();
+        assert_eq!(effort["value"], "6");
+    }
+
+    #[tokio::test]
+    async fn test_hierarchical_links_reject_cycles() {
+        let app = setup().await;
+        let (_, work_type) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let items_uri = format!("/worktypes/{}/items", work_type["id"].as_str().unwrap());
+        let mut ids: Vec<String> = Vec::new();
+        for summary in ["Epic", "Story", "Task"] {
+            let values = json!({ "values": { "summary": summary } });
+            let (_, item) = send(&app, "POST", &items_uri, Some(values)).await;
+            ids.push(item["id"].as_str().unwrap().to_string());
+        }
+
+        let link_type = json!({
+            "name": "Hierarchy",
+            "outward_name": "parent of",
+            "inward_name": "child of",
+            "is_hierarchical": true
+        });
+        let (status, link_type) = send(&app, "POST", "/link-types", Some(link_type)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let link = |target: &str| json!({ "link_type_id": link_type["id"], "target_id": target });
+
+        let epic_links = format!("/items/{}/links", ids[0]);
+        let story_links = format!("/items/{}/links", ids[1]);
+        let task_links = format!("/items/{}/links", ids[2]);
+        let (status, _) = send(&app, "POST", &epic_links, Some(link(&ids[1]))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, _) = send(&app, "POST", &story_links, Some(link(&ids[2]))).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = send(&app, "POST", &task_links, Some(link(&ids[0]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("cycle"));
+
+        let uri = format!("/items/{}?include=links", ids[1]);
+        let (status, story) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let links = story["links"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0]["name"], "child of");
+        assert_eq!(links[0]["item_id"], ids[0].as_str());
+        assert_eq!(links[1]["name"], "parent of");
+    }
+
+    #[tokio::test]
+    async fn test_worktype_schema_describes_item_payloads() {
+        let app = setup().await;
+        let mut request = bug_worktype();
+        request["attributes"][1]["default_value"] = json!("3");
+        request["attributes"].as_array_mut().unwrap().push(
+            json!({ "name": "Platform", "data_type": "single_select", "is_required": true,
+                          "is_hidden": false, "options": ["Web", "iOS"] }),
+        );
+        let (_, work_type) = send(&app, "POST", "/worktypes", Some(request)).await;
+
+        let uri = format!("/worktypes/{}/schema", work_type["id"].as_str().unwrap());
+        let (status, schema) = send(&app, "GET", &uri, None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            schema["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        assert_eq!(schema["title"], "Bug");
+        assert_eq!(schema["description"], "A software bug");
+        let values = &schema["properties"]["values"];
+        assert_eq!(values["required"], json!(["summary", "platform"]));
+        assert_eq!(values["additionalProperties"], false);
+        assert_eq!(
+            values["properties"]["platform"]["enum"],
+            json!(["Web", "iOS"])
+        );
+        assert_eq!(values["properties"]["severity"]["default"], "3");
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_bundles() {
+        let app = setup().await;
+        let (_, parent) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let child = json!({
+            "title": "Security Bug",
+            "description": null,
+            "parent_id": parent["id"],
+            "attributes": [
+                { "name": "CVE", "data_type": "string", "is_required": false, "is_hidden": false }
+            ]
+        });
+        let (_, child) = send(&app, "POST", "/worktypes", Some(child)).await;
+
+        let uri = format!("/worktypes/export?ids={}", child["id"].as_str().unwrap());
+        let (status, bundle) = send(&app, "GET", &uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(bundle["work_types"][0]["title"], "Bug");
+        assert_eq!(bundle["work_types"][1]["parent"], "Bug");
+        assert_eq!(bundle["work_types"][1]["attributes"][0]["name"], "CVE");
+        assert!(bundle["work_types"][1].get("id").is_none());
+
+        let (_, outcomes) = send(&app, "POST", "/worktypes/import", Some(bundle.clone())).await;
+        assert_eq!(outcomes[0]["action"], "skipped");
+        assert_eq!(outcomes[0]["work_type"]["id"], parent["id"]);
+
+        let (status, outcomes) = send(
+            &app,
+            "POST",
+            "/worktypes/import?conflict=rename",
+            Some(bundle),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(outcomes[1]["action"], "renamed");
+        assert_eq!(outcomes[1]["work_type"]["title"], "Security Bug (2)");
+        assert_eq!(
+            outcomes[1]["work_type"]["parent_id"],
+            outcomes[0]["work_type"]["id"]
+        );
+        let (_, listed) = send(&app, "GET", "/worktypes", None).await;
+        assert_eq!(listed.as_array().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_sync_plans_and_applies_desired_state() {
+        let app = setup().await;
+        let (_, bug) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let task = json!({ "title": "Task", "description": null, "attributes": [] });
+        let (_, task) = send(&app, "POST", "/worktypes", Some(task)).await;
+
+        let document = json!({
+            "version": 1,
+            "work_types": [
+                {
+                    "title": "Bug",
+                    "description": "A software bug",
+                    "attributes": [
+                        { "name": "Summary", "data_type": "string", "is_required": true, "is_hidden": false },
+                        { "name": "Severity", "data_type": "numeric", "is_required": false, "is_hidden": false,
+                          "min_value": 1.0, "max_value": 3.0 },
+                        { "name": "Component", "data_type": "string", "is_required": false, "is_hidden": false }
+                    ]
+                },
+                { "title": "Spike", "parent": "Bug" }
+            ]
+        });
+
+        let (status, plan) = send(&app, "POST", "/worktypes/sync", Some(document.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plan["applied"], false);
+        let changes = plan["changes"].as_array().unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0]["action"], "update");
+        assert_eq!(changes[0]["work_type_id"], bug["id"]);
+        assert_eq!(changes[0]["attributes"][0]["name"], "Severity");
+        assert_eq!(changes[0]["attributes"][0]["fields"], json!(["max_value"]));
+        assert_eq!(changes[0]["attributes"][1]["action"], "create");
+        assert_eq!(changes[1]["action"], "create");
+        assert_eq!(changes[2]["action"], "delete");
+        assert_eq!(changes[2]["work_type_id"], task["id"]);
+        let (_, listed) = send(&app, "GET", "/worktypes", None).await;
+        assert_eq!(listed.as_array().unwrap().len(), 2);
+
+        let (status, plan) = send(
+            &app,
+            "POST",
+            "/worktypes/sync?apply=true",
+            Some(document.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plan["applied"], true);
+        let (_, listed) = send(&app, "GET", "/worktypes?sort=title", None).await;
+        let titles: Vec<&str> = listed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|wt| wt["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Bug", "Spike"]);
+        assert_eq!(listed[1]["parent_id"], bug["id"]);
+        assert_eq!(plan["changes"][1]["work_type_id"], listed[1]["id"]);
+
+        let (_, plan) = send(&app, "POST", "/worktypes/sync", Some(document)).await;
+        assert_eq!(plan["changes"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_manage_single_attributes() {
+        let app = setup().await;
+        let (_, bug) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = bug["id"].as_str().unwrap();
+        let uri = format!("/worktypes/{}/attributes", id);
+
+        let component = json!({ "name": "Component", "data_type": "string", "is_required": false,
+                                "is_hidden": false, "position": 0 });
+        let (status, created) = send(&app, "POST", &uri, Some(component)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created["position"], 0);
+
+        let (_, listed) = send(&app, "GET", &uri, None).await;
+        let names: Vec<&str> = listed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["Component", "Summary", "Severity", "Description"]
+        );
+
+        let (_, item) = send(
+            &app,
+            "POST",
+            &format!("/worktypes/{}/items", id),
+            Some(json!({ "values": { "summary": "Crash", "component": "ui" } })),
+        )
+        .await;
+
+        let attribute_uri = format!("{}/{}", uri, created["id"].as_str().unwrap());
+        let renamed = json!({ "name": "Area", "data_type": "string", "is_required": false,
+                              "is_hidden": false, "position": 5 });
+        let (status, updated) = send(&app, "PUT", &attribute_uri, Some(renamed)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["id"], created["id"]);
+        assert_eq!(updated["position"], 3);
+
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+        let (_, item) = send(&app, "GET", &item_uri, None).await;
+        assert_eq!(item["work_attributes"][0]["name"], "Area");
+
+        let (_, work_type) = send(&app, "GET", &format!("/worktypes/{}", id), None).await;
+        assert_ne!(work_type["updated_at"], bug["updated_at"]);
+        assert_eq!(work_type["attributes"][3]["name"], "Area");
+
+        let duplicate = json!({ "name": "Summary", "data_type": "string", "is_required": false,
+                                "is_hidden": false });
+        let (status, _) = send(&app, "POST", &uri, Some(duplicate)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = send(&app, "DELETE", &attribute_uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send(&app, "GET", &attribute_uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_system_attributes_are_always_present() {
+        let app = setup().await;
+        let task = json!({ "title": "Task", "description": null, "attributes": [
+            { "name": "Summary", "data_type": "string", "is_required": true, "is_hidden": false },
+            { "name": "Points", "data_type": "numeric", "is_required": false, "is_hidden": false }
+        ] });
+        let (status, task) = send(&app, "POST", "/worktypes", Some(task)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let attributes: Vec<(&str, bool, bool)> = task["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| {
+                let name = a["name"].as_str().unwrap();
+                (name, a["is_system"] == true, a["is_required"] == true)
+            })
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("Summary", true, true),
+                ("Points", false, false),
+                ("Description", true, false)
+            ]
+        );
+
+        let id = task["id"].as_str().unwrap();
+        let update = json!({ "title": "Task", "description": null, "attributes": [] });
+        let (_, updated) = send(&app, "PUT", &format!("/worktypes/{}", id), Some(update)).await;
+        assert_eq!(updated["attributes"][0]["id"], task["attributes"][0]["id"]);
+        assert_eq!(updated["attributes"].as_array().unwrap().len(), 2);
+
+        let summary_uri = format!(
+            "/worktypes/{}/attributes/{}",
+            id,
+            task["attributes"][0]["id"].as_str().unwrap()
+        );
+        let (status, _) = send(&app, "DELETE", &summary_uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let retyped = json!({ "name": "Summary", "data_type": "numeric", "is_required": true,
+                              "is_hidden": false });
+        let (status, _) = send(&app, "PUT", &summary_uri, Some(retyped)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let child =
+            json!({ "title": "Sub-task", "description": null, "parent_id": id, "attributes": [] });
+        let (_, child) = send(&app, "POST", "/worktypes", Some(child)).await;
+        let inherited: Vec<&Value> = child["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|a| a["is_system"] == true)
+            .collect();
+        assert_eq!(inherited.len(), 2);
+        assert!(inherited.iter().all(|a| a["inherited_from"] == task["id"]));
+    }
+
+    #[tokio::test]
+    async fn test_attribute_keys_address_values() {
+        let app = setup().await;
+        let story = json!({ "title": "Story", "description": null, "attributes": [
+            { "name": "Story Points", "data_type": "numeric", "is_required": true, "is_hidden": false },
+            { "name": "Acceptance", "key": "criteria", "data_type": "string", "is_required": false,
+              "is_hidden": false }
+        ] });
+        let (status, story) = send(&app, "POST", "/worktypes", Some(story)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(story["attributes"][0]["key"], "story_points");
+        assert_eq!(story["attributes"][1]["key"], "criteria");
+        let id = story["id"].as_str().unwrap();
+
+        let values = json!({ "values": { "story_points": "3", "criteria": "Works offline" } });
+        let (status, item) = send(
+            &app,
+            "POST",
+            &format!("/worktypes/{}/items", id),
+            Some(values),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(item["work_attributes"][0]["key"], "story_points");
+        let by_name = json!({ "values": { "Story Points": "3" } });
+        let (status, _) = send(
+            &app,
+            "POST",
+            &format!("/worktypes/{}/items", id),
+            Some(by_name),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let attribute_uri = format!(
+            "/worktypes/{}/attributes/{}",
+            id,
+            story["attributes"][0]["id"].as_str().unwrap()
+        );
+        let renamed = json!({ "name": "Estimate", "data_type": "numeric", "is_required": true,
+                              "is_hidden": false });
+        let (_, renamed) = send(&app, "PUT", &attribute_uri, Some(renamed)).await;
+        assert_eq!(renamed["key"], "story_points");
+        let rekeyed = json!({ "name": "Estimate", "key": "estimate", "data_type": "numeric",
+                              "is_required": true, "is_hidden": false });
+        let (status, _) = send(&app, "PUT", &attribute_uri, Some(rekeyed)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+        let (_, item) = send(&app, "GET", &item_uri, None).await;
+        assert_eq!(item["work_attributes"][0]["name"], "Estimate");
+        assert_eq!(item["work_attributes"][0]["value"], "3");
+
+        let clash = json!({ "name": "estimate", "data_type": "string", "is_required": false,
+                            "is_hidden": false });
+        let uri = format!("/worktypes/{}/attributes", id);
+        let (status, body) = send(&app, "POST", &uri, Some(clash)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("Estimate"));
+        let same_key = json!({ "name": "Criteria", "key": "criteria", "data_type": "string",
+                               "is_required": false, "is_hidden": false });
+        let (status, _) = send(&app, "POST", &uri, Some(same_key)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_worktype_status_lifecycle() {
+        let app = setup().await;
+        let mut draft = bug_worktype();
+        draft["status"] = json!("draft");
+        let (status, bug) = send(&app, "POST", "/worktypes", Some(draft)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(bug["status"], "draft");
+        let id = bug["id"].as_str().unwrap();
+        let items_uri = format!("/worktypes/{}/items", id);
+        let values = json!({ "values": { "summary": "Login fails" } });
+
+        let (_, listed) = send(&app, "GET", "/worktypes", None).await;
+        assert!(listed.as_array().unwrap().is_empty());
+        let (_, drafts) = send(&app, "GET", "/worktypes?status=draft", None).await;
+        assert_eq!(drafts[0]["id"], bug["id"]);
+        let (status, _) = send(&app, "POST", &items_uri, Some(values.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = send(&app, "POST", &format!("/worktypes/{}/deprecate", id), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let child = json!({ "title": "Regression", "description": null, "parent_id": id,
+                            "attributes": [] });
+        let (status, _) = send(&app, "POST", "/worktypes", Some(child)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, published) =
+            send(&app, "POST", &format!("/worktypes/{}/publish", id), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(published["status"], "published");
+        let (status, item) = send(&app, "POST", &items_uri, Some(values.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (_, deprecated) =
+            send(&app, "POST", &format!("/worktypes/{}/deprecate", id), None).await;
+        assert_eq!(deprecated["status"], "deprecated");
+        let (status, _) = send(&app, "POST", &items_uri, Some(values.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+        let (status, _) = send(&app, "PUT", &item_uri, Some(values)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, listed) = send(&app, "GET", "/worktypes", None).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+
+        let mut update = bug_worktype();
+        update["status"] = json!("published");
+        let (status, _) = send(&app, "PUT", &format!("/worktypes/{}", id), Some(update)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_attribute_change_impact() {
+        let app = setup().await;
+        let (_, bug) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = bug["id"].as_str().unwrap();
+        let severity = bug["attributes"][1]["id"].as_str().unwrap();
+        let items_uri = format!("/worktypes/{}/items", id);
+        let impact_uri = format!("/worktypes/{}/impact", id);
+
+        let mut items = Vec::new();
+        for values in [
+            json!({ "summary": "Crash", "severity": "4" }),
+            json!({ "summary": "Typo", "severity": "2" }),
+            json!({ "summary": "Slow" }),
+        ] {
+            let (_, item) = send(&app, "POST", &items_uri, Some(json!({ "values": values }))).await;
+            items.push(item["id"].clone());
+        }
+
+        let change = json!({ "action": "update", "attribute_id": severity, "attribute": {
+            "name": "Severity", "data_type": "numeric", "is_required": true, "is_hidden": false,
+            "min_value": 1.0, "max_value": 3.0 } });
+        let (status, report) = send(&app, "POST", &impact_uri, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["checked"], 3);
+        assert_eq!(report["affected"], 2);
+        assert_eq!(report["missing"]["count"], 1);
+        assert_eq!(report["missing"]["sample"], json!([items[2]]));
+        assert_eq!(report["invalid"]["sample"], json!([items[0]]));
+        assert_eq!(report["discarded"]["count"], 0);
+
+        let change = json!({ "action": "delete", "attribute_id": severity });
+        let (_, report) = send(&app, "POST", &impact_uri, Some(change)).await;
+        assert_eq!(report["discarded"]["count"], 2);
+        assert_eq!(report["affected"], 2);
+
+        let (_, unchanged) = send(&app, "GET", &format!("/worktypes/{}", id), None).await;
+        assert_eq!(unchanged["attributes"], bug["attributes"]);
+        let change =
+            json!({ "action": "delete", "attribute_id": "00000000-0000-0000-0000-000000000000" });
+        let (status, _) = send(&app, "POST", &impact_uri, Some(change)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_attribute_data_type() {
+        let app = setup().await;
+        let estimate = json!({ "title": "Task", "description": null, "attributes": [
+            { "name": "Estimate", "data_type": "string", "is_required": false, "is_hidden": false }
+        ] });
+        let (_, task) = send(&app, "POST", "/worktypes", Some(estimate)).await;
+        let id = task["id"].as_str().unwrap();
+        let attribute_id = task["attributes"][0]["id"].as_str().unwrap();
+        let migrate_uri = format!("/worktypes/{}/attributes/{}/migrate", id, attribute_id);
+
+        let mut items = Vec::new();
+        for estimate in ["3", "4,5", "a lot"] {
+            let values = json!({ "values": { "estimate": estimate } });
+            let (_, item) = send(
+                &app,
+                "POST",
+                &format!("/worktypes/{}/items", id),
+                Some(values),
+            )
+            .await;
+            items.push(format!("/items/{}", item["id"].as_str().unwrap()));
+        }
+
+        let mut request = json!({ "name": "Estimate", "data_type": "numeric", "is_required": false,
+                                  "is_hidden": false, "on_failure": "abort" });
+        let (status, _) = send(&app, "POST", &migrate_uri, Some(request.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (_, unchanged) = send(&app, "GET", &format!("/worktypes/{}", id), None).await;
+        assert_eq!(unchanged["attributes"][0]["data_type"], "string");
+
+        request["on_failure"] = json!("default");
+        request["default_value"] = json!("0");
+        let (status, report) = send(&app, "POST", &migrate_uri, Some(request)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["attribute"]["data_type"], "numeric");
+        assert_eq!(report["converted"], 2);
+        assert_eq!(report["failed"]["count"], 1);
+
+        let mut migrated = Vec::new();
+        for uri in &items {
+            let (_, item) = send(&app, "GET", uri, None).await;
+            migrated.push(item["work_attributes"][0]["value"].clone());
+        }
+        assert_eq!(migrated, vec![json!("3"), json!("4.5"), json!("0")]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_work_items() {
+        let app = setup().await;
+        let incident = json!({ "title": "Incident", "description": null, "attributes": [
+            { "name": "Severity", "data_type": "numeric", "is_required": false, "is_hidden": false },
+            { "name": "Reported By", "data_type": "string", "is_required": false, "is_hidden": false },
+            { "name": "Tags", "data_type": "multi_select", "options": ["ui", "api"],
+              "is_required": false, "is_hidden": false },
+            { "name": "Due", "data_type": "date", "is_required": false, "is_hidden": false }
+        ] });
+        let (_, incident) = send(&app, "POST", "/worktypes", Some(incident)).await;
+        let id = incident["id"].as_str().unwrap();
+        let workflow = json!({ "initial_status": "open", "statuses": [
+            { "name": "open", "category": "todo", "transitions": ["closed"] },
+            { "name": "closed", "category": "done", "transitions": [] }
+        ] });
+        send(
+            &app,
+            "PUT",
+            &format!("/worktypes/{}/workflow", id),
+            Some(workflow),
+        )
+        .await;
+
+        let mut items = Vec::new();
+        for values in [
+            json!({ "severity": "4", "reported_by": "Ana Perez", "tags": "ui", "due": "2024-01-10" }),
+            json!({ "severity": "2", "reported_by": "Mariana", "tags": "ui, api" }),
+            json!({ "severity": "5", "reported_by": "Luis" }),
+        ] {
+            let uri = format!("/worktypes/{}/items", id);
+            let (_, item) = send(&app, "POST", &uri, Some(json!({ "values": values }))).await;
+            items.push(item["id"].clone());
+        }
+        let transition_uri = format!("/items/{}/transition", items[1].as_str().unwrap());
+        send(
+            &app,
+            "POST",
+            &transition_uri,
+            Some(json!({ "to": "closed" })),
+        )
+        .await;
+
+        // Codifica el filtro para la URL
+        let filter = |expression: &str| {
+            let encoded: String = expression
+                .bytes()
+                .map(|b| match b {
+                    b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'.' | b'-' => {
+                        (b as char).to_string()
+                    }
+                    _ => format!("%{:02X}", b),
+                })
+                .collect();
+            format!("/worktypes/{}/items?filter={}", id, encoded)
+        };
+        let cases = [
+            (
+                r#"severity >= 3 AND status = "open" AND reported_by ~ "ana""#,
+                vec![0],
+            ),
+            (r#"reported_by ~ "ANA""#, vec![0, 1]),
+            (r#"tags ~ "api" OR severity > 4"#, vec![1, 2]),
+            (r#"NOT (tags = null) AND status != "closed""#, vec![0]),
+            (r#"due < "2024-02-01""#, vec![0]),
+        ];
+        for (expression, expected) in cases {
+            let (status, found) = send(&app, "GET", &filter(expression), None).await;
+            assert_eq!(status, StatusCode::OK, "{}", expression);
+            let found: Vec<Value> = found
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|i| i["id"].clone())
+                .collect();
+            let expected: Vec<Value> = expected.into_iter().map(|i| items[i].clone()).collect();
+            assert_eq!(found, expected, "{}", expression);
+        }
+
+        for invalid in [
+            r#"severity ~ "4""#,
+            "priority > 1",
+            r#"due > "tomorrow""#,
+            "severity >",
+        ] {
+            let (status, _) = send(&app, "GET", &filter(invalid), None).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_work_items() {
+        let app = setup().await;
+        let (_, bug) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = bug["id"].as_str().unwrap();
+        let uri = format!("/worktypes/{}/items", id);
+
+        let mut items = Vec::new();
+        for values in [
+            json!({ "summary": "Login page crashes", "description": "The login form crashes on submit" }),
+            json!({ "summary": "Crash when saving the profile", "description": "Unexpected login prompt", "severity": "3" }),
+            json!({ "summary": "Slow dashboard" }),
+        ] {
+            let (_, item) = send(&app, "POST", &uri, Some(json!({ "values": values }))).await;
+            items.push(item["id"].clone());
+        }
+
+        let (status, hits) = send(&app, "GET", &format!("{}?q=login", uri), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 2);
+        // El que repite la palabra es el mas relevante
+        assert_eq!(hits[0]["id"], items[0]);
+        assert_eq!(hits[1]["id"], items[1]);
+        assert!(hits[0]["rank"].as_f64().unwrap() > hits[1]["rank"].as_f64().unwrap());
+        assert_eq!(
+            hits[0]["highlights"]["summary"],
+            "<mark>Login</mark> page crashes"
+        );
+        assert!(hits[1]["highlights"].get("summary").is_none());
+
+        // Todas las palabras deben aparecer, y se combina con el filtro
+        let (_, hits) = send(&app, "GET", &format!("{}?q=crashes%20login", uri), None).await;
+        assert_eq!(hits.as_array().unwrap().len(), 1);
+        let (_, hits) = send(
+            &app,
+            "GET",
+            &format!("{}?q=login&filter=severity%20%3D%203", uri),
+            None,
+        )
+        .await;
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], items[1]);
+
+        let (status, _) = send(&app, "GET", &format!("{}?q=%20!", uri), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // El texto se escapa como HTML antes de marcar las palabras
+        let values = json!({ "values": { "summary": "Checkout <script>x</script> & <mark>" } });
+        send(&app, "POST", &uri, Some(values)).await;
+        let (_, hits) = send(&app, "GET", &format!("{}?q=checkout", uri), None).await;
+        assert_eq!(
+            hits[0]["highlights"]["summary"],
+            "<mark>Checkout</mark> &lt;script&gt;x&lt;/script&gt; &amp; &lt;mark&gt;"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_work_item_history() {
+        let app = setup().await;
+        let (_, bug) = send(&app, "POST", "/worktypes", Some(bug_worktype())).await;
+        let id = bug["id"].as_str().unwrap();
+        let workflow = json!({ "initial_status": "open", "statuses": [
+            { "name": "open", "category": "todo", "transitions": ["closed"] },
+            { "name": "closed", "category": "done", "transitions": [] }
+        ] });
+        send(
+            &app,
+            "PUT",
+            &format!("/worktypes/{}/workflow", id),
+            Some(workflow),
+        )
+        .await;
+
+        // La creacion indica quien la hace en la cabecera X-User
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/worktypes/{}/items", id))
+            .header("Content-Type", "application/json")
+            .header("X-User", "ana@example.com")
+            .body(Body::from(
+                json!({ "values": { "summary": "Crash", "severity": "2" } }).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let item: Value = serde_json::from_slice(&bytes).unwrap();
+        let item_uri = format!("/items/{}", item["id"].as_str().unwrap());
+
+        let values = json!({ "values": { "summary": "Crash on login" } });
+        send(&app, "PUT", &item_uri, Some(values)).await;
+        let transition_uri = format!("{}/transition", item_uri);
+        send(
+            &app,
+            "POST",
+            &transition_uri,
+            Some(json!({ "to": "closed" })),
+        )
+        .await;
+
+        let history_uri = format!("{}/history", item_uri);
+        let (status, history) = send(&app, "GET", &history_uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(history["total"], 3);
+        assert_eq!(history["page"], 1);
+        let entries = history["entries"].as_array().unwrap();
+        let actions: Vec<&str> = entries
+            .iter()
+            .map(|e| e["action"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, ["transitioned", "updated", "created"]);
+
+        assert_eq!(
+            entries[0]["status"],
+            json!({ "from": "open", "to": "closed" })
+        );
+        assert_eq!(entries[0]["attributes"], json!([]));
+        assert_eq!(entries[0]["changed_by"], Value::Null);
+        assert_eq!(entries[1]["status"], Value::Null);
+        assert_eq!(
+            entries[1]["attributes"],
+            json!([
+                { "key": "summary", "name": "Summary", "old_value": "Crash", "new_value": "Crash on login" },
+                { "key": "severity", "name": "Severity", "old_value": "2", "new_value": null }
+            ])
+        );
+        assert_eq!(entries[2]["changed_by"], "ana@example.com");
+        assert_eq!(entries[2]["status"], json!({ "from": null, "to": "open" }));
+        assert_eq!(entries[2]["attributes"].as_array().unwrap().len(), 2);
+
+        let (_, page) = send(
+            &app,
+            "GET",
+            &format!("{}?page=2&per_page=2", history_uri),
+            None,
+        )
+        .await;
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(page["entries"][0]["action"], "created");
+
+        // Quitar un atributo borra sus valores, y el cambio queda sin autor
+        let values = json!({ "values": { "summary": "Crash on login", "severity": "4" } });
+        send(&app, "PUT", &item_uri, Some(values)).await;
+        let severity = bug["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|attribute| attribute["key"] == "severity")
+            .unwrap();
+        let severity_uri = format!(
+            "/worktypes/{}/attributes/{}",
+            id,
+            severity["id"].as_str().unwrap()
+        );
+        let (status, _) = send(&app, "DELETE", &severity_uri, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (_, history) = send(&app, "GET", &history_uri, None).await;
+        assert_eq!(history["total"], 5);
+        assert_eq!(history["entries"][0]["action"], "updated");
+        assert_eq!(history["entries"][0]["changed_by"], Value::Null);
+        assert_eq!(
+            history["entries"][0]["attributes"],
+            json!([{ "key": "severity", "name": "Severity", "old_value": "4", "new_value": null }])
+        );
+
+        let (status, _) = send(&app, "GET", &format!("{}?per_page=0", history_uri), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        send(&app, "DELETE", &item_uri, None).await;
+        let (status, _) = send(&app, "GET", &history_uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
 }
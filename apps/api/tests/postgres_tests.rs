@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use api::AppModules;
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+        Router,
+    };
+    use companies::CompaniesModule;
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use worktypes::WorktypesModule;
+
+    // Estas pruebas necesitan una base de datos migrada en DATABASE_URL; sin
+    // ella no hacen nada
+    async fn setup() -> Option<(Router, PgPool)> {
+        let database_url = std::env::var("DATABASE_URL").ok()?;
+        let companies = CompaniesModule::with_provider(companies::RepositoryProvider::Memory)
+            .await
+            .unwrap();
+        let worktypes = WorktypesModule::with_provider(worktypes::RepositoryProvider::Postgres(
+            database_url.clone(),
+        ))
+        .await
+        .unwrap();
+        let companies = companies.with_hooks(worktypes.company_hooks());
+        let pool = PgPool::connect(&database_url).await.unwrap();
+        let app = AppModules {
+            companies,
+            worktypes,
+        }
+        .combined_routes();
+        Some((app, pool))
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("Content-Type", "application/json");
+        let request = match body {
+            Some(body) => request.body(Body::from(body.to_string())).unwrap(),
+            None => request.body(Body::empty()).unwrap(),
+        };
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    // El documento JSONB del work item y si su search_vector encuentra `word`
+    async fn document(pool: &PgPool, item_id: &str, word: &str) -> (Value, bool) {
+        sqlx::query_as(
+            "SELECT attributes, search_vector @@ plainto_tsquery(search_language::regconfig, $2)
+             FROM work_item WHERE id = $1::uuid",
+        )
+        .bind(item_id)
+        .bind(word)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unknown_company_has_no_worktypes() {
+        let Some((app, _)) = setup().await else {
+            return;
+        };
+
+        let (status, _) = send(&app, "GET", "/companies/unknown/worktypes", None).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_work_item_documents_follow_changes() {
+        let Some((app, pool)) = setup().await else {
+            return;
+        };
+        // Claves unicas para no chocar con otros datos ni con otros indices
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let summary_key = format!("summary_{}", suffix);
+        let weight_key = format!("weight_{}", suffix);
+        let request = json!({
+            "title": format!("Document {}", suffix),
+            "description": null,
+            "attributes": [
+                { "name": format!("Summary {}", suffix), "data_type": "string",
+                  "is_required": true, "is_hidden": false },
+                { "name": format!("Weight {}", suffix), "data_type": "numeric",
+                  "is_required": false, "is_hidden": false }
+            ]
+        });
+        let (status, work_type) = send(&app, "POST", "/worktypes", Some(request)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = work_type["id"].as_str().unwrap();
+        let weight_id = work_type["attributes"][1]["id"].as_str().unwrap();
+
+        let values = json!({ "values": { &summary_key: "Broken pipeline", &weight_key: "3" } });
+        let uri = format!("/worktypes/{}/items", id);
+        let (status, item) = send(&app, "POST", &uri, Some(values)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let item_id = item["id"].as_str().unwrap();
+        let (attributes, found) = document(&pool, item_id, "pipeline").await;
+        assert_eq!(attributes[&summary_key], "Broken pipeline");
+        assert_eq!(attributes[&weight_key], 3);
+        assert!(found);
+
+        let values = json!({ "values": { &summary_key: "Flaky deployment", &weight_key: "5" } });
+        let item_uri = format!("/items/{}", item_id);
+        let (status, _) = send(&app, "PUT", &item_uri, Some(values)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (attributes, found) = document(&pool, item_id, "pipeline").await;
+        assert_eq!(attributes[&summary_key], "Flaky deployment");
+        assert_eq!(attributes[&weight_key], 5);
+        assert!(!found);
+        assert!(document(&pool, item_id, "deployment").await.1);
+
+        let renamed = json!({ "name": "Effort", "data_type": "numeric",
+                              "is_required": false, "is_hidden": false });
+        let uri = format!("/worktypes/{}/attributes/{}", id, weight_id);
+        let (status, _) = send(&app, "PUT", &uri, Some(renamed)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (attributes, found) = document(&pool, item_id, "deployment").await;
+        assert_eq!(attributes[&weight_key], 5);
+        assert!(found);
+
+        // El indice de la clave numerica se crea en segundo plano
+        let index = format!("idx_work_item_value_{}", weight_key);
+        let mut valid = false;
+        for _ in 0..50 {
+            valid = sqlx::query_scalar(
+                "SELECT i.indisvalid FROM pg_index i JOIN pg_class c ON c.oid = i.indexrelid
+                 WHERE c.relname = $1",
+            )
+            .bind(&index)
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+            .unwrap_or(false);
+            if valid {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(valid);
+
+        send(&app, "DELETE", &item_uri, None).await;
+        send(&app, "DELETE", &format!("/worktypes/{}", id), None).await;
+        sqlx::query(&format!("DROP INDEX IF EXISTS {}", index))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -2,10 +2,22 @@ use dotenvy::dotenv;
 use std::env;
 use tracing_subscriber::EnvFilter;
 
+// Donde guardan sus datos los modulos
+#[derive(Debug, Clone)]
+pub enum Storage {
+    Postgres(String),
+    // Con REPOSITORY=memory: sin base de datos, para desarrollo y pruebas
+    Memory,
+}
+
 #[derive(Debug)]
 pub struct Config {
-    pub database_url: String,
+    pub storage: Storage,
     pub port: u16,
+    // Lista JSON con los atributos de sistema de los tipos de trabajo
+    pub system_attributes: Option<String>,
+    // Idioma de la busqueda de texto en los work items: `english` o `spanish`
+    pub search_language: Option<String>,
 }
 
 impl Config {
@@ -19,14 +31,32 @@ impl Config {
             .pretty()
             .init();
 
-        let database_url =
-            env::var("DATABASE_URL").expect("Missing DATABASE_URL in environment or .env file.");
+        // DATABASE_URL solo hace falta si los datos van a PostgreSQL
+        let storage = match env::var("REPOSITORY").ok().as_deref() {
+            Some("memory") => Storage::Memory,
+            None | Some("postgres") => Storage::Postgres(
+                env::var("DATABASE_URL")
+                    .expect("Missing DATABASE_URL in environment or .env file."),
+            ),
+            Some(other) => panic!(
+                "Unknown REPOSITORY '{}': expected 'postgres' or 'memory'.",
+                other
+            ),
+        };
 
         let port = env::var("PORT")
             .ok()
             .and_then(|p| p.parse().ok())
             .unwrap_or(3000);
 
-        Self { database_url, port }
+        let system_attributes = env::var("SYSTEM_ATTRIBUTES").ok();
+        let search_language = env::var("SEARCH_LANGUAGE").ok();
+
+        Self {
+            storage,
+            port,
+            system_attributes,
+            search_language,
+        }
     }
 }
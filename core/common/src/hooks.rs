@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+
+use crate::error::Result;
+
+// Lo que otros modulos hacen con sus propios datos cuando el modulo de
+// compañias duplica o elimina una compañia. La aplicacion los registra al
+// montar los modulos, asi que ninguno depende de otro.
+#[async_trait]
+pub trait CompanyHooks: Send + Sync {
+    async fn company_created(&self, company_id: &str) -> Result<()>;
+    async fn company_duplicated(&self, from_company_id: &str, to_company_id: &str) -> Result<()>;
+    async fn company_deleted(&self, company_id: &str) -> Result<()>;
+}
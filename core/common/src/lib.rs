@@ -1,5 +1,6 @@
 pub mod config;
 pub mod error;
+pub mod hooks;
 pub mod modules;
 pub mod repositories;
 pub mod server;
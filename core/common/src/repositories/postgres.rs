@@ -31,7 +31,7 @@ impl PostgresRepository {
             .map_err(AppError::Database)?;
 
         // Ejecutar cada sentencia individualmente
-        for stmt in sql_query.split(';') {
+        for stmt in split_statements(sql_query) {
             let trimmed = stmt.trim();
             if !trimmed.is_empty() {
                 tracing::info!(sql = %trimmed, "Ejecutando sentencia SQL");
@@ -47,3 +47,48 @@ impl PostgresRepository {
         })
     }
 }
+
+// Separa las sentencias por `;`, salvo los que van dentro de un cuerpo entre
+// `$$`, como el de un bloque `DO`
+fn split_statements(sql_query: &str) -> Vec<&str> {
+    let mut statements: Vec<&str> = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    let mut chars = sql_query.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '$' if chars.peek().map(|(_, next)| *next) == Some('$') => {
+                chars.next();
+                quoted = !quoted;
+            }
+            ';' if !quoted => {
+                statements.push(&sql_query[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(&sql_query[start..]);
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_statements;
+
+    #[test]
+    fn test_split_statements_keeps_dollar_quoted_bodies() {
+        let sql = "CREATE TABLE a (id INT); DO $$ BEGIN PERFORM 1; END $$; SELECT 1";
+
+        let statements: Vec<&str> = split_statements(sql).into_iter().map(str::trim).collect();
+
+        assert_eq!(
+            statements,
+            [
+                "CREATE TABLE a (id INT)",
+                "DO $$ BEGIN PERFORM 1; END $$",
+                "SELECT 1"
+            ]
+        );
+    }
+}
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS work_item (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS work_attribute_item (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    work_item_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+    attribute_type_id UUID NOT NULL REFERENCES work_attribute_type(id) ON DELETE CASCADE,
+    value TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_item_work_type ON work_item(work_type_id);
+CREATE INDEX IF NOT EXISTS idx_work_attribute_item_work_item ON work_attribute_item(work_item_id);
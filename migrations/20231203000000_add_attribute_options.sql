@@ -0,0 +1 @@
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS options TEXT[];
@@ -0,0 +1,6 @@
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS min_value DOUBLE PRECISION;
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS max_value DOUBLE PRECISION;
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS min_length INTEGER;
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS max_length INTEGER;
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS pattern TEXT;
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS default_value TEXT;
@@ -0,0 +1,43 @@
+ALTER TABLE work_type ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS work_type_version (
+    work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    attributes JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (work_type_id, version)
+);
+
+ALTER TABLE work_item ADD COLUMN IF NOT EXISTS schema_version INTEGER NOT NULL DEFAULT 1;
+
+-- Version inicial para los tipos de trabajo creados antes del versionado
+INSERT INTO work_type_version (work_type_id, version, attributes, created_at)
+SELECT
+    wt.id,
+    wt.version,
+    COALESCE(
+        (
+            SELECT jsonb_agg(jsonb_build_object(
+                'id', wat.id,
+                'name', wat.name,
+                'data_type', wat.data_type,
+                'options', wat.options,
+                'is_required', wat.is_required,
+                'is_hidden', wat.is_hidden,
+                'min_value', wat.min_value,
+                'max_value', wat.max_value,
+                'min_length', wat.min_length,
+                'max_length', wat.max_length,
+                'pattern', wat.pattern,
+                'default_value', wat.default_value,
+                'created_at', wat.created_at,
+                'updated_at', wat.updated_at
+            ))
+            FROM work_attribute_type wat
+            WHERE wat.work_type_id = wt.id
+        ),
+        '[]'::jsonb
+    ),
+    wt.updated_at
+FROM work_type wt
+WHERE NOT EXISTS (SELECT 1 FROM work_type_version v WHERE v.work_type_id = wt.id);
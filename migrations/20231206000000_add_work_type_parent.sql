@@ -0,0 +1,3 @@
+ALTER TABLE work_type ADD COLUMN IF NOT EXISTS parent_id UUID REFERENCES work_type(id);
+
+CREATE INDEX IF NOT EXISTS idx_work_type_parent ON work_type(parent_id);
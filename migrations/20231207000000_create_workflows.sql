@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS work_type_status (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    category VARCHAR(20) NOT NULL,
+    is_initial BOOLEAN NOT NULL DEFAULT FALSE,
+    transitions TEXT[] NOT NULL DEFAULT '{}',
+    position INTEGER NOT NULL,
+    UNIQUE (work_type_id, name)
+);
+
+ALTER TABLE work_item ADD COLUMN IF NOT EXISTS status VARCHAR(100);
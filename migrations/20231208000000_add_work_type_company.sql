@@ -0,0 +1,2 @@
+ALTER TABLE work_type ADD COLUMN IF NOT EXISTS company_id TEXT;
+CREATE INDEX IF NOT EXISTS idx_work_type_company ON work_type(company_id);
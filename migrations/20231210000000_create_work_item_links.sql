@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS link_type (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    outward_name VARCHAR(100) NOT NULL,
+    inward_name VARCHAR(100) NOT NULL,
+    is_hierarchical BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_link_type_name ON link_type(LOWER(name));
+
+CREATE TABLE IF NOT EXISTS work_item_link (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    link_type_id UUID NOT NULL REFERENCES link_type(id) ON DELETE CASCADE,
+    source_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+    target_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (link_type_id, source_id, target_id),
+    CHECK (source_id <> target_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_work_item_link_source ON work_item_link(source_id);
+CREATE INDEX IF NOT EXISTS idx_work_item_link_target ON work_item_link(target_id);
@@ -0,0 +1,13 @@
+-- Orden explicito de los atributos propios de cada tipo de trabajo
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS position INTEGER;
+
+-- Los atributos existentes conservan el orden en que se crearon
+UPDATE work_attribute_type wat
+SET position = ordered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY work_type_id ORDER BY created_at, id) - 1 AS position
+    FROM work_attribute_type
+) ordered
+WHERE wat.id = ordered.id AND wat.position IS NULL;
+
+ALTER TABLE work_attribute_type ALTER COLUMN position SET NOT NULL;
@@ -0,0 +1,2 @@
+-- Atributos de sistema (Summary, Description...), que no se pueden eliminar
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS is_system BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,31 @@
+-- Clave estable de cada atributo, unica dentro de su tipo de trabajo
+ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS key VARCHAR(100);
+
+-- Los atributos existentes reciben una clave derivada de su nombre. Si dos
+-- dan la misma, la segunda y siguientes llevan un sufijo numerico.
+UPDATE work_attribute_type wat
+SET key = LEFT(derived.slug || CASE WHEN derived.n > 1 THEN '_' || derived.n ELSE '' END, 100)
+FROM (
+    SELECT id, slug, ROW_NUMBER() OVER (PARTITION BY work_type_id, slug ORDER BY position, id) AS n
+    FROM (
+        SELECT id, work_type_id, position,
+            CASE
+                WHEN cleaned = '' THEN 'attribute'
+                WHEN cleaned ~ '^[0-9]' THEN 'attr_' || cleaned
+                ELSE cleaned
+            END AS slug
+        FROM (
+            SELECT id, work_type_id, position,
+                TRIM(BOTH '_' FROM REGEXP_REPLACE(
+                    TRANSLATE(LOWER(name), 'áàäâéèëêíìïîóòöôúùüûñç', 'aaaaeeeeiiiioooouuuunc'),
+                    '[^a-z0-9]+', '_', 'g'
+                )) AS cleaned
+            FROM work_attribute_type
+        ) cleaned_names
+    ) slugs
+) derived
+WHERE wat.id = derived.id AND wat.key IS NULL;
+
+ALTER TABLE work_attribute_type ALTER COLUMN key SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_work_attribute_type_key ON work_attribute_type(work_type_id, key);
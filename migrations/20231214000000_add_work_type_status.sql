@@ -0,0 +1,2 @@
+-- Ciclo de vida de los tipos de trabajo: draft, published o deprecated
+ALTER TABLE work_type ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published';
@@ -0,0 +1,2 @@
+-- Las migraciones de tipo de dato recorren los valores de un atributo
+CREATE INDEX IF NOT EXISTS idx_work_attribute_item_attribute_type ON work_attribute_item(attribute_type_id);
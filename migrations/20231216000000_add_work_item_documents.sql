@@ -0,0 +1,51 @@
+-- Documento JSONB tipado con los valores de cada work item, por clave de atributo
+ALTER TABLE work_item ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- Los numeros, booleanos y selecciones multiples se guardan con su tipo JSON;
+-- los valores que no encajan con su tipo de dato se quedan como texto
+CREATE OR REPLACE FUNCTION work_item_typed_value(data_type TEXT, value TEXT) RETURNS JSONB
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT CASE
+        WHEN (data_type = 'numeric' OR data_type LIKE 'decimal%')
+            AND btrim(value) ~ '^[-+]?([0-9]+[.]?[0-9]*|[.][0-9]+)([eE][-+]?[0-9]+)?$'
+            THEN to_jsonb(btrim(value)::numeric)
+        WHEN data_type = 'boolean' AND lower(btrim(value)) IN ('true', 'false')
+            THEN to_jsonb(lower(btrim(value))::boolean)
+        WHEN data_type = 'multi_select'
+            THEN COALESCE((SELECT jsonb_agg(btrim(part)) FROM regexp_split_to_table(value, ',') AS part
+                           WHERE btrim(part) <> ''), '[]'::jsonb)
+        ELSE to_jsonb(value)
+    END
+$$;
+
+CREATE INDEX IF NOT EXISTS idx_work_item_attributes ON work_item USING GIN (attributes jsonb_path_ops);
+
+UPDATE work_item wi
+SET attributes = (
+    SELECT jsonb_object_agg(wat.key, work_item_typed_value(wat.data_type, wai.value))
+    FROM work_attribute_item wai
+    JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+    WHERE wai.work_item_id = wi.id
+)
+WHERE wi.attributes = '{}'::jsonb
+  AND EXISTS (SELECT 1 FROM work_attribute_item wai WHERE wai.work_item_id = wi.id);
+
+-- Indices de expresion para comparar y ordenar los atributos numericos y de fecha
+DO $$
+DECLARE
+    attribute_key TEXT;
+    index_name TEXT;
+BEGIN
+    FOR attribute_key IN
+        SELECT DISTINCT key FROM work_attribute_type
+        WHERE data_type IN ('numeric', 'date', 'datetime') OR data_type LIKE 'decimal%'
+    LOOP
+        index_name := CASE
+            WHEN length(attribute_key) <= 43 THEN 'idx_work_item_value_' || attribute_key
+            ELSE 'idx_work_item_value_' || left(attribute_key, 30) || '_' || left(md5(attribute_key), 12)
+        END;
+        EXECUTE format('CREATE INDEX IF NOT EXISTS %I ON work_item ((attributes -> %L))',
+                       index_name, attribute_key);
+    END LOOP;
+END
+$$;
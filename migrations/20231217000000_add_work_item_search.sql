@@ -0,0 +1,5 @@
+-- Busqueda de texto en los atributos de texto de los work items. El idioma con
+-- el que se indexo cada uno permite reindexarlo al cambiar la configuracion.
+ALTER TABLE work_item ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+ALTER TABLE work_item ADD COLUMN IF NOT EXISTS search_language VARCHAR(20);
+CREATE INDEX IF NOT EXISTS idx_work_item_search ON work_item USING GIN (search_vector);
@@ -0,0 +1,13 @@
+-- Historial de cambios de los work items. Las entradas no se modifican: solo
+-- desaparecen con su work item.
+CREATE TABLE IF NOT EXISTS work_item_history (
+    id UUID PRIMARY KEY,
+    work_item_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+    action VARCHAR(20) NOT NULL,
+    changed_by VARCHAR(255),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    status_change JSONB,
+    attribute_changes JSONB NOT NULL DEFAULT '[]'::jsonb
+);
+CREATE INDEX IF NOT EXISTS idx_work_item_history_item ON work_item_history(work_item_id, changed_at DESC);
+CREATE OR REPLACE RULE work_item_history_immutable AS ON UPDATE TO work_item_history DO INSTEAD NOTHING;
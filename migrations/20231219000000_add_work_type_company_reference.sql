@@ -0,0 +1,9 @@
+-- Los tipos de trabajo propios de una compañia se borran con ella. Los que
+-- apuntan a una compañia que ya no existe no se pueden listar y se quitan.
+DELETE FROM work_type
+WHERE company_id IS NOT NULL AND company_id NOT IN (SELECT id FROM company);
+
+ALTER TABLE work_type
+    DROP CONSTRAINT IF EXISTS work_type_company_id_fkey,
+    ADD CONSTRAINT work_type_company_id_fkey
+        FOREIGN KEY (company_id) REFERENCES company(id) ON DELETE CASCADE;
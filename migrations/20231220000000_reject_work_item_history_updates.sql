@@ -0,0 +1,13 @@
+-- Un UPDATE sobre el historial falla con un error en vez de ignorarse en
+-- silencio como con la regla anterior
+CREATE OR REPLACE FUNCTION work_item_history_immutable() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'work item history entries cannot be modified';
+END
+$$ LANGUAGE plpgsql;
+
+DROP RULE IF EXISTS work_item_history_immutable ON work_item_history;
+
+CREATE OR REPLACE TRIGGER work_item_history_immutable
+    BEFORE UPDATE ON work_item_history
+    FOR EACH ROW EXECUTE FUNCTION work_item_history_immutable();
@@ -13,7 +13,6 @@ use super::{
 };
 use common::error::AppError;
 
-
 pub async fn list_companies(
     State(repository): State<Arc<dyn CompanyRepositoryTrait + Send + Sync>>,
     Query(query): Query<CompanyQuery>,
@@ -73,3 +72,16 @@ pub async fn duplicate_company(
         Err(e) => e.into_response(),
     }
 }
+
+pub async fn delete_company(
+    State(repository): State<Arc<dyn CompanyRepositoryTrait + Send + Sync>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match repository.delete(&id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => {
+            AppError::NotFound(format!("Compañía con ID {} no encontrada", id)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
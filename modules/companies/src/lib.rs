@@ -4,39 +4,78 @@ mod repositories;
 mod routes;
 use async_trait::async_trait;
 use axum::Router;
-use common::{config::Config, modules::Module, repositories::postgres::PostgresRepository};
+use common::{
+    config::Config, hooks::CompanyHooks, modules::Module,
+    repositories::postgres::PostgresRepository,
+};
 use common::{error::AppError, error::Result};
+use repositories::hooked::HookedCompanyRepository;
+use repositories::memory::MemoryCompanyRepository;
 use repositories::repository::CompanyRepositoryTrait;
 use std::sync::Arc;
 
+pub use repositories::repository::RepositoryProvider;
+
 pub struct CompaniesModule {
     repository: Arc<dyn CompanyRepositoryTrait + Send + Sync>,
+    hooks: Vec<Arc<dyn CompanyHooks>>,
 }
 
-#[async_trait]
-impl Module for CompaniesModule {
-    async fn create(config: &Config) -> Result<Self> {
-        match PostgresRepository::new_with_ensured_query(
-            &config.database_url,
-            repositories::postgres::QUERY,
-        )
-        .await
-        {
-            Ok(repo) => {
-                tracing::info!("Módulo de compañías: Conectado a PostgreSQL");
-                let psql_repo = Arc::new(repo) as Arc<dyn CompanyRepositoryTrait + Send + Sync>;
+impl CompaniesModule {
+    pub async fn with_provider(provider: RepositoryProvider) -> Result<Self> {
+        match provider {
+            RepositoryProvider::Memory => {
+                tracing::info!("Módulo de compañías: Usando el repositorio en memoria");
+                let memory_repo = Arc::new(MemoryCompanyRepository::new())
+                    as Arc<dyn CompanyRepositoryTrait + Send + Sync>;
                 Ok(Self {
-                    repository: psql_repo,
+                    repository: memory_repo,
+                    hooks: Vec::new(),
                 })
             }
-            Err(e) => Err(AppError::Internal(format!(
-                "Company Module: Problem connecting to PostgreSQL. Error: {}",
-                e
-            ))),
+            RepositoryProvider::Postgres(database_url) => {
+                match PostgresRepository::new_with_ensured_query(
+                    &database_url,
+                    repositories::postgres::QUERY,
+                )
+                .await
+                {
+                    Ok(repo) => {
+                        tracing::info!("Módulo de compañías: Conectado a PostgreSQL");
+                        let psql_repo =
+                            Arc::new(repo) as Arc<dyn CompanyRepositoryTrait + Send + Sync>;
+                        Ok(Self {
+                            repository: psql_repo,
+                            hooks: Vec::new(),
+                        })
+                    }
+                    Err(e) => Err(AppError::Internal(format!(
+                        "Company Module: Problem connecting to PostgreSQL. Error: {}",
+                        e
+                    ))),
+                }
+            }
         }
     }
 
+    // Otros modulos que guardan datos por compañia los copian o los borran
+    // con ella
+    pub fn with_hooks(mut self, hooks: Arc<dyn CompanyHooks>) -> Self {
+        self.hooks.push(hooks);
+        self
+    }
+}
+
+#[async_trait]
+impl Module for CompaniesModule {
+    async fn create(config: &Config) -> Result<Self> {
+        Self::with_provider(config.storage.clone().into()).await
+    }
+
     fn routes(&self) -> Router {
-        routes::create_routes(self.repository.clone())
+        routes::create_routes(Arc::new(HookedCompanyRepository::new(
+            self.repository.clone(),
+            self.hooks.clone(),
+        )))
     }
 }
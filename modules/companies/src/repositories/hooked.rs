@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::{error::Result, hooks::CompanyHooks};
+
+use crate::models::{Company, CompanyRequest};
+
+use super::repository::CompanyRepositoryTrait;
+
+// Envuelve cualquier repositorio de compañias y avisa a los otros modulos
+// despues de crear, duplicar o eliminar una compañia, sea cual sea el proveedor
+pub struct HookedCompanyRepository {
+    repository: Arc<dyn CompanyRepositoryTrait + Send + Sync>,
+    hooks: Vec<Arc<dyn CompanyHooks>>,
+}
+
+impl HookedCompanyRepository {
+    pub fn new(
+        repository: Arc<dyn CompanyRepositoryTrait + Send + Sync>,
+        hooks: Vec<Arc<dyn CompanyHooks>>,
+    ) -> Self {
+        Self { repository, hooks }
+    }
+}
+
+#[async_trait]
+impl CompanyRepositoryTrait for HookedCompanyRepository {
+    async fn list(&self, name_filter: Option<String>) -> Result<Vec<Company>> {
+        self.repository.list(name_filter).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Company>> {
+        self.repository.get(id).await
+    }
+
+    async fn create(&self, company_req: CompanyRequest) -> Result<Company> {
+        let company = self.repository.create(company_req).await?;
+        for hook in &self.hooks {
+            hook.company_created(&company.id).await?;
+        }
+        Ok(company)
+    }
+
+    async fn update(&self, id: &str, company_req: CompanyRequest) -> Result<Option<Company>> {
+        self.repository.update(id, company_req).await
+    }
+
+    // Si un modulo no puede copiar sus datos, la copia de la compañia se
+    // deshace para no dejarla a medias. Se devuelve siempre el error de la
+    // copia; los fallos al deshacerla solo se registran.
+    async fn duplicate(&self, id: &str) -> Result<Option<Company>> {
+        let company = match self.repository.duplicate(id).await? {
+            Some(company) => company,
+            None => return Ok(None),
+        };
+        for hook in &self.hooks {
+            let copied = match hook.company_created(&company.id).await {
+                Ok(()) => hook.company_duplicated(id, &company.id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = copied {
+                if let Err(cleanup) = self.delete(&company.id).await {
+                    tracing::error!(
+                        "The copy {} of the company {} could not be removed: {:?}",
+                        company.id,
+                        id,
+                        cleanup
+                    );
+                }
+                return Err(e);
+            }
+        }
+        Ok(Some(company))
+    }
+
+    // Los datos de los otros modulos se borran despues de la compañia. En
+    // PostgreSQL ya los borra la clave foranea con ON DELETE CASCADE y el
+    // aviso no hace nada; en memoria el aviso es lo unico que los borra.
+    async fn delete(&self, id: &str) -> Result<bool> {
+        if !self.repository.delete(id).await? {
+            return Ok(false);
+        }
+        for hook in &self.hooks {
+            hook.company_deleted(id).await?;
+        }
+        Ok(true)
+    }
+}
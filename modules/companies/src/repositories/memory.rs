@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 use common::error::Result;
-use uuid::Uuid;
 use std::sync::RwLock;
+use uuid::Uuid;
 
 use crate::models::{Company, CompanyRequest};
 
@@ -69,7 +69,7 @@ impl CompanyRepositoryTrait for MemoryCompanyRepository {
         let mut companies = self.companies.write().unwrap();
 
         if let Some(company) = companies.get_mut(id) {
-            company.name = company_req.name;
+            company.update(company_req.name);
             if let Some(cif) = company_req.cif_number {
                 company.cif_number = Some(cif);
             }
@@ -120,4 +120,9 @@ impl CompanyRepositoryTrait for MemoryCompanyRepository {
 
         Ok(None)
     }
+
+    async fn delete(&self, id: &str) -> Result<bool> {
+        let mut companies = self.companies.write().unwrap();
+        Ok(companies.remove(id).is_some())
+    }
 }
@@ -1,3 +1,4 @@
+pub mod hooked;
 pub mod memory;
 pub mod postgres;
 pub mod repository;
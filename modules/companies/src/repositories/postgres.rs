@@ -1,15 +1,16 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use common::{
+    error::{AppError, Result},
+    repositories::postgres::PostgresRepository,
+};
 use sqlx::query_as;
 use uuid::Uuid;
-use common::{error::{AppError, Result}, repositories::postgres::PostgresRepository};
-
 
 use crate::models::{Company, CompanyRequest};
 
 use super::repository::CompanyRepositoryTrait;
 
-
 pub static QUERY: &str = "
             CREATE TABLE IF NOT EXISTS company (
                 id TEXT PRIMARY KEY,
@@ -183,6 +184,8 @@ impl CompanyRepositoryTrait for PostgresRepository {
 
     async fn duplicate(&self, id: &str) -> Result<Option<Company>> {
         let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
 
         // Primero obtenemos la compañía original
         let original = query_as!(
@@ -194,7 +197,7 @@ impl CompanyRepositoryTrait for PostgresRepository {
             "#,
             id
         )
-        .fetch_optional(&*pool)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(AppError::Database)?;
 
@@ -227,15 +230,30 @@ impl CompanyRepositoryTrait for PostgresRepository {
                 now,
                 now
             )
-            .fetch_one(&*pool)
+            .fetch_one(&mut *tx)
             .await
             .map_err(AppError::Database)?;
 
+            tx.commit().await.map_err(AppError::Database)?;
             Ok(Some(company.into()))
         } else {
             Ok(None)
         }
     }
+
+    async fn delete(&self, id: &str) -> Result<bool> {
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let deleted = sqlx::query!(r#"DELETE FROM Company WHERE id = $1"#, id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(deleted.rows_affected() > 0)
+    }
 }
 
 // Estructura para mapear los resultados de la base de datos
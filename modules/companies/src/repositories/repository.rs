@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use common::config::Storage;
 use common::error::Result;
 
 use crate::models::{Company, CompanyRequest};
@@ -9,6 +10,7 @@ pub trait CompanyRepositoryTrait {
     async fn create(&self, company_req: CompanyRequest) -> Result<Company>;
     async fn update(&self, id: &str, company_req: CompanyRequest) -> Result<Option<Company>>;
     async fn duplicate(&self, id: &str) -> Result<Option<Company>>;
+    async fn delete(&self, id: &str) -> Result<bool>;
 }
 
 // Enum para seleccionar el tipo de repositorio
@@ -16,3 +18,12 @@ pub enum RepositoryProvider {
     Memory,
     Postgres(String),
 }
+
+impl From<Storage> for RepositoryProvider {
+    fn from(storage: Storage) -> Self {
+        match storage {
+            Storage::Postgres(database_url) => RepositoryProvider::Postgres(database_url),
+            Storage::Memory => RepositoryProvider::Memory,
+        }
+    }
+}
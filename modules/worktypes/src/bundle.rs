@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{WorkAttributeType, WorkType, Workflow};
+use crate::requests::{
+    BundleFormat, ConflictPolicy, CreateWorkAttributeType, CreateWorkType, CreateWorkflow,
+    CreateWorkflowStatus,
+};
+use crate::validation::{validate_work_type_request, validate_workflow_request};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+// Definiciones de tipos de trabajo portables entre instalaciones: sin ids ni
+// fechas, y con la herencia expresada por el titulo del padre. Solo se guardan
+// los atributos propios; los heredados vuelven a salir del padre al importar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkTypeBundle {
+    pub version: u32,
+    pub work_types: Vec<BundledWorkType>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledWorkType {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<CreateWorkAttributeType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<CreateWorkflow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Created,
+    Overwritten,
+    Renamed,
+    Skipped,
+}
+
+// Resultado de importar un tipo de trabajo del paquete: `title` es el del
+// paquete y `work_type` el tipo de trabajo con el que ha quedado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportOutcome {
+    pub title: String,
+    pub action: ImportAction,
+    pub work_type: WorkType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParentRef {
+    // Titulo de otro tipo de trabajo del mismo paquete, que se importa antes
+    Bundled(String),
+    Existing(Uuid),
+}
+
+// Paso de una importacion. `existing_id` es el tipo de trabajo que se omite o
+// se sobrescribe; el `parent_id` de `request` se rellena al ejecutar el paso.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedImport {
+    pub title: String,
+    pub action: ImportAction,
+    pub existing_id: Option<Uuid>,
+    pub parent: Option<ParentRef>,
+    pub request: CreateWorkType,
+    pub workflow: CreateWorkflow,
+}
+
+impl WorkTypeBundle {
+    pub fn parse(body: &str, format: BundleFormat) -> Result<Self, AppError> {
+        let parsed = match format {
+            BundleFormat::Json => serde_json::from_str(body).map_err(|e| e.to_string()),
+            BundleFormat::Yaml => serde_yaml::from_str(body).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| AppError::Validation(format!("invalid bundle: {}", e)))
+    }
+
+    pub fn to_yaml(&self) -> Result<String, AppError> {
+        serde_yaml::to_string(self).map_err(|e| AppError::Internal(e.to_string()))
+    }
+}
+
+impl WorkAttributeType {
+    pub fn to_create_request(&self) -> CreateWorkAttributeType {
+        CreateWorkAttributeType {
+            name: self.name.clone(),
+            key: Some(self.key.clone()),
+            data_type: self.data_type,
+            options: self.options.clone(),
+            is_required: self.is_required,
+            is_hidden: self.is_hidden,
+            min_value: self.min_value,
+            max_value: self.max_value,
+            min_length: self.min_length,
+            max_length: self.max_length,
+            pattern: self.pattern.clone(),
+            default_value: self.default_value.clone(),
+            formula: self.formula.clone(),
+        }
+    }
+}
+
+impl Workflow {
+    pub fn to_create_request(&self) -> CreateWorkflow {
+        CreateWorkflow {
+            initial_status: self.initial_status.clone(),
+            statuses: self
+                .statuses
+                .iter()
+                .map(|status| CreateWorkflowStatus {
+                    name: status.name.clone(),
+                    category: status.category,
+                    transitions: status.transitions.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl BundledWorkType {
+    pub fn new(work_type: &WorkType, parent: Option<&WorkType>, workflow: &Workflow) -> Self {
+        Self {
+            title: work_type.title.clone(),
+            description: work_type.description.clone(),
+            parent: parent.map(|parent| parent.title.clone()),
+            attributes: work_type
+                .own_attributes()
+                .map(WorkAttributeType::to_create_request)
+                .collect(),
+            workflow: (!workflow.statuses.is_empty()).then(|| workflow.to_create_request()),
+        }
+    }
+}
+
+// Tipos de trabajo a exportar junto con sus ancestros, los padres antes que
+// los hijos. Sin `ids` se exportan todos.
+pub fn export_order<'a>(
+    work_types: &'a [WorkType],
+    ids: &[Uuid],
+) -> Result<Vec<&'a WorkType>, AppError> {
+    let by_id: HashMap<Uuid, &WorkType> = work_types.iter().map(|wt| (wt.id, wt)).collect();
+
+    let mut selected: Vec<&WorkType> = Vec::new();
+    let roots: Vec<Uuid> = if ids.is_empty() {
+        work_types.iter().map(|wt| wt.id).collect()
+    } else {
+        ids.to_vec()
+    };
+    for id in roots {
+        let mut chain: Vec<&WorkType> = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let work_type = by_id
+                .get(&id)
+                .ok_or_else(|| AppError::Validation(format!("worktype {} does not exist", id)))?;
+            chain.push(work_type);
+            current = work_type.parent_id;
+        }
+        for work_type in chain.into_iter().rev() {
+            if !selected.iter().any(|s| s.id == work_type.id) {
+                selected.push(work_type);
+            }
+        }
+    }
+
+    // Los padres se referencian por titulo, asi que no puede haber dos iguales
+    for (i, work_type) in selected.iter().enumerate() {
+        if selected[..i]
+            .iter()
+            .any(|other| same_title(&other.title, &work_type.title))
+        {
+            return Err(AppError::Validation(format!(
+                "more than one exported worktype is titled '{}'",
+                work_type.title
+            )));
+        }
+    }
+
+    Ok(selected)
+}
+
+// Decide que hacer con cada tipo de trabajo del paquete frente a los tipos de
+// trabajo `existing` y los ordena para que los padres se importen antes. No
+// toca nada: si el paquete no es valido no se importa ninguno.
+pub fn plan_import(
+    bundle: WorkTypeBundle,
+    existing: &[WorkType],
+    policy: ConflictPolicy,
+) -> Result<Vec<PlannedImport>, AppError> {
+    if bundle.version != BUNDLE_VERSION {
+        return Err(AppError::Validation(format!(
+            "unsupported bundle version {}, expected {}",
+            bundle.version, BUNDLE_VERSION
+        )));
+    }
+
+    let mut pending: Vec<BundledWorkType> = bundle.work_types;
+    for (i, entry) in pending.iter().enumerate() {
+        if entry.title.trim().is_empty() {
+            return Err(AppError::Validation(
+                "bundled worktypes need a title".to_string(),
+            ));
+        }
+        if pending[..i]
+            .iter()
+            .any(|other| same_title(&other.title, &entry.title))
+        {
+            return Err(AppError::Validation(format!(
+                "the bundle defines '{}' more than once",
+                entry.title
+            )));
+        }
+    }
+
+    let mut taken: Vec<String> = existing.iter().map(|wt| wt.title.clone()).collect();
+    taken.extend(pending.iter().map(|entry| entry.title.clone()));
+
+    let mut plans: Vec<PlannedImport> = Vec::new();
+    while !pending.is_empty() {
+        let position = pending
+            .iter()
+            .position(|entry| {
+                entry.parent.as_ref().is_none_or(|parent| {
+                    !pending.iter().any(|other| same_title(&other.title, parent))
+                })
+            })
+            .ok_or_else(|| {
+                AppError::Validation("the bundle has an inheritance cycle".to_string())
+            })?;
+        let entry = pending.remove(position);
+
+        let clashes: Vec<&WorkType> = existing
+            .iter()
+            .filter(|wt| same_title(&wt.title, &entry.title))
+            .collect();
+        let (action, existing_id, title) = match (clashes.is_empty(), policy) {
+            (true, _) => (ImportAction::Created, None, entry.title.clone()),
+            (false, ConflictPolicy::Skip) => (
+                ImportAction::Skipped,
+                Some(unique_match(existing, &entry.title)?.id),
+                entry.title.clone(),
+            ),
+            (false, ConflictPolicy::Overwrite) => (
+                ImportAction::Overwritten,
+                Some(unique_match(existing, &entry.title)?.id),
+                entry.title.clone(),
+            ),
+            (false, ConflictPolicy::Rename) => {
+                let title = free_title(&entry.title, &taken);
+                taken.push(title.clone());
+                (ImportAction::Renamed, None, title)
+            }
+        };
+
+        // Los omitidos no necesitan resolver su padre
+        let parent = match &entry.parent {
+            Some(_) if action == ImportAction::Skipped => None,
+            None => None,
+            Some(parent) if plans.iter().any(|p| same_title(&p.title, parent)) => {
+                Some(ParentRef::Bundled(parent.clone()))
+            }
+            Some(parent) => Some(ParentRef::Existing(unique_match(existing, parent)?.id)),
+        };
+
+        let request = CreateWorkType {
+            title,
+            description: entry.description,
+            parent_id: None,
+            status: None,
+            attributes: entry.attributes,
+        };
+        let workflow = entry.workflow.unwrap_or_default();
+        if action != ImportAction::Skipped {
+            validate_work_type_request(&request)
+                .and_then(|_| validate_workflow_request(&workflow))
+                .map_err(|e| match e {
+                    AppError::Validation(message) => {
+                        AppError::Validation(format!("'{}': {}", entry.title, message))
+                    }
+                    other => other,
+                })?;
+        }
+
+        plans.push(PlannedImport {
+            title: entry.title,
+            action,
+            existing_id,
+            parent,
+            request,
+            workflow,
+        });
+    }
+
+    Ok(plans)
+}
+
+impl PlannedImport {
+    // Los padres del paquete ya se han importado y estan en `imported`, por `title_key`
+    pub fn parent_id(&self, imported: &HashMap<String, Uuid>) -> Option<Uuid> {
+        match &self.parent {
+            Some(ParentRef::Bundled(title)) => imported.get(&title_key(title)).copied(),
+            Some(ParentRef::Existing(id)) => Some(*id),
+            None => None,
+        }
+    }
+}
+
+// Los titulos se comparan sin distinguir mayusculas
+pub fn title_key(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+pub fn same_title(a: &str, b: &str) -> bool {
+    title_key(a) == title_key(b)
+}
+
+fn unique_match<'a>(work_types: &'a [WorkType], title: &str) -> Result<&'a WorkType, AppError> {
+    let matches: Vec<&WorkType> = work_types
+        .iter()
+        .filter(|wt| same_title(&wt.title, title))
+        .collect();
+    match matches.as_slice() {
+        [work_type] => Ok(work_type),
+        [] => Err(AppError::Validation(format!(
+            "parent worktype '{}' is neither in the bundle nor in the catalog",
+            title
+        ))),
+        _ => Err(AppError::Validation(format!(
+            "{} worktypes are titled '{}', cannot tell which one to use",
+            matches.len(),
+            title
+        ))),
+    }
+}
+
+// Primer titulo libre del estilo "Bug (2)", "Bug (3)", ...
+fn free_title(title: &str, taken: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", title, n))
+        .find(|candidate| !taken.iter().any(|t| same_title(t, candidate)))
+        .unwrap()
+}
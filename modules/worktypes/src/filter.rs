@@ -0,0 +1,609 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate};
+use common::error::AppError;
+
+use crate::models::{DataType, WorkItem, WorkType};
+use crate::validation::split_multi_select;
+
+// Filtros del listado de work items, por ejemplo
+// `severity >= 3 AND status = "open" AND reported_by ~ "ana"`. Las condiciones
+// comparan un campo con un literal y se combinan con AND, OR, NOT y
+// parentesis. Los campos son claves de atributo y `status`, el estado del
+// flujo de trabajo, si ningun atributo usa esa clave.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Condition(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub comparator: Comparator,
+    pub operand: Operand,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Attribute { key: String, data_type: DataType },
+    Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(f64),
+    Text(String),
+    Comparator(Comparator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Comparator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparator::Equal => "=",
+            Comparator::NotEqual => "!=",
+            Comparator::Greater => ">",
+            Comparator::GreaterOrEqual => ">=",
+            Comparator::Less => "<",
+            Comparator::LessOrEqual => "<=",
+            Comparator::Contains => "~",
+        }
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(
+            self,
+            Comparator::Greater
+                | Comparator::GreaterOrEqual
+                | Comparator::Less
+                | Comparator::LessOrEqual
+        )
+    }
+
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparator::Equal => ordering == Ordering::Equal,
+            Comparator::NotEqual => ordering != Ordering::Equal,
+            Comparator::Greater => ordering == Ordering::Greater,
+            Comparator::GreaterOrEqual => ordering != Ordering::Less,
+            Comparator::Less => ordering == Ordering::Less,
+            Comparator::LessOrEqual => ordering != Ordering::Greater,
+            Comparator::Contains => false,
+        }
+    }
+}
+
+// Analiza el filtro y comprueba cada condicion contra los atributos del tipo
+// de trabajo
+pub fn parse(input: &str, work_type: &WorkType) -> Result<Filter, AppError> {
+    let invalid = |reason: String| AppError::Validation(format!("invalid filter: {}", reason));
+
+    let tokens = tokenize(input).map_err(invalid)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        work_type,
+    };
+    let filter = parser.disjunction().map_err(invalid)?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(token) => Err(invalid(format!("unexpected {:?}", token))),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '=' | '~' => {
+                chars.next();
+                tokens.push(Token::Comparator(match c {
+                    '=' => Comparator::Equal,
+                    _ => Comparator::Contains,
+                }));
+            }
+            '!' | '<' | '>' => {
+                chars.next();
+                let comparator = match (c, chars.peek()) {
+                    ('!', Some('=')) | ('<', Some('>')) => Comparator::NotEqual,
+                    ('<', Some('=')) => Comparator::LessOrEqual,
+                    ('>', Some('=')) => Comparator::GreaterOrEqual,
+                    ('<', _) => Comparator::Less,
+                    ('>', _) => Comparator::Greater,
+                    _ => return Err("'!' must be followed by '='".to_string()),
+                };
+                if matches!(
+                    comparator,
+                    Comparator::NotEqual | Comparator::LessOrEqual | Comparator::GreaterOrEqual
+                ) {
+                    chars.next();
+                }
+                tokens.push(Token::Comparator(comparator));
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                let mut closed = false;
+                for ch in chars.by_ref() {
+                    if ch == c {
+                        closed = true;
+                        break;
+                    }
+                    text.push(ch);
+                }
+                if !closed {
+                    return Err("unterminated text".to_string());
+                }
+                tokens.push(Token::Text(text));
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let mut number = String::new();
+                if c == '-' {
+                    number.push(c);
+                    chars.next();
+                }
+                while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_digit() || **ch == '.') {
+                    number.push(ch);
+                    chars.next();
+                }
+                let value = number
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' is not a number", number))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&ch) = chars
+                    .peek()
+                    .filter(|ch| ch.is_alphanumeric() || **ch == '_')
+                {
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Identifier(word),
+                });
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    work_type: &'a WorkType,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    // disyuncion := conjuncion ('OR' conjuncion)*
+    fn disjunction(&mut self) -> Result<Filter, String> {
+        let mut left = self.conjunction()?;
+        while let Some(Token::Or) = self.peek() {
+            self.next();
+            let right = self.conjunction()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    // conjuncion := negacion ('AND' negacion)*
+    fn conjunction(&mut self) -> Result<Filter, String> {
+        let mut left = self.negation()?;
+        while let Some(Token::And) = self.peek() {
+            self.next();
+            let right = self.negation()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    // negacion := 'NOT' negacion | '(' disyuncion ')' | campo comparador literal
+    fn negation(&mut self) -> Result<Filter, String> {
+        match self.next().cloned() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.negation()?))),
+            Some(Token::Open) => {
+                let filter = self.disjunction()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Identifier(name)) => {
+                let comparator = match self.next() {
+                    Some(Token::Comparator(comparator)) => *comparator,
+                    _ => return Err(format!("'{}' must be followed by a comparison", name)),
+                };
+                let operand = match self.next().cloned() {
+                    Some(Token::Number(n)) => Operand::Number(n),
+                    Some(Token::Text(t)) => Operand::Text(t),
+                    Some(Token::Identifier(word)) => match word.to_lowercase().as_str() {
+                        "true" => Operand::Boolean(true),
+                        "false" => Operand::Boolean(false),
+                        "null" => Operand::Null,
+                        _ => return Err(format!("'{}' is not a value, quote texts", word)),
+                    },
+                    Some(token) => return Err(format!("unexpected {:?}", token)),
+                    None => return Err("filter ends unexpectedly".to_string()),
+                };
+                let field = self.field(&name)?;
+                check_condition(&name, &field, comparator, &operand, self.work_type)?;
+                Ok(Filter::Condition(Condition {
+                    field,
+                    comparator,
+                    operand,
+                }))
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("filter ends unexpectedly".to_string()),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<Field, String> {
+        match self.work_type.attributes.iter().find(|a| a.key == name) {
+            Some(attribute) => Ok(Field::Attribute {
+                key: attribute.key.clone(),
+                data_type: attribute.data_type,
+            }),
+            None if name == "status" => Ok(Field::Status),
+            None => Err(format!("unknown attribute '{}'", name)),
+        }
+    }
+}
+
+// Cada tipo de dato admite sus comparadores y literales: los numeros se
+// ordenan, las fechas se escriben como texto, `~` busca dentro de los textos y
+// entre las opciones de las selecciones multiples. `null` solo con = y !=.
+fn check_condition(
+    name: &str,
+    field: &Field,
+    comparator: Comparator,
+    operand: &Operand,
+    work_type: &WorkType,
+) -> Result<(), String> {
+    let symbol = comparator.symbol();
+    if *operand == Operand::Null {
+        return match comparator {
+            Comparator::Equal | Comparator::NotEqual => Ok(()),
+            _ => Err("null can only be compared with = or !=".to_string()),
+        };
+    }
+
+    let data_type = match field {
+        Field::Status => {
+            return match (comparator, operand) {
+                (Comparator::Equal | Comparator::NotEqual, Operand::Text(_)) => Ok(()),
+                _ => Err(format!(
+                    "status only supports = and != with a text, not {}",
+                    symbol
+                )),
+            };
+        }
+        Field::Attribute { data_type, .. } => *data_type,
+    };
+    let unsupported = || {
+        format!(
+            "'{}' cannot be used with the {} attribute '{}'",
+            symbol, data_type, name
+        )
+    };
+    let expected = |what: &str| {
+        format!(
+            "the {} attribute '{}' is compared with {}",
+            data_type, name, what
+        )
+    };
+
+    match data_type {
+        DataType::NumericType | DataType::DecimalType { .. } => match (comparator, operand) {
+            (Comparator::Contains, _) => Err(unsupported()),
+            (_, Operand::Number(_)) => Ok(()),
+            _ => Err(expected("a number")),
+        },
+        DataType::BooleanType => match (comparator, operand) {
+            (Comparator::Equal | Comparator::NotEqual, Operand::Boolean(_)) => Ok(()),
+            (Comparator::Equal | Comparator::NotEqual, _) => Err(expected("true or false")),
+            _ => Err(unsupported()),
+        },
+        DataType::DateType | DataType::DateTimeType => match (comparator, operand) {
+            (Comparator::Contains, _) => Err(unsupported()),
+            (_, Operand::Text(text)) => data_type.validate(text),
+            _ => Err(expected("a quoted date")),
+        },
+        DataType::SingleSelectType | DataType::MultiSelectType => {
+            let Operand::Text(text) = operand else {
+                return Err(expected("a quoted option"));
+            };
+            let valid = matches!(
+                (data_type, comparator),
+                (
+                    DataType::SingleSelectType,
+                    Comparator::Equal | Comparator::NotEqual
+                ) | (DataType::MultiSelectType, Comparator::Contains)
+            );
+            if !valid {
+                return Err(unsupported());
+            }
+            let options = work_type
+                .attributes
+                .iter()
+                .find(|a| a.key == name)
+                .and_then(|a| a.options.as_deref())
+                .unwrap_or_default();
+            if options.iter().any(|option| option == text) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "'{}' is not one of the options of '{}'",
+                    text, name
+                ))
+            }
+        }
+        DataType::StringType | DataType::EmailType | DataType::UrlType => {
+            match (comparator, operand) {
+                (comparator, _) if comparator.is_ordering() => Err(unsupported()),
+                (_, Operand::Text(_)) => Ok(()),
+                _ => Err(expected("a quoted text")),
+            }
+        }
+    }
+}
+
+impl Filter {
+    // Evalua el filtro sobre un work item. Una condicion sobre un valor que
+    // falta solo se cumple con `= null` y con `!=`.
+    pub fn matches(&self, item: &WorkItem) -> bool {
+        self.evaluate(item, &item.values())
+    }
+
+    fn evaluate(&self, item: &WorkItem, values: &HashMap<String, String>) -> bool {
+        match self {
+            Filter::And(left, right) => left.evaluate(item, values) && right.evaluate(item, values),
+            Filter::Or(left, right) => left.evaluate(item, values) || right.evaluate(item, values),
+            Filter::Not(inner) => !inner.evaluate(item, values),
+            Filter::Condition(condition) => {
+                let value = match &condition.field {
+                    Field::Attribute { key, .. } => values.get(key).map(String::as_str),
+                    Field::Status => item.status.as_deref(),
+                };
+                condition.evaluate(value)
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn evaluate(&self, value: Option<&str>) -> bool {
+        let (value, operand) = match (value, &self.operand) {
+            (None, Operand::Null) => return self.comparator == Comparator::Equal,
+            (Some(_), Operand::Null) => return self.comparator == Comparator::NotEqual,
+            (None, _) => return self.comparator == Comparator::NotEqual,
+            (Some(value), operand) => (value, operand),
+        };
+        let data_type = match &self.field {
+            Field::Attribute { data_type, .. } => *data_type,
+            Field::Status => DataType::StringType,
+        };
+
+        if self.comparator == Comparator::Contains {
+            let Operand::Text(text) = operand else {
+                return false;
+            };
+            return match data_type {
+                DataType::MultiSelectType => split_multi_select(value).contains(&text.as_str()),
+                _ => value.to_lowercase().contains(&text.to_lowercase()),
+            };
+        }
+
+        let ordering = match (data_type, operand) {
+            (DataType::NumericType | DataType::DecimalType { .. }, Operand::Number(n)) => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|value| value.partial_cmp(n)),
+            (DataType::BooleanType, Operand::Boolean(b)) => {
+                let value = value.trim().to_lowercase();
+                (value == "true" || value == "false").then(|| (value == "true").cmp(b))
+            }
+            (DataType::DateType, Operand::Text(text)) => {
+                let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+                parse(value).zip(parse(text)).map(|(a, b)| a.cmp(&b))
+            }
+            (DataType::DateTimeType, Operand::Text(text)) => {
+                let parse = |s: &str| DateTime::parse_from_rfc3339(s.trim()).ok();
+                parse(value).zip(parse(text)).map(|(a, b)| a.cmp(&b))
+            }
+            (_, Operand::Text(text)) => Some(value.cmp(text.as_str())),
+            _ => None,
+        };
+
+        // Un valor que no encaja con su tipo solo cumple `!=`
+        match ordering {
+            Some(ordering) => self.comparator.accepts(ordering),
+            None => self.comparator == Comparator::NotEqual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkAttributeType;
+
+    fn bug() -> WorkType {
+        let attribute = |name: &str, data_type| {
+            WorkAttributeType::new(name.to_string(), data_type, false, false)
+        };
+        WorkType {
+            attributes: vec![
+                attribute("Severity", DataType::NumericType),
+                attribute("Summary", DataType::StringType),
+                attribute("Reported At", DataType::DateTimeType),
+                WorkAttributeType {
+                    options: Some(vec!["ui".to_string(), "api".to_string()]),
+                    ..attribute("Tags", DataType::MultiSelectType)
+                },
+            ],
+            ..WorkType::default("Bug".to_string())
+        }
+    }
+
+    fn condition(filter: &str) -> Condition {
+        match parse(filter, &bug()) {
+            Ok(Filter::Condition(condition)) => condition,
+            other => panic!("{} is not a single condition: {:?}", filter, other),
+        }
+    }
+
+    fn parse_error(filter: &str) -> String {
+        match parse(filter, &bug()) {
+            Err(AppError::Validation(message)) => message,
+            other => panic!("{} should not parse: {:?}", filter, other),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_binds_tighter_than_or() {
+        let filter = parse(
+            "severity > 2 OR summary = 'crash' and not tags ~ 'ui'",
+            &bug(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            filter,
+            Filter::Or(
+                Box::new(Filter::Condition(condition("severity > 2"))),
+                Box::new(Filter::And(
+                    Box::new(Filter::Condition(condition("summary = 'crash'"))),
+                    Box::new(Filter::Not(Box::new(Filter::Condition(condition(
+                        "tags ~ 'ui'"
+                    ))))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_conditions() {
+        assert_eq!(
+            parse_error("severity = 1 AND"),
+            "invalid filter: filter ends unexpectedly"
+        );
+        assert_eq!(
+            parse_error("(severity = 1"),
+            "invalid filter: missing closing parenthesis"
+        );
+        assert_eq!(
+            parse_error("summary = 'open"),
+            "invalid filter: unterminated text"
+        );
+        assert_eq!(
+            parse_error("priority = 1"),
+            "invalid filter: unknown attribute 'priority'"
+        );
+        assert_eq!(
+            parse_error("summary > 'a'"),
+            "invalid filter: '>' cannot be used with the string attribute 'summary'"
+        );
+        assert_eq!(
+            parse_error("severity < null"),
+            "invalid filter: null can only be compared with = or !="
+        );
+        assert_eq!(
+            parse_error("tags ~ 'db'"),
+            "invalid filter: 'db' is not one of the options of 'tags'"
+        );
+        assert_eq!(
+            parse_error("reported_at > '2024-01-01'"),
+            "invalid filter: '2024-01-01' is not an RFC 3339 datetime"
+        );
+    }
+
+    #[test]
+    fn test_not_equal_matches_missing_values() {
+        let not_equal = condition("severity != 3");
+
+        assert!(not_equal.evaluate(None));
+        assert!(not_equal.evaluate(Some("not a number")));
+        assert!(not_equal.evaluate(Some("2")));
+        assert!(!not_equal.evaluate(Some("3")));
+        assert!(!condition("severity = 3").evaluate(None));
+        assert!(condition("severity = null").evaluate(None));
+        assert!(!condition("severity != null").evaluate(None));
+    }
+
+    #[test]
+    fn test_contains_is_case_insensitive_and_literal() {
+        let contains = condition("summary ~ '50%_off'");
+
+        assert!(contains.evaluate(Some("Sale 50%_OFF today")));
+        assert!(!contains.evaluate(Some("Sale 50 off today")));
+        assert!(condition("tags ~ 'ui'").evaluate(Some("api,ui")));
+    }
+
+    #[test]
+    fn test_datetime_comparisons_use_instants() {
+        let after = condition("reported_at > '2024-01-01T10:00:00+02:00'");
+
+        assert!(after.evaluate(Some("2024-01-01T09:00:00Z")));
+        assert!(!after.evaluate(Some("2024-01-01T08:00:00Z")));
+        assert!(!after.evaluate(Some("yesterday")));
+    }
+}
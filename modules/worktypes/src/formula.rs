@@ -0,0 +1,585 @@
+use std::collections::HashMap;
+
+use common::error::AppError;
+
+use crate::models::{attribute_key, DataType, WorkAttributeType, WorkType};
+use crate::validation::{key_error, ValueIssue};
+
+// Expresiones de los atributos calculados. Admiten numeros, textos entre
+// comillas, referencias a otros atributos y los operadores + - * / con
+// parentesis. Las referencias son la clave del atributo, que no cambia al
+// renombrarlo: `story_points * 2`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(String),
+    Reference(String),
+    Negate(Box<Expr>),
+    Binary {
+        operator: Operator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+// Tipo de los valores con los que opera una formula
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormulaType {
+    Number,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Reference(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+impl Operator {
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+        }
+    }
+}
+
+impl FormulaType {
+    // Los numericos operan como numeros; el resto solo se pueden concatenar
+    pub fn of(data_type: DataType) -> Self {
+        if data_type.is_numeric() {
+            FormulaType::Number
+        } else {
+            FormulaType::Text
+        }
+    }
+}
+
+impl Value {
+    fn to_text(&self) -> String {
+        match self {
+            Value::Number(n) => format_number(*n),
+            Value::Text(t) => t.clone(),
+        }
+    }
+
+    // Representacion del resultado segun el tipo del atributo calculado
+    pub fn format(&self, data_type: DataType) -> String {
+        match (self, data_type) {
+            (Value::Number(n), DataType::DecimalType { scale, .. }) => {
+                format!("{:.*}", scale as usize, n)
+            }
+            _ => self.to_text(),
+        }
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+pub fn parse(input: &str) -> Result<Expr, String> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+    };
+    let expr = parser.expression()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {:?} in formula", token)),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Operator(match c {
+                    '+' => Operator::Add,
+                    '-' => Operator::Subtract,
+                    '*' => Operator::Multiply,
+                    _ => Operator::Divide,
+                }));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                let mut closed = false;
+                for ch in chars.by_ref() {
+                    if ch == c {
+                        closed = true;
+                        break;
+                    }
+                    text.push(ch);
+                }
+                if !closed {
+                    return Err("unterminated text in formula".to_string());
+                }
+                tokens.push(Token::Text(text));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&ch) = chars.peek().filter(|ch| ch.is_ascii_digit() || **ch == '.') {
+                    number.push(ch);
+                    chars.next();
+                }
+                let value = number
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' is not a number", number))?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut key = String::new();
+                while let Some(&ch) = chars
+                    .peek()
+                    .filter(|ch| ch.is_alphanumeric() || **ch == '_')
+                {
+                    key.push(ch);
+                    chars.next();
+                }
+                // Las referencias siguen la misma gramatica que las claves
+                if let Some(reason) = key_error(&key) {
+                    return Err(format!(
+                        "'{}' is not an attribute key ({}), for example '{}'",
+                        key,
+                        reason,
+                        attribute_key(&key)
+                    ));
+                }
+                tokens.push(Token::Reference(key));
+            }
+            other => return Err(format!("unexpected character '{}' in formula", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Formula de antes de las claves con las referencias por nombre cambiadas por
+// la clave del atributo: `{Story Points} * 2` pasa a ser `story_points * 2`.
+// Devuelve `None` si no hay nada que cambiar.
+pub fn legacy_references_to_keys(
+    formula: &str,
+    attributes: &[WorkAttributeType],
+) -> Option<String> {
+    let key_of = |name: &str| {
+        attributes
+            .iter()
+            .find(|a| a.name == name && !attributes.iter().any(|other| other.key == name))
+            .map(|a| a.key.clone())
+    };
+    let mut rewritten = String::new();
+    let mut chars = formula.chars().peekable();
+    let mut quote: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            (_, Some(q)) => {
+                rewritten.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            ('"' | '\'', None) => {
+                rewritten.push(c);
+                quote = Some(c);
+            }
+            ('{', None) => {
+                let mut name = String::new();
+                for ch in chars.by_ref() {
+                    if ch == '}' {
+                        break;
+                    }
+                    name.push(ch);
+                }
+                match key_of(name.trim()) {
+                    Some(key) => rewritten.push_str(&key),
+                    None => rewritten.push_str(&format!("{{{}}}", name)),
+                }
+            }
+            (c, None) if c.is_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(&ch) = chars
+                    .peek()
+                    .filter(|ch| ch.is_alphanumeric() || **ch == '_')
+                {
+                    name.push(ch);
+                    chars.next();
+                }
+                rewritten.push_str(&key_of(&name).unwrap_or(name));
+            }
+            (c, None) => rewritten.push(c),
+        }
+    }
+
+    (rewritten != formula).then_some(rewritten)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    // expresion := termino (('+' | '-') termino)*
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Operator(operator @ (Operator::Add | Operator::Subtract))) =
+            self.peek().cloned()
+        {
+            self.next();
+            let right = self.term()?;
+            left = Expr::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    // termino := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.factor()?;
+        while let Some(Token::Operator(operator @ (Operator::Multiply | Operator::Divide))) =
+            self.peek().cloned()
+        {
+            self.next();
+            let right = self.factor()?;
+            left = Expr::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.next().cloned() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Text(t)) => Ok(Expr::Text(t)),
+            Some(Token::Reference(name)) => Ok(Expr::Reference(name)),
+            Some(Token::Operator(Operator::Subtract)) => Ok(Expr::Negate(Box::new(self.factor()?))),
+            Some(Token::Open) => {
+                let expr = self.expression()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing closing parenthesis in formula".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?} in formula", token)),
+            None => Err("formula ends unexpectedly".to_string()),
+        }
+    }
+}
+
+impl Expr {
+    pub fn references(&self) -> Vec<&str> {
+        match self {
+            Expr::Number(_) | Expr::Text(_) => Vec::new(),
+            Expr::Reference(name) => vec![name.as_str()],
+            Expr::Negate(inner) => inner.references(),
+            Expr::Binary { left, right, .. } => {
+                let mut references = left.references();
+                references.extend(right.references());
+                references
+            }
+        }
+    }
+
+    // `+` suma numeros y concatena en cuanto uno de los lados es texto; el
+    // resto de operadores solo admite numeros
+    pub fn infer(&self, types: &HashMap<&str, FormulaType>) -> Result<FormulaType, String> {
+        match self {
+            Expr::Number(_) => Ok(FormulaType::Number),
+            Expr::Text(_) => Ok(FormulaType::Text),
+            Expr::Reference(name) => types
+                .get(name.as_str())
+                .copied()
+                .ok_or_else(|| format!("unknown attribute key '{}'", name)),
+            Expr::Negate(inner) => match inner.infer(types)? {
+                FormulaType::Number => Ok(FormulaType::Number),
+                FormulaType::Text => Err("'-' cannot be applied to text".to_string()),
+            },
+            Expr::Binary {
+                operator,
+                left,
+                right,
+            } => match (operator, left.infer(types)?, right.infer(types)?) {
+                (_, FormulaType::Number, FormulaType::Number) => Ok(FormulaType::Number),
+                (Operator::Add, _, _) => Ok(FormulaType::Text),
+                (operator, _, _) => {
+                    Err(format!("'{}' cannot be applied to text", operator.symbol()))
+                }
+            },
+        }
+    }
+
+    pub fn evaluate(&self, values: &HashMap<&str, Value>) -> Result<Value, String> {
+        match self {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Text(t) => Ok(Value::Text(t.clone())),
+            Expr::Reference(name) => values
+                .get(name.as_str())
+                .cloned()
+                .ok_or_else(|| format!("missing value for '{}'", name)),
+            Expr::Negate(inner) => match inner.evaluate(values)? {
+                Value::Number(n) => Ok(Value::Number(-n)),
+                Value::Text(_) => Err("'-' cannot be applied to text".to_string()),
+            },
+            Expr::Binary {
+                operator,
+                left,
+                right,
+            } => match (operator, left.evaluate(values)?, right.evaluate(values)?) {
+                (Operator::Add, Value::Number(a), Value::Number(b)) => Ok(Value::Number(a + b)),
+                (Operator::Subtract, Value::Number(a), Value::Number(b)) => {
+                    Ok(Value::Number(a - b))
+                }
+                (Operator::Multiply, Value::Number(a), Value::Number(b)) => {
+                    Ok(Value::Number(a * b))
+                }
+                (Operator::Divide, Value::Number(_), Value::Number(0.0)) => {
+                    Err("division by zero".to_string())
+                }
+                (Operator::Divide, Value::Number(a), Value::Number(b)) => Ok(Value::Number(a / b)),
+                (Operator::Add, a, b) => Ok(Value::Text(a.to_text() + &b.to_text())),
+                (operator, _, _) => {
+                    Err(format!("'{}' cannot be applied to text", operator.symbol()))
+                }
+            },
+        }
+    }
+}
+
+impl WorkType {
+    // Recalcula los atributos calculados a partir del resto de valores. Las
+    // formulas citan los atributos por clave, igual que los valores. Si falta
+    // alguno de los valores de los que depende, se queda sin valor.
+    pub fn compute_values(&self, values: &mut HashMap<String, String>) -> Result<(), AppError> {
+        for attribute in self.attributes.iter().filter(|a| a.formula.is_some()) {
+            values.remove(&attribute.key);
+            let formula = attribute.formula.as_deref().unwrap_or_default();
+            let invalid = |reason: String| {
+                AppError::Validation(
+                    ValueIssue::Invalid {
+                        key: attribute.key.clone(),
+                        reason,
+                    }
+                    .to_string(),
+                )
+            };
+
+            let expr = parse(formula).map_err(invalid)?;
+            let mut inputs: HashMap<&str, Value> = HashMap::new();
+            for reference in expr.references() {
+                let input = self.attributes.iter().find(|a| a.key == reference);
+                let (Some(input), Some(value)) =
+                    (input, input.and_then(|input| values.get(&input.key)))
+                else {
+                    continue;
+                };
+                let value = match FormulaType::of(input.data_type) {
+                    FormulaType::Number => Value::Number(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|_| invalid(format!("'{}' is not a number", value)))?,
+                    ),
+                    FormulaType::Text => Value::Text(value.clone()),
+                };
+                inputs.insert(reference, value);
+            }
+            if expr.references().iter().any(|r| !inputs.contains_key(r)) {
+                continue;
+            }
+
+            let result = expr.evaluate(&inputs).map_err(invalid)?;
+            let value = result.format(attribute.data_type);
+            attribute.validate_value(&value).map_err(invalid)?;
+            values.insert(attribute.key.clone(), value);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(formula: &str) -> Result<Value, String> {
+        parse(formula)?.evaluate(&HashMap::new())
+    }
+
+    #[test]
+    fn test_parse_respects_operator_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(Value::Number(7.0)));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(Value::Number(9.0)));
+        assert_eq!(evaluate("10 - 4 - 3"), Ok(Value::Number(3.0)));
+        assert_eq!(evaluate("12 / 3 / 2"), Ok(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn test_parse_unary_minus() {
+        assert_eq!(
+            parse("-points * 2"),
+            Ok(Expr::Binary {
+                operator: Operator::Multiply,
+                left: Box::new(Expr::Negate(Box::new(Expr::Reference(
+                    "points".to_string()
+                )))),
+                right: Box::new(Expr::Number(2.0)),
+            })
+        );
+        assert_eq!(evaluate("--3"), Ok(Value::Number(3.0)));
+        assert_eq!(evaluate("2 - -3"), Ok(Value::Number(5.0)));
+        assert_eq!(
+            evaluate("-'text'"),
+            Err("'-' cannot be applied to text".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_formulas() {
+        assert_eq!(
+            parse("'open"),
+            Err("unterminated text in formula".to_string())
+        );
+        assert_eq!(
+            parse("\"open' + 1"),
+            Err("unterminated text in formula".to_string())
+        );
+        assert_eq!(
+            parse("(1 + 2"),
+            Err("missing closing parenthesis in formula".to_string())
+        );
+        assert_eq!(
+            parse("1 + 2)"),
+            Err("unexpected Close in formula".to_string())
+        );
+        assert_eq!(parse("1 +"), Err("formula ends unexpectedly".to_string()));
+    }
+
+    #[test]
+    fn test_evaluate_rejects_division_by_zero() {
+        assert_eq!(evaluate("1 / 0"), Err("division by zero".to_string()));
+        assert_eq!(
+            parse("1 / divisor")
+                .unwrap()
+                .evaluate(&HashMap::from([("divisor", Value::Number(-0.0))])),
+            Err("division by zero".to_string())
+        );
+        assert_eq!(evaluate("1 / -0"), Err("division by zero".to_string()));
+    }
+
+    #[test]
+    fn test_evaluate_concatenates_text() {
+        assert_eq!(
+            evaluate("'Sprint ' + 3"),
+            Ok(Value::Text("Sprint 3".to_string()))
+        );
+        assert_eq!(
+            evaluate("1.5 + \" pts\""),
+            Ok(Value::Text("1.5 pts".to_string()))
+        );
+        assert_eq!(
+            evaluate("'a' * 2"),
+            Err("'*' cannot be applied to text".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_uses_decimal_scale() {
+        let decimal = DataType::DecimalType {
+            precision: 10,
+            scale: 2,
+        };
+
+        assert_eq!(Value::Number(1.0 / 3.0).format(decimal), "0.33");
+        assert_eq!(Value::Number(5.0).format(decimal), "5.00");
+        assert_eq!(Value::Number(5.0).format(DataType::NumericType), "5");
+        assert_eq!(Value::Number(2.5).format(DataType::NumericType), "2.5");
+    }
+
+    #[test]
+    fn test_legacy_references_to_keys() {
+        let attributes = vec![
+            WorkAttributeType::new(
+                "Story Points".to_string(),
+                DataType::NumericType,
+                false,
+                false,
+            ),
+            WorkAttributeType::new("Effort".to_string(), DataType::NumericType, false, false),
+        ];
+
+        assert_eq!(
+            legacy_references_to_keys("{Story Points} * Effort", &attributes),
+            Some("story_points * effort".to_string())
+        );
+        assert_eq!(
+            legacy_references_to_keys("'{Story Points} and Effort' + Effort", &attributes),
+            Some("'{Story Points} and Effort' + effort".to_string())
+        );
+        assert_eq!(
+            legacy_references_to_keys("{Unknown} + 1", &attributes),
+            None
+        );
+        assert_eq!(
+            legacy_references_to_keys("story_points * 2", &attributes),
+            None
+        );
+    }
+}
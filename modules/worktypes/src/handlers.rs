@@ -1,17 +1,34 @@
 use std::sync::Arc;
 
-use crate::{repositories::repository::WorkTypeRepositoryTrait, requests::CreateWorkType};
+use crate::{
+    bundle::WorkTypeBundle,
+    history::MAX_CHANGED_BY_LENGTH,
+    impact::ProposedChange,
+    models::{WorkItemWithLinks, WorkTypeStatus},
+    repositories::repository::{
+        LinkRepositoryTrait, WorkItemRepositoryTrait, WorkTypeRepositoryTrait,
+    },
+    requests::{
+        BundleExportQuery, BundleFormat, BundleImportQuery, CreateLinkType, CreateWorkItem,
+        CreateWorkItemLink, CreateWorkType, CreateWorkflow, HistoryQuery, MigrateAttribute,
+        SyncQuery, TransitionWorkItem, WorkAttributeRequest, WorkItemListQuery, WorkItemQuery,
+        WorkTypeQuery,
+    },
+};
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use common::error::AppError;
+use uuid::Uuid;
 
 pub async fn list_worktypes(
     State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Query(query): Query<WorkTypeQuery>,
 ) -> impl IntoResponse {
-    match repository.list().await {
+    match repository.list(query).await {
         Ok(companies) => (StatusCode::OK, Json(companies)).into_response(),
         Err(e) => e.into_response(),
     }
@@ -26,3 +43,583 @@ pub async fn create_worktype(
         Err(e) => e.into_response(),
     }
 }
+
+pub async fn list_company_worktypes(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(company_id): Path<String>,
+    Query(query): Query<WorkTypeQuery>,
+) -> impl IntoResponse {
+    match repository.list_for_company(&company_id, query).await {
+        Ok(Some(work_types)) => (StatusCode::OK, Json(work_types)).into_response(),
+        Ok(None) => company_not_found(&company_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_company_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(company_id): Path<String>,
+    Json(payload): Json<CreateWorkType>,
+) -> impl IntoResponse {
+    match repository.create_for_company(&company_id, payload).await {
+        Ok(Some(created)) => (StatusCode::CREATED, Json(created)).into_response(),
+        Ok(None) => company_not_found(&company_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.get(id).await {
+        Ok(Some(work_type)) => (StatusCode::OK, Json(work_type)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn update_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateWorkType>,
+) -> impl IntoResponse {
+    match repository.update(id, payload).await {
+        Ok(Some(work_type)) => (StatusCode::OK, Json(work_type)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.delete(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn duplicate_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.duplicate(id).await {
+        Ok(Some(work_type)) => (StatusCode::CREATED, Json(work_type)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn publish_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.set_status(id, WorkTypeStatus::Published).await {
+        Ok(Some(work_type)) => (StatusCode::OK, Json(work_type)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn deprecate_worktype(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.set_status(id, WorkTypeStatus::Deprecated).await {
+        Ok(Some(work_type)) => (StatusCode::OK, Json(work_type)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// Incluye los atributos heredados, en el orden en que se resuelven
+pub async fn list_worktype_attributes(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.get(id).await {
+        Ok(Some(work_type)) => (StatusCode::OK, Json(work_type.attributes)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_worktype_attribute(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path((id, attribute_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match repository.get(id).await {
+        Ok(Some(work_type)) => match work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute_id)
+        {
+            Some(attribute) => (StatusCode::OK, Json(attribute)).into_response(),
+            None => attribute_not_found(id, attribute_id).into_response(),
+        },
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_worktype_attribute(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<WorkAttributeRequest>,
+) -> impl IntoResponse {
+    match repository.create_attribute(id, payload).await {
+        Ok(Some(attribute)) => (StatusCode::CREATED, Json(attribute)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn update_worktype_attribute(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path((id, attribute_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<WorkAttributeRequest>,
+) -> impl IntoResponse {
+    match repository.update_attribute(id, attribute_id, payload).await {
+        Ok(Some(attribute)) => (StatusCode::OK, Json(attribute)).into_response(),
+        Ok(None) => attribute_not_found(id, attribute_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_worktype_attribute(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path((id, attribute_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match repository.delete_attribute(id, attribute_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => attribute_not_found(id, attribute_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn migrate_worktype_attribute(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path((id, attribute_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<MigrateAttribute>,
+) -> impl IntoResponse {
+    match repository
+        .migrate_attribute(id, attribute_id, request)
+        .await
+    {
+        Ok(Some(report)) => (StatusCode::OK, Json(report)).into_response(),
+        Ok(None) => attribute_not_found(id, attribute_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn analyze_worktype_impact(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(change): Json<ProposedChange>,
+) -> impl IntoResponse {
+    let attribute_id = change.attribute_id();
+    match repository.analyze_impact(id, change).await {
+        Ok(Some(report)) => (StatusCode::OK, Json(report)).into_response(),
+        Ok(None) => match attribute_id {
+            Some(attribute_id) => attribute_not_found(id, attribute_id).into_response(),
+            None => not_found(id).into_response(),
+        },
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn export_worktypes(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Query(query): Query<BundleExportQuery>,
+) -> impl IntoResponse {
+    let ids = match query.ids() {
+        Ok(ids) => ids,
+        Err(e) => return e.into_response(),
+    };
+    let bundle = match repository.export_bundle(ids).await {
+        Ok(bundle) => bundle,
+        Err(e) => return e.into_response(),
+    };
+
+    match query.format.unwrap_or_default() {
+        BundleFormat::Json => (StatusCode::OK, Json(bundle)).into_response(),
+        BundleFormat::Yaml => match bundle.to_yaml() {
+            Ok(yaml) => (
+                StatusCode::OK,
+                [(header::CONTENT_TYPE, "application/yaml")],
+                yaml,
+            )
+                .into_response(),
+            Err(e) => e.into_response(),
+        },
+    }
+}
+
+// El paquete se lee como YAML si el Content-Type lo indica y como JSON en otro caso
+pub async fn import_worktypes(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Query(query): Query<BundleImportQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let bundle = match WorkTypeBundle::parse(&body, body_format(&headers)) {
+        Ok(bundle) => bundle,
+        Err(e) => return e.into_response(),
+    };
+
+    match repository
+        .import_bundle(bundle, query.conflict.unwrap_or_default())
+        .await
+    {
+        Ok(outcomes) => (StatusCode::OK, Json(outcomes)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// El documento tiene el formato de los paquetes y se lee igual que al importar
+pub async fn sync_worktypes(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Query(query): Query<SyncQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let document = match WorkTypeBundle::parse(&body, body_format(&headers)) {
+        Ok(document) => document,
+        Err(e) => return e.into_response(),
+    };
+
+    match repository
+        .sync(document, query.apply.unwrap_or_default())
+        .await
+    {
+        Ok(plan) => (StatusCode::OK, Json(plan)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+fn body_format(headers: &HeaderMap) -> BundleFormat {
+    match headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(content_type) if content_type.contains("yaml") => BundleFormat::Yaml,
+        _ => BundleFormat::Json,
+    }
+}
+
+pub async fn get_worktype_schema(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.get(id).await {
+        Ok(Some(work_type)) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/schema+json")],
+            Json(work_type.json_schema()),
+        )
+            .into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_worktype_versions(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.list_versions(id).await {
+        Ok(Some(versions)) => (StatusCode::OK, Json(versions)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_worktype_version(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path((id, version)): Path<(Uuid, i32)>,
+) -> impl IntoResponse {
+    match repository.get_version(id, version).await {
+        Ok(Some(work_type_version)) => (StatusCode::OK, Json(work_type_version)).into_response(),
+        Ok(None) => AppError::NotFound(format!(
+            "Version {} del tipo de trabajo con ID {} no encontrada",
+            version, id
+        ))
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_worktype_workflow(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.get_workflow(id).await {
+        Ok(Some(workflow)) => (StatusCode::OK, Json(workflow)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn set_worktype_workflow(
+    State(repository): State<Arc<dyn WorkTypeRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateWorkflow>,
+) -> impl IntoResponse {
+    match repository.set_workflow(id, payload).await {
+        Ok(Some(workflow)) => (StatusCode::OK, Json(workflow)).into_response(),
+        Ok(None) => not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_work_items(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(work_type_id): Path<Uuid>,
+    Query(query): Query<WorkItemListQuery>,
+) -> impl IntoResponse {
+    if query.q.is_some() {
+        return match repository.search(work_type_id, query).await {
+            Ok(Some(hits)) => (StatusCode::OK, Json(hits)).into_response(),
+            Ok(None) => not_found(work_type_id).into_response(),
+            Err(e) => e.into_response(),
+        };
+    }
+    match repository.list(work_type_id, query).await {
+        Ok(Some(items)) => (StatusCode::OK, Json(items)).into_response(),
+        Ok(None) => not_found(work_type_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_work_item(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(work_type_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateWorkItem>,
+) -> impl IntoResponse {
+    let changed_by = match changed_by(&headers) {
+        Ok(changed_by) => changed_by,
+        Err(e) => return e.into_response(),
+    };
+    match repository.create(work_type_id, payload, changed_by).await {
+        Ok(Some(item)) => (StatusCode::CREATED, Json(item)).into_response(),
+        Ok(None) => not_found(work_type_id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_work_item(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    State(link_repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<WorkItemQuery>,
+) -> impl IntoResponse {
+    let item = match repository.get(id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return item_not_found(id).into_response(),
+        Err(e) => return e.into_response(),
+    };
+    if !query.includes("links") {
+        return (StatusCode::OK, Json(item)).into_response();
+    }
+
+    match link_repository.list_links(id).await {
+        Ok(Some(links)) => {
+            (StatusCode::OK, Json(WorkItemWithLinks { item, links })).into_response()
+        }
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn update_work_item(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateWorkItem>,
+) -> impl IntoResponse {
+    let changed_by = match changed_by(&headers) {
+        Ok(changed_by) => changed_by,
+        Err(e) => return e.into_response(),
+    };
+    match repository.update(id, payload, changed_by).await {
+        Ok(Some(item)) => (StatusCode::OK, Json(item)).into_response(),
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_work_item(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.delete(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn transition_work_item(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<TransitionWorkItem>,
+) -> impl IntoResponse {
+    let changed_by = match changed_by(&headers) {
+        Ok(changed_by) => changed_by,
+        Err(e) => return e.into_response(),
+    };
+    match repository.transition(id, payload, changed_by).await {
+        Ok(Some(item)) => (StatusCode::OK, Json(item)).into_response(),
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_work_item_history(
+    State(repository): State<Arc<dyn WorkItemRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    match repository.history(id, query).await {
+        Ok(Some(history)) => (StatusCode::OK, Json(history)).into_response(),
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// Quien hace el cambio, para el historial, se indica en la cabecera X-User
+fn changed_by(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let value = match headers.get("x-user") {
+        Some(value) => value
+            .to_str()
+            .map_err(|_| AppError::Validation("X-User must be visible ASCII text".to_string()))?
+            .trim(),
+        None => return Ok(None),
+    };
+    if value.len() > MAX_CHANGED_BY_LENGTH {
+        return Err(AppError::Validation(format!(
+            "X-User cannot be longer than {} characters",
+            MAX_CHANGED_BY_LENGTH
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+pub async fn list_link_types(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+) -> impl IntoResponse {
+    match repository.list_link_types().await {
+        Ok(link_types) => (StatusCode::OK, Json(link_types)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn create_link_type(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Json(payload): Json<CreateLinkType>,
+) -> impl IntoResponse {
+    match repository.create_link_type(payload).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn get_link_type(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.get_link_type(id).await {
+        Ok(Some(link_type)) => (StatusCode::OK, Json(link_type)).into_response(),
+        Ok(None) => link_type_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn update_link_type(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateLinkType>,
+) -> impl IntoResponse {
+    match repository.update_link_type(id, payload).await {
+        Ok(Some(link_type)) => (StatusCode::OK, Json(link_type)).into_response(),
+        Ok(None) => link_type_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_link_type(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.delete_link_type(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => link_type_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_work_item_links(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    match repository.list_links(id).await {
+        Ok(Some(links)) => (StatusCode::OK, Json(links)).into_response(),
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn add_work_item_link(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreateWorkItemLink>,
+) -> impl IntoResponse {
+    match repository.add_link(id, payload).await {
+        Ok(Some(link)) => (StatusCode::CREATED, Json(link)).into_response(),
+        Ok(None) => item_not_found(id).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn remove_work_item_link(
+    State(repository): State<Arc<dyn LinkRepositoryTrait + Send + Sync>>,
+    Path((id, link_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    match repository.remove_link(id, link_id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => AppError::NotFound(format!(
+            "Enlace con ID {} no encontrado en el work item {}",
+            link_id, id
+        ))
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+fn not_found(id: Uuid) -> AppError {
+    AppError::NotFound(format!("Tipo de trabajo con ID {} no encontrado", id))
+}
+
+fn attribute_not_found(id: Uuid, attribute_id: Uuid) -> AppError {
+    AppError::NotFound(format!(
+        "Atributo con ID {} no encontrado en el tipo de trabajo {}",
+        attribute_id, id
+    ))
+}
+
+fn item_not_found(id: Uuid) -> AppError {
+    AppError::NotFound(format!("Work item con ID {} no encontrado", id))
+}
+
+fn company_not_found(id: &str) -> AppError {
+    AppError::NotFound(format!("Compañía con ID {} no encontrada", id))
+}
+
+fn link_type_not_found(id: Uuid) -> AppError {
+    AppError::NotFound(format!("Tipo de enlace con ID {} no encontrado", id))
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::WorkItem;
+
+// Longitud maxima de quien hace el cambio, la de la columna `changed_by`
+pub const MAX_CHANGED_BY_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Transitioned,
+}
+
+// Entrada del historial de un work item. No se modifica una vez registrada:
+// guarda quien y cuando hizo el cambio y, de lo que cambio, el valor anterior
+// y el nuevo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkItemChange {
+    pub id: Uuid,
+    pub work_item_id: Uuid,
+    pub action: ChangeAction,
+    pub changed_by: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    pub status: Option<StatusChange>,
+    pub attributes: Vec<AttributeChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+// Un valor que no existia o que se quito queda como `None`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeChange {
+    pub key: String,
+    pub name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+// Pagina del historial, de la entrada mas reciente a la mas antigua
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub page: u32,
+    pub per_page: u32,
+    pub total: u64,
+    pub entries: Vec<WorkItemChange>,
+}
+
+impl FromStr for ChangeAction {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "created" => Ok(ChangeAction::Created),
+            "updated" => Ok(ChangeAction::Updated),
+            "transitioned" => Ok(ChangeAction::Transitioned),
+            other => Err(AppError::Validation(format!(
+                "unknown change action: {}",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ChangeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let x: &'static str = match self {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+            ChangeAction::Transitioned => "transitioned",
+        };
+        write!(f, "{}", x)
+    }
+}
+
+impl WorkItemChange {
+    // Diferencias entre el work item antes del cambio (ninguno si se acaba de
+    // crear) y despues. Los atributos siguen el orden del work item, y al
+    // final los que se quitaron.
+    pub fn between(
+        action: ChangeAction,
+        before: Option<&WorkItem>,
+        after: &WorkItem,
+        changed_by: Option<String>,
+    ) -> Self {
+        let old_status = before.and_then(|item| item.status.clone());
+        let status = (old_status != after.status).then(|| StatusChange {
+            from: old_status,
+            to: after.status.clone(),
+        });
+
+        let old_value = |key: &str| {
+            before
+                .and_then(|item| item.work_attributes.iter().find(|att| att.key == key))
+                .map(|att| att.value.clone())
+        };
+        let mut attributes: Vec<AttributeChange> = after
+            .work_attributes
+            .iter()
+            .filter_map(|att| {
+                let old_value = old_value(&att.key);
+                (old_value.as_ref() != Some(&att.value)).then(|| AttributeChange {
+                    key: att.key.clone(),
+                    name: att.name.clone(),
+                    old_value,
+                    new_value: Some(att.value.clone()),
+                })
+            })
+            .collect();
+        if let Some(before) = before {
+            attributes.extend(
+                before
+                    .work_attributes
+                    .iter()
+                    .filter(|att| !after.work_attributes.iter().any(|a| a.key == att.key))
+                    .map(|att| AttributeChange {
+                        key: att.key.clone(),
+                        name: att.name.clone(),
+                        old_value: Some(att.value.clone()),
+                        new_value: None,
+                    }),
+            );
+        }
+
+        Self {
+            id: Uuid::new_v4(),
+            work_item_id: after.id,
+            action,
+            changed_by,
+            changed_at: after.updated_at,
+            status,
+            attributes,
+        }
+    }
+
+    // Cambio de valores que hace el esquema al quitar o migrar un atributo, y
+    // no una persona, asi que no tiene autor. `None` si no cambia ningun valor.
+    pub fn by_schema(before: &WorkItem, after: &WorkItem) -> Option<Self> {
+        let change = Self {
+            changed_at: Utc::now(),
+            ..Self::between(ChangeAction::Updated, Some(before), after, None)
+        };
+        (!change.attributes.is_empty()).then_some(change)
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{WorkAttributeType, WorkItem, WorkType};
+use crate::requests::WorkAttributeRequest;
+use crate::validation::{check_values, validate_attribute_request, ValueIssue};
+
+// Work items que se citan como ejemplo en cada apartado del informe
+const SAMPLE_SIZE: usize = 10;
+
+// Cambio de un atributo propio que se quiere evaluar antes de aplicarlo. Lleva
+// el mismo cuerpo que los endpoints de atributos sueltos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ProposedChange {
+    Create {
+        attribute: WorkAttributeRequest,
+    },
+    Update {
+        attribute_id: Uuid,
+        attribute: WorkAttributeRequest,
+    },
+    Delete {
+        attribute_id: Uuid,
+    },
+}
+
+// Lo que pasaria con los work items del tipo de trabajo y de sus descendientes
+// si se aplicara el cambio. `missing` e `invalid` son los que dejarian de
+// poder guardarse tal y como estan; `discarded` los que perderian valores.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImpactReport {
+    pub checked: usize,
+    pub affected: usize,
+    pub missing: AffectedItems,
+    pub invalid: AffectedItems,
+    pub discarded: AffectedItems,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AffectedItems {
+    pub count: usize,
+    pub sample: Vec<Uuid>,
+}
+
+impl ProposedChange {
+    pub fn attribute_id(&self) -> Option<Uuid> {
+        match self {
+            ProposedChange::Create { .. } => None,
+            ProposedChange::Update { attribute_id, .. }
+            | ProposedChange::Delete { attribute_id } => Some(*attribute_id),
+        }
+    }
+}
+
+impl AffectedItems {
+    pub(crate) fn add(&mut self, item_id: Uuid) {
+        self.count += 1;
+        if self.sample.len() < SAMPLE_SIZE {
+            self.sample.push(item_id);
+        }
+    }
+}
+
+impl WorkType {
+    // Atributos propios tal y como quedarian tras el cambio, o `None` si el
+    // atributo que se cambia no existe
+    pub fn proposed_attributes(
+        &self,
+        change: &ProposedChange,
+    ) -> Result<Option<Vec<WorkAttributeType>>, AppError> {
+        match change {
+            ProposedChange::Create { attribute } => {
+                validate_attribute_request(&attribute.attribute)?;
+                let created = WorkAttributeType::from_create_request(&attribute.attribute);
+                self.place_attribute(created, attribute.position).map(Some)
+            }
+            ProposedChange::Update {
+                attribute_id,
+                attribute,
+            } => {
+                validate_attribute_request(&attribute.attribute)?;
+                match self.own_attribute(*attribute_id)? {
+                    Some(existing) => {
+                        let redefined = existing.redefine(&attribute.attribute)?;
+                        self.place_attribute(redefined, attribute.position)
+                            .map(Some)
+                    }
+                    None => Ok(None),
+                }
+            }
+            ProposedChange::Delete { attribute_id } => match self.own_attribute(*attribute_id)? {
+                Some(_) => self.remove_attribute(*attribute_id).map(Some),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+impl WorkItem {
+    // Valores del work item por clave de atributo, como los envian los clientes
+    pub fn values(&self) -> HashMap<String, String> {
+        self.work_attributes
+            .iter()
+            .map(|value| (value.key.clone(), value.value.clone()))
+            .collect()
+    }
+}
+
+// Contrasta los valores guardados de cada work item con su tipo de trabajo
+// antes y despues del cambio, con la misma validacion que al guardarlos. Solo
+// cuentan los problemas que aparecen con el cambio. Los valores de los
+// atributos calculados no los envian los clientes y no se comprueban.
+pub fn analyze_impact(
+    current: &HashMap<Uuid, WorkType>,
+    proposed: &HashMap<Uuid, WorkType>,
+    items: &[WorkItem],
+) -> ImpactReport {
+    let mut report = ImpactReport::default();
+
+    for item in items {
+        let (Some(before), Some(after)) = (
+            current.get(&item.work_type_id),
+            proposed.get(&item.work_type_id),
+        ) else {
+            continue;
+        };
+        let previous = item_issues(before, item);
+        let issues: Vec<ValueIssue> = item_issues(after, item)
+            .into_iter()
+            .filter(|issue| !previous.contains(issue))
+            .collect();
+
+        let has = |f: fn(&ValueIssue) -> bool| issues.iter().any(f);
+        if has(|issue| matches!(issue, ValueIssue::Missing(_))) {
+            report.missing.add(item.id);
+        }
+        if has(|issue| matches!(issue, ValueIssue::Invalid { .. })) {
+            report.invalid.add(item.id);
+        }
+        if has(|issue| matches!(issue, ValueIssue::Unknown(_))) {
+            report.discarded.add(item.id);
+        }
+
+        report.checked += 1;
+        if !issues.is_empty() {
+            report.affected += 1;
+        }
+    }
+
+    report
+}
+
+fn item_issues(work_type: &WorkType, item: &WorkItem) -> Vec<ValueIssue> {
+    let mut values = item.values();
+    values.retain(|key, _| {
+        !work_type
+            .attributes
+            .iter()
+            .any(|a| &a.key == key && a.formula.is_some())
+    });
+    check_values(work_type, &values)
+}
@@ -2,46 +2,137 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use axum::Router;
-use common::{config::Config, modules::Module, repositories::postgres::PostgresRepository};
+use common::{
+    config::Config, hooks::CompanyHooks, modules::Module,
+    repositories::postgres::PostgresRepository,
+};
 use common::{error::AppError, error::Result};
-use repositories::repository::WorkTypeRepositoryTrait;
+use repositories::memory::MemoryWorkTypeRepository;
+use repositories::repository::{
+    LinkRepositoryTrait, WorkItemRepositoryTrait, WorkTypeRepositoryTrait,
+};
+use search::SearchLanguage;
+use system::SystemAttributes;
 
+mod bundle;
+mod filter;
+mod formula;
 mod handlers;
+mod history;
+mod impact;
+mod migration;
 mod models;
 mod repositories;
 mod requests;
 mod routes;
+mod schema;
+mod search;
+mod sync;
+mod system;
+mod validation;
+
+pub use repositories::repository::RepositoryProvider;
 
 pub struct WorktypesModule {
     repository: Arc<dyn WorkTypeRepositoryTrait + Send + Sync>,
+    item_repository: Arc<dyn WorkItemRepositoryTrait + Send + Sync>,
+    link_repository: Arc<dyn LinkRepositoryTrait + Send + Sync>,
+}
+
+impl WorktypesModule {
+    pub async fn with_provider(provider: RepositoryProvider) -> Result<Self> {
+        match provider {
+            RepositoryProvider::Memory => {
+                tracing::info!("[Worktype Module] Usando el repositorio en memoria");
+                let memory_repo = Arc::new(MemoryWorkTypeRepository::new());
+                Ok(Self {
+                    repository: memory_repo.clone()
+                        as Arc<dyn WorkTypeRepositoryTrait + Send + Sync>,
+                    item_repository: memory_repo.clone()
+                        as Arc<dyn WorkItemRepositoryTrait + Send + Sync>,
+                    link_repository: memory_repo as Arc<dyn LinkRepositoryTrait + Send + Sync>,
+                })
+            }
+            RepositoryProvider::Postgres(database_url) => {
+                let repo = PostgresRepository::new_with_ensured_query(
+                    &database_url,
+                    repositories::postgres::QUERY,
+                )
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!(
+                        "[Worktype Module] Problem connecting to PostgreSQL. Error: {}",
+                        e
+                    ))
+                })?;
+                tracing::info!("[Worktype Module] Conectado a PostgreSQL");
+                repositories::postgres::ensure_system_attributes(&repo).await?;
+                repositories::postgres::ensure_formula_keys(&repo).await?;
+                repositories::postgres_values::ensure_search_index(&repo).await?;
+                repositories::postgres_values::spawn_value_indexes(&*repo.pool.lock().await, None);
+
+                let psql_repo = Arc::new(repo);
+                Ok(Self {
+                    repository: psql_repo.clone() as Arc<dyn WorkTypeRepositoryTrait + Send + Sync>,
+                    item_repository: psql_repo.clone()
+                        as Arc<dyn WorkItemRepositoryTrait + Send + Sync>,
+                    link_repository: psql_repo as Arc<dyn LinkRepositoryTrait + Send + Sync>,
+                })
+            }
+        }
+    }
+}
+
+impl WorktypesModule {
+    // Para que el modulo de compañias copie o borre el catalogo propio de
+    // cada compañia
+    pub fn company_hooks(&self) -> Arc<dyn CompanyHooks> {
+        Arc::new(CompanyWorkTypes {
+            repository: self.repository.clone(),
+        })
+    }
+}
+
+struct CompanyWorkTypes {
+    repository: Arc<dyn WorkTypeRepositoryTrait + Send + Sync>,
+}
+
+#[async_trait]
+impl CompanyHooks for CompanyWorkTypes {
+    async fn company_created(&self, company_id: &str) -> Result<()> {
+        self.repository.add_company(company_id).await
+    }
+
+    async fn company_duplicated(&self, from_company_id: &str, to_company_id: &str) -> Result<()> {
+        self.repository
+            .duplicate_for_company(from_company_id, to_company_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn company_deleted(&self, company_id: &str) -> Result<()> {
+        self.repository.delete_for_company(company_id).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl Module for WorktypesModule {
     async fn create(config: &Config) -> Result<Self> {
-        let repo_opt: Result<PostgresRepository> = PostgresRepository::new_with_ensured_query(
-            &config.database_url,
-            repositories::postgres::QUERY,
-        )
-        .await;
-
-        repo_opt
-            .map(|r| {
-                tracing::info!("[Worktype Module] Conectado a PostgreSQL");
-                let psql_repo = Arc::new(r) as Arc<dyn WorkTypeRepositoryTrait + Send + Sync>;
-                Self {
-                    repository: psql_repo,
-                }
-            })
-            .map_err(|e| {
-                AppError::Internal(format!(
-                    "[Worktype Module] Problem connecting to PostgreSQL. Error: {}",
-                    e
-                ))
-            })
+        if let Some(system_attributes) = &config.system_attributes {
+            SystemAttributes::from_json(system_attributes)?.install();
+        }
+        if let Some(search_language) = &config.search_language {
+            search_language.parse::<SearchLanguage>()?.install();
+        }
+        Self::with_provider(config.storage.clone().into()).await
     }
 
     fn routes(&self) -> Router {
-        routes::create_routes(self.repository.clone())
+        routes::create_routes(
+            self.repository.clone(),
+            self.item_repository.clone(),
+            self.link_repository.clone(),
+        )
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,162 @@
+use chrono::{DateTime, NaiveDate};
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::impact::AffectedItems;
+use crate::models::{DataType, WorkAttributeType};
+use crate::requests::FailureStrategy;
+use crate::validation::split_multi_select;
+
+// Valor guardado de un atributo en un work item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredValue {
+    pub id: Uuid,
+    pub work_item_id: Uuid,
+    pub value: String,
+}
+
+// Resultado de migrar los valores de un atributo. `failed` cuenta los valores
+// que no se pudieron convertir, que segun `on_failure` se quitan o pasan a
+// tener el valor por defecto.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub attribute: WorkAttributeType,
+    pub on_failure: FailureStrategy,
+    pub converted: usize,
+    pub failed: AffectedItems,
+}
+
+// Valores que cambian con la migracion, por id; `None` quita el valor
+pub type MigratedValues = Vec<(Uuid, Option<String>)>;
+
+impl DataType {
+    // Convierte un valor guardado con otro tipo de dato a la forma que espera
+    // este. Las restricciones del atributo se comprueban despues.
+    pub fn coerce(&self, value: &str) -> Result<String, String> {
+        let trimmed = value.trim();
+        let failed = || format!("'{}' cannot be converted to {}", value, self);
+        match self {
+            DataType::StringType => Ok(value.to_string()),
+            DataType::EmailType | DataType::UrlType => Ok(trimmed.to_string()),
+            DataType::NumericType => parse_number(trimmed)
+                .map(|number| number.to_string())
+                .ok_or_else(failed),
+            DataType::DecimalType { scale, .. } => parse_number(trimmed)
+                .map(|number| format!("{:.*}", *scale as usize, number))
+                .ok_or_else(failed),
+            DataType::BooleanType => match trimmed.to_lowercase().as_str() {
+                "true" | "yes" | "si" | "sí" | "1" => Ok("true".to_string()),
+                "false" | "no" | "0" => Ok("false".to_string()),
+                _ => Err(failed()),
+            },
+            DataType::DateType => NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                .or_else(|_| DateTime::parse_from_rfc3339(trimmed).map(|dt| dt.date_naive()))
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .map_err(|_| failed()),
+            DataType::DateTimeType => DateTime::parse_from_rfc3339(trimmed)
+                .map(|dt| dt.to_rfc3339())
+                .or_else(|_| {
+                    NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                        .map(|date| format!("{}T00:00:00+00:00", date.format("%Y-%m-%d")))
+                })
+                .map_err(|_| failed()),
+            DataType::SingleSelectType => match split_multi_select(trimmed).as_slice() {
+                [option] => Ok(option.to_string()),
+                _ => Err(failed()),
+            },
+            DataType::MultiSelectType => Ok(split_multi_select(trimmed).join(", ")),
+        }
+    }
+}
+
+// Admite la coma como separador decimal y los booleanos como 1 y 0
+fn parse_number(value: &str) -> Option<f64> {
+    let number = match value.to_lowercase().as_str() {
+        "true" => Some(1.0),
+        "false" => Some(0.0),
+        _ if !value.contains('.') => value.replace(',', ".").parse().ok(),
+        _ => value.parse().ok(),
+    };
+    number.filter(|n: &f64| n.is_finite())
+}
+
+// Comprueba que la estrategia se puede aplicar al atributo antes de tocar nada
+fn check_strategy(
+    attribute: &WorkAttributeType,
+    on_failure: FailureStrategy,
+) -> Result<(), AppError> {
+    if attribute.formula.is_some() {
+        return Err(AppError::Validation(format!(
+            "the computed attribute '{}' has no stored values to migrate",
+            attribute.name
+        )));
+    }
+    match on_failure {
+        FailureStrategy::Null if attribute.is_required => Err(AppError::Validation(format!(
+            "on_failure null would leave the required attribute '{}' without values",
+            attribute.name
+        ))),
+        FailureStrategy::Default if attribute.default_value.is_none() => {
+            Err(AppError::Validation(format!(
+                "on_failure default needs a default_value for attribute '{}'",
+                attribute.name
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Convierte los valores guardados al nuevo tipo del atributo y aplica la
+// estrategia a los que no encajan. Con `abort` cualquier fallo es un error.
+pub fn migrate_values(
+    attribute: &WorkAttributeType,
+    values: &[StoredValue],
+    on_failure: FailureStrategy,
+) -> Result<(MigrationReport, MigratedValues), AppError> {
+    check_strategy(attribute, on_failure)?;
+
+    let mut converted = 0;
+    let mut failed = AffectedItems::default();
+    let mut first_error: Option<String> = None;
+    let mut changes: MigratedValues = Vec::new();
+
+    for stored in values {
+        let migrated = attribute.data_type.coerce(&stored.value).and_then(|value| {
+            attribute.validate_value(&value)?;
+            Ok(value)
+        });
+        match migrated {
+            Ok(value) => {
+                converted += 1;
+                if value != stored.value {
+                    changes.push((stored.id, Some(value)));
+                }
+            }
+            Err(reason) => {
+                failed.add(stored.work_item_id);
+                first_error.get_or_insert(reason);
+                let replacement = match on_failure {
+                    FailureStrategy::Default => attribute.default_value.clone(),
+                    _ => None,
+                };
+                changes.push((stored.id, replacement));
+            }
+        }
+    }
+
+    if let (FailureStrategy::Abort, Some(reason)) = (on_failure, first_error) {
+        return Err(AppError::Validation(format!(
+            "cannot migrate attribute '{}' to {}, failed values: {} (for example {})",
+            attribute.name, attribute.data_type, failed.count, reason
+        )));
+    }
+
+    let report = MigrationReport {
+        attribute: attribute.clone(),
+        on_failure,
+        converted,
+        failed,
+    };
+    Ok((report, changes))
+}
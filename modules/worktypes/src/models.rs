@@ -1,4 +1,4 @@
-use std::{fmt, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -6,32 +6,108 @@ use uuid::Uuid;
 
 use common::error::AppError;
 
-use crate::requests::CreateWorkType;
+use crate::requests::{
+    CreateLinkType, CreateWorkAttributeType, CreateWorkItem, CreateWorkType, CreateWorkflow,
+};
+use crate::system::SystemAttributes;
+use crate::validation::validate_values;
 // Aqui definimos los modelos para los tipos de entidades de trabajo
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkType {
     pub id: Uuid,
     pub title: String,
     pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+    // Compañia propietaria del tipo de trabajo; `None` si es global
+    pub company_id: Option<String>,
+    pub status: WorkTypeStatus,
     pub attributes: Vec<WorkAttributeType>,
+    pub version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+
+// Ciclo de vida de un tipo de trabajo. Los borradores no salen en los
+// listados ni admiten work items; los obsoletos conservan los que tienen pero
+// no admiten nuevos.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkTypeStatus {
+    Draft,
+    #[default]
+    Published,
+    Deprecated,
+}
+
+// Foto inmutable de los atributos de un tipo de trabajo en una version concreta
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkTypeVersion {
+    pub work_type_id: Uuid,
+    pub version: i32,
+    pub attributes: Vec<WorkAttributeType>,
+    pub created_at: DateTime<Utc>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkAttributeType {
     pub id: Uuid,
     pub name: String,
+    // Identificador estable del atributo dentro del tipo de trabajo, con el que
+    // se direccionan sus valores. No cambia aunque se renombre el atributo.
+    #[serde(default)]
+    pub key: String,
     pub data_type: DataType,
+    pub options: Option<Vec<String>>,
     pub is_required: bool,
     pub is_hidden: bool,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub pattern: Option<String>,
+    pub default_value: Option<String>,
+    // Expresion de los atributos calculados, que no admiten valores de los clientes
+    pub formula: Option<String>,
+    // Orden entre los atributos propios del tipo de trabajo. Las versiones
+    // registradas antes de existir no lo guardan.
+    #[serde(default)]
+    pub position: i32,
+    // Atributo de sistema: no se puede eliminar, renombrar ni cambiar de tipo
+    #[serde(default)]
+    pub is_system: bool,
+    // Tipo de trabajo del que se hereda el atributo, `None` si es propio
+    pub inherited_from: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+// Ciclo de vida de los work items de un tipo de trabajo. Sin estados, el tipo
+// de trabajo no tiene flujo de trabajo.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub initial_status: Option<String>,
+    pub statuses: Vec<WorkflowStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkflowStatus {
+    pub name: String,
+    pub category: StatusCategory,
+    pub transitions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusCategory {
+    Todo,
+    InProgress,
+    Done,
+}
 // Aqui definimos los modelos para los las implementaciones de las entidades de trabajo
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkAttributeItem {
     pub id: Uuid,
-    pub attribute_type: WorkAttributeType,
+    pub attribute_type_id: Uuid,
+    pub key: String,
+    pub name: String,
     pub value: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -40,37 +116,199 @@ pub struct WorkAttributeItem {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkItem {
     pub id: Uuid,
-    pub work_type: WorkType,
-    pub work_attributes: Vec<WorkAttributeType>,
+    pub work_type_id: Uuid,
+    pub schema_version: i32,
+    pub status: Option<String>,
+    pub work_attributes: Vec<WorkAttributeItem>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+// Tipo de enlace entre work items definido por un administrador. El nombre de
+// salida se lee desde el origen ("blocks") y el de entrada desde el destino
+// ("is blocked by"). Los jerarquicos (padre/hijo) no admiten ciclos.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkType {
+    pub id: Uuid,
+    pub name: String,
+    pub outward_name: String,
+    pub inward_name: String,
+    pub is_hierarchical: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkItemLink {
+    pub id: Uuid,
+    pub link_type_id: Uuid,
+    pub source_id: Uuid,
+    pub target_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkDirection {
+    Outward,
+    Inward,
+}
+
+// Un enlace visto desde uno de sus extremos: `item_id` es el otro work item y
+// `name` el nombre del tipo de enlace en esa direccion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemLink {
+    pub id: Uuid,
+    pub link_type_id: Uuid,
+    pub direction: LinkDirection,
+    pub name: String,
+    pub item_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkItemWithLinks {
+    #[serde(flatten)]
+    pub item: WorkItem,
+    pub links: Vec<ItemLink>,
+}
+
+// El sufijo evita confundir las variantes con los tipos de Rust (`String`, ...)
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum DataType {
     StringType,
     NumericType,
+    BooleanType,
+    DateType,
+    DateTimeType,
+    SingleSelectType,
+    MultiSelectType,
+    EmailType,
+    UrlType,
+    DecimalType { precision: u32, scale: u32 },
 }
 
-impl FromStr for DataType {
+impl DataType {
+    pub const DEFAULT_DECIMAL: DataType = DataType::DecimalType {
+        precision: 18,
+        scale: 2,
+    };
+
+    pub fn is_select(&self) -> bool {
+        matches!(self, DataType::SingleSelectType | DataType::MultiSelectType)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, DataType::NumericType | DataType::DecimalType { .. })
+    }
+
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            DataType::StringType | DataType::EmailType | DataType::UrlType
+        )
+    }
+
+    // Admite tanto `decimal` como `decimal(precision,scale)`
+    fn parse_decimal(s: &str) -> Option<DataType> {
+        if s == "decimal" {
+            return Some(DataType::DEFAULT_DECIMAL);
+        }
+        let args = s.strip_prefix("decimal(")?.strip_suffix(')')?;
+        let (precision, scale) = args.split_once(',')?;
+        let precision: u32 = precision.trim().parse().ok()?;
+        let scale: u32 = scale.trim().parse().ok()?;
+        (precision > 0 && scale <= precision).then_some(DataType::DecimalType { precision, scale })
+    }
+}
+
+impl FromStr for StatusCategory {
     type Err = AppError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "string" => Ok(DataType::StringType),
-            "numeric" => Ok(DataType::NumericType),
+            "todo" => Ok(StatusCategory::Todo),
+            "in_progress" => Ok(StatusCategory::InProgress),
+            "done" => Ok(StatusCategory::Done),
+            other => Err(AppError::Validation(format!(
+                "unknown status category: {}",
+                other
+            ))),
+        }
+    }
+}
+impl fmt::Display for StatusCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let x: &'static str = match self {
+            StatusCategory::Todo => "todo",
+            StatusCategory::InProgress => "in_progress",
+            StatusCategory::Done => "done",
+        };
+        write!(f, "{}", x)
+    }
+}
+
+impl FromStr for WorkTypeStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "draft" => Ok(WorkTypeStatus::Draft),
+            "published" => Ok(WorkTypeStatus::Published),
+            "deprecated" => Ok(WorkTypeStatus::Deprecated),
             other => Err(AppError::Validation(format!(
-                "unknown data type: {}",
+                "unknown worktype status: {}",
                 other
             ))),
         }
     }
 }
+impl fmt::Display for WorkTypeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let x: &'static str = match self {
+            WorkTypeStatus::Draft => "draft",
+            WorkTypeStatus::Published => "published",
+            WorkTypeStatus::Deprecated => "deprecated",
+        };
+        write!(f, "{}", x)
+    }
+}
+
+impl FromStr for DataType {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "string" => Ok(DataType::StringType),
+            "numeric" => Ok(DataType::NumericType),
+            "boolean" => Ok(DataType::BooleanType),
+            "date" => Ok(DataType::DateType),
+            "datetime" => Ok(DataType::DateTimeType),
+            "single_select" => Ok(DataType::SingleSelectType),
+            "multi_select" => Ok(DataType::MultiSelectType),
+            "email" => Ok(DataType::EmailType),
+            "url" => Ok(DataType::UrlType),
+            other => DataType::parse_decimal(other)
+                .ok_or_else(|| AppError::Validation(format!("unknown data type: {}", other))),
+        }
+    }
+}
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let x: &'static str = match self {
             DataType::StringType => "string",
             DataType::NumericType => "numeric",
+            DataType::BooleanType => "boolean",
+            DataType::DateType => "date",
+            DataType::DateTimeType => "datetime",
+            DataType::SingleSelectType => "single_select",
+            DataType::MultiSelectType => "multi_select",
+            DataType::EmailType => "email",
+            DataType::UrlType => "url",
+            DataType::DecimalType { precision, scale } => {
+                return write!(f, "decimal({},{})", precision, scale);
+            }
         };
         write!(f, "{}", x)
     }
@@ -91,52 +329,341 @@ impl<'de> Deserialize<'de> for DataType {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        match s.to_lowercase().as_str() {
-            "string" => Ok(DataType::StringType),
-            "numeric" => Ok(DataType::NumericType),
-            other => Err(serde::de::Error::custom(format!(
-                "unknown data type: {}",
-                other
-            ))),
-        }
+        s.parse::<DataType>().map_err(|_| {
+            serde::de::Error::custom(format!("unknown data type: {}", s.to_lowercase()))
+        })
     }
 }
 
 impl WorkType {
-    pub fn from_create_request(request: CreateWorkType) -> Self {
+    // Los atributos de la peticion se completan con los de sistema. Sin
+    // `status` el tipo de trabajo se publica directamente.
+    pub fn from_create_request(request: CreateWorkType) -> Result<Self, AppError> {
+        let status = request.status.unwrap_or_default();
+        if status == WorkTypeStatus::Deprecated {
+            return Err(AppError::Validation(
+                "a worktype cannot be created deprecated".to_string(),
+            ));
+        }
         let attributes: Vec<WorkAttributeType> = request
             .attributes
             .iter()
-            .map(|att_req| {
-                WorkAttributeType::new(
-                    att_req.name.clone(),
-                    att_req.data_type,
-                    att_req.is_required,
-                    att_req.is_hidden,
-                )
+            .enumerate()
+            .map(|(position, att_req)| WorkAttributeType {
+                position: position as i32,
+                ..WorkAttributeType::from_create_request(att_req)
             })
             .collect();
 
         let new_worktype: WorkType = WorkType::default(request.title);
-        Self {
+        Ok(Self {
             description: request.description,
-            attributes,
+            parent_id: request.parent_id,
+            status,
+            attributes: SystemAttributes::get().merge(request.parent_id.is_some(), attributes)?,
             ..new_worktype
-        }
+        })
     }
     pub fn default(title: String) -> Self {
         let now = Utc::now();
-        let summary: WorkAttributeType = WorkAttributeType::summary();
-        let description: WorkAttributeType = WorkAttributeType::description();
         Self {
             id: Uuid::new_v4(),
             title,
             description: None,
-            attributes: vec![summary, description],
+            parent_id: None,
+            company_id: None,
+            status: WorkTypeStatus::default(),
+            attributes: Vec::new(),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    // Completa los atributos que no vienen informados con su valor por defecto
+    pub fn apply_defaults(&self, values: &mut HashMap<String, String>) {
+        for attribute in &self.attributes {
+            if let Some(default_value) = &attribute.default_value {
+                values
+                    .entry(attribute.key.clone())
+                    .or_insert_with(|| default_value.clone());
+            }
+        }
+    }
+
+    // Preparacion de los valores al crear y al actualizar un work item. Una
+    // actualizacion sustituye todos los valores, asi que los atributos que no
+    // vengan vuelven a su valor por defecto igual que al crear.
+    pub fn prepare_values(&self, values: &mut HashMap<String, String>) -> Result<(), AppError> {
+        self.apply_defaults(values);
+        validate_values(self, values)?;
+        self.compute_values(values)
+    }
+
+    // Solo los atributos forman parte del esquema versionado
+    pub fn same_schema(&self, attributes: &[WorkAttributeType]) -> bool {
+        self.attributes.len() == attributes.len()
+            && self
+                .attributes
+                .iter()
+                .all(|attribute| attributes.iter().any(|o| attribute.same_definition(o)))
+    }
+
+    // Aplana la cadena de herencia: los atributos de los ancestros se
+    // sobrescriben por clave con los del descendiente. `work_types` contiene
+    // los tipos de trabajo solo con sus atributos propios.
+    pub fn resolve(&self, work_types: &HashMap<Uuid, WorkType>) -> WorkType {
+        let mut chain: Vec<&WorkType> = Vec::new();
+        let mut parent_id = self.parent_id;
+        while let Some(parent) = parent_id.and_then(|id| work_types.get(&id)) {
+            if parent.id == self.id || chain.iter().any(|wt| wt.id == parent.id) {
+                break;
+            }
+            chain.push(parent);
+            parent_id = parent.parent_id;
+        }
+
+        let mut attributes: Vec<WorkAttributeType> = Vec::new();
+        for ancestor in chain.iter().rev() {
+            for attribute in ancestor.own_attributes() {
+                let inherited = WorkAttributeType {
+                    inherited_from: Some(ancestor.id),
+                    ..attribute.clone()
+                };
+                match attributes.iter_mut().find(|a| a.key == attribute.key) {
+                    Some(existing) => *existing = inherited,
+                    None => attributes.push(inherited),
+                }
+            }
+        }
+        for attribute in self.own_attributes() {
+            match attributes.iter_mut().find(|a| a.key == attribute.key) {
+                Some(existing) => *existing = attribute.clone(),
+                None => attributes.push(attribute.clone()),
+            }
+        }
+
+        WorkType {
+            attributes,
+            ..self.clone()
+        }
+    }
+
+    pub fn own_attributes(&self) -> impl Iterator<Item = &WorkAttributeType> {
+        self.attributes
+            .iter()
+            .filter(|attribute| attribute.inherited_from.is_none())
+    }
+
+    // Los atributos heredados solo se cambian en el tipo de trabajo que los define
+    pub fn own_attribute(
+        &self,
+        attribute_id: Uuid,
+    ) -> Result<Option<&WorkAttributeType>, AppError> {
+        match self.attributes.iter().find(|a| a.id == attribute_id) {
+            Some(attribute) => match attribute.inherited_from {
+                Some(owner) => Err(AppError::Validation(format!(
+                    "attribute '{}' is inherited from worktype {}",
+                    attribute.name, owner
+                ))),
+                None => Ok(Some(attribute)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // Atributos propios con `attribute` añadido, o sustituido si ya tenia su
+    // id. Va a `position` o, si no se indica, donde estaba o al final; el
+    // resto se renumera a continuacion.
+    pub fn place_attribute(
+        &self,
+        attribute: WorkAttributeType,
+        position: Option<i32>,
+    ) -> Result<Vec<WorkAttributeType>, AppError> {
+        for other in self.own_attributes().filter(|a| a.id != attribute.id) {
+            if other.key == attribute.key {
+                return Err(AppError::Validation(format!(
+                    "an attribute with key '{}' already exists",
+                    attribute.key
+                )));
+            }
+            if other.name.to_lowercase() == attribute.name.to_lowercase() {
+                return Err(AppError::Validation(format!(
+                    "an attribute named '{}' already exists",
+                    other.name
+                )));
+            }
+        }
+
+        let current = self.own_attributes().position(|a| a.id == attribute.id);
+        let mut attributes: Vec<WorkAttributeType> = self
+            .own_attributes()
+            .filter(|a| a.id != attribute.id)
+            .cloned()
+            .collect();
+        let index = match position {
+            Some(position) if position < 0 => {
+                return Err(AppError::Validation(
+                    "attribute position cannot be negative".to_string(),
+                ))
+            }
+            Some(position) => position as usize,
+            None => current.unwrap_or(attributes.len()),
+        };
+        attributes.insert(index.min(attributes.len()), attribute);
+
+        for (position, attribute) in attributes.iter_mut().enumerate() {
+            attribute.position = position as i32;
+        }
+        SystemAttributes::get().merge(self.parent_id.is_some(), attributes)
+    }
+
+    // Atributos propios sin el indicado, renumerados
+    pub fn remove_attribute(&self, attribute_id: Uuid) -> Result<Vec<WorkAttributeType>, AppError> {
+        if let Some(attribute) = self
+            .own_attributes()
+            .find(|a| a.id == attribute_id && a.is_system)
+        {
+            return Err(AppError::Validation(format!(
+                "system attribute '{}' cannot be deleted",
+                attribute.name
+            )));
+        }
+
+        Ok(self
+            .own_attributes()
+            .filter(|a| a.id != attribute_id)
+            .enumerate()
+            .map(|(position, attribute)| WorkAttributeType {
+                position: position as i32,
+                ..attribute.clone()
+            })
+            .collect())
+    }
+
+    // Atributo propio que corresponde a una definicion: el de su clave si la
+    // indica y, si no, el de su nombre sin distinguir mayusculas
+    pub fn matching_attribute(
+        &self,
+        request: &CreateWorkAttributeType,
+    ) -> Option<&WorkAttributeType> {
+        match &request.key {
+            Some(key) => self.own_attributes().find(|a| &a.key == key),
+            None => self
+                .own_attributes()
+                .find(|a| a.name.to_lowercase() == request.name.to_lowercase()),
+        }
+    }
+
+    // Atributos propios tras sustituirlos por los de `request`, emparejados con
+    // `matching_attribute` para conservar su identificador y ordenados como en
+    // la peticion. Los de sistema que no vengan se conservan al final.
+    pub fn updated_attributes(
+        &self,
+        request: &CreateWorkType,
+    ) -> Result<Vec<WorkAttributeType>, AppError> {
+        let mut attributes: Vec<WorkAttributeType> = request
+            .attributes
+            .iter()
+            .map(|att_req| match self.matching_attribute(att_req) {
+                Some(existing) => existing.redefine(att_req),
+                None => Ok(WorkAttributeType::from_create_request(att_req)),
+            })
+            .collect::<Result<_, _>>()?;
+        let system: Vec<WorkAttributeType> = self
+            .own_attributes()
+            .filter(|a| a.is_system && !attributes.iter().any(|kept| kept.id == a.id))
+            .cloned()
+            .collect();
+        attributes.extend(system);
+
+        for (position, attribute) in attributes.iter_mut().enumerate() {
+            attribute.position = position as i32;
+        }
+        SystemAttributes::get().merge(request.parent_id.is_some(), attributes)
+    }
+
+    // El estado solo cambia con `publish` y `deprecate`; una actualizacion
+    // puede repetirlo pero no cambiarlo
+    pub fn check_update_status(&self, request: &CreateWorkType) -> Result<(), AppError> {
+        match request.status {
+            Some(status) if status != self.status => Err(AppError::Validation(format!(
+                "worktype {} is {}, use publish or deprecate to change its status",
+                self.id, self.status
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // Publicar vale para borradores y obsoletos; solo se puede dejar obsoleto
+    // lo publicado. Pedir el estado que ya tiene no hace nada.
+    pub fn check_status_change(&self, to: WorkTypeStatus) -> Result<(), AppError> {
+        match (self.status, to) {
+            (from, to) if from == to => Ok(()),
+            (_, WorkTypeStatus::Published) => Ok(()),
+            (WorkTypeStatus::Published, WorkTypeStatus::Deprecated) => Ok(()),
+            (from, to) => Err(AppError::Validation(format!(
+                "worktype {} cannot go from {} to {}",
+                self.id, from, to
+            ))),
+        }
+    }
+
+    pub fn check_accepts_items(&self) -> Result<(), AppError> {
+        match self.status {
+            WorkTypeStatus::Published => Ok(()),
+            WorkTypeStatus::Draft => Err(AppError::Validation(format!(
+                "worktype {} is a draft and cannot receive work items",
+                self.id
+            ))),
+            WorkTypeStatus::Deprecated => Err(AppError::Validation(format!(
+                "worktype {} is deprecated and does not accept new work items",
+                self.id
+            ))),
+        }
+    }
+
+    pub fn snapshot(&self) -> WorkTypeVersion {
+        WorkTypeVersion {
+            work_type_id: self.id,
+            version: self.version,
+            attributes: self.attributes.clone(),
+            created_at: self.updated_at,
+        }
+    }
+
+    // Copia profunda del tipo de trabajo: tanto el tipo como cada uno de sus
+    // atributos propios reciben identificadores nuevos. Los heredados se
+    // siguen resolviendo a traves del mismo padre.
+    pub fn duplicate(&self) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            title: format!("{} (copia)", self.title),
+            description: self.description.clone(),
+            parent_id: self.parent_id,
+            company_id: self.company_id.clone(),
+            status: self.status,
+            attributes: self
+                .own_attributes()
+                .map(WorkAttributeType::duplicate)
+                .collect(),
+            version: 1,
             created_at: now,
             updated_at: now,
         }
     }
+
+    // Copia del tipo de trabajo para otra compañia: conserva el titulo y se
+    // engancha al padre que se le indique, que puede ser tambien una copia
+    pub fn copy_for_company(&self, company_id: &str, parent_id: Option<Uuid>) -> Self {
+        Self {
+            title: self.title.clone(),
+            parent_id,
+            company_id: Some(company_id.to_string()),
+            ..self.duplicate()
+        }
+    }
 }
 
 impl WorkAttributeType {
@@ -144,26 +671,707 @@ impl WorkAttributeType {
         let now = Utc::now();
         WorkAttributeType {
             id: Uuid::new_v4(),
+            key: attribute_key(&name),
             name,
             data_type,
+            options: None,
             is_required,
             is_hidden,
+            min_value: None,
+            max_value: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            default_value: None,
+            formula: None,
+            position: 0,
+            is_system: false,
+            inherited_from: None,
             created_at: now,
             updated_at: now,
         }
     }
 
-    pub fn new_name_data_type(name: String, data_type: DataType) -> Self {
-        WorkAttributeType::new(name, data_type, false, false)
+    pub fn from_create_request(request: &CreateWorkAttributeType) -> Self {
+        Self {
+            key: request.key(),
+            options: request.options.clone(),
+            min_value: request.min_value,
+            max_value: request.max_value,
+            min_length: request.min_length,
+            max_length: request.max_length,
+            pattern: request.pattern.clone(),
+            default_value: request.default_value.clone(),
+            formula: request.formula.clone(),
+            ..WorkAttributeType::new(
+                request.name.clone(),
+                request.data_type,
+                request.is_required,
+                request.is_hidden,
+            )
+        }
+    }
+
+    // Nueva definicion de un atributo existente: conserva su id, su clave y su posicion
+    pub fn redefine(&self, request: &CreateWorkAttributeType) -> Result<Self, AppError> {
+        if request.key.as_ref().is_some_and(|key| key != &self.key) {
+            return Err(AppError::Validation(format!(
+                "the key of attribute '{}' cannot be changed from '{}'",
+                self.name, self.key
+            )));
+        }
+        if self.is_system && request.name != self.name {
+            return Err(AppError::Validation(format!(
+                "system attribute '{}' cannot be renamed",
+                self.name
+            )));
+        }
+        if self.is_system && request.data_type != self.data_type {
+            return Err(AppError::Validation(format!(
+                "system attribute '{}' must be of type {}",
+                self.name, self.data_type
+            )));
+        }
+
+        Ok(Self {
+            id: self.id,
+            key: self.key.clone(),
+            position: self.position,
+            is_system: self.is_system,
+            created_at: self.created_at,
+            ..Self::from_create_request(request)
+        })
+    }
+
+    pub fn duplicate(&self) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            ..self.clone()
+        }
     }
 
-    #[must_use]
-    pub fn summary() -> Self {
-        Self::new("Summary".to_string(), DataType::StringType, true, false)
+    // Compara dos atributos ignorando sus marcas de tiempo y su posicion: el
+    // orden no cambia los valores que se aceptan
+    pub fn same_definition(&self, other: &WorkAttributeType) -> bool {
+        WorkAttributeType {
+            position: other.position,
+            created_at: other.created_at,
+            updated_at: other.updated_at,
+            ..self.clone()
+        } == *other
     }
+}
 
-    #[must_use]
-    pub fn description() -> Self {
-        Self::new("Description".to_string(), DataType::StringType, true, false)
+// Clave derivada del nombre de un atributo: "Steps to reproduce" pasa a ser
+// "steps_to_reproduce". Puede quedar vacia si el nombre no tiene letras ni numeros.
+pub fn attribute_key(name: &str) -> String {
+    let mut key = String::new();
+    for c in name.to_lowercase().chars() {
+        let c = match c {
+            'á' | 'à' | 'ä' | 'â' => 'a',
+            'é' | 'è' | 'ë' | 'ê' => 'e',
+            'í' | 'ì' | 'ï' | 'î' => 'i',
+            'ó' | 'ò' | 'ö' | 'ô' => 'o',
+            'ú' | 'ù' | 'ü' | 'û' => 'u',
+            'ñ' => 'n',
+            'ç' => 'c',
+            c => c,
+        };
+        if c.is_ascii_alphanumeric() {
+            key.push(c);
+        } else if !key.is_empty() && !key.ends_with('_') {
+            key.push('_');
+        }
+    }
+
+    let key = key.trim_end_matches('_');
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("attr_{}", key)
+    } else {
+        key.to_string()
+    }
+}
+
+impl Workflow {
+    pub fn from_create_request(request: CreateWorkflow) -> Self {
+        Self {
+            initial_status: request.initial_status,
+            statuses: request
+                .statuses
+                .into_iter()
+                .map(|status| WorkflowStatus {
+                    name: status.name,
+                    category: status.category,
+                    transitions: status.transitions,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn status(&self, name: &str) -> Option<&WorkflowStatus> {
+        self.statuses.iter().find(|status| status.name == name)
+    }
+
+    pub fn check_transition(&self, from: Option<&str>, to: &str) -> Result<(), AppError> {
+        if self.statuses.is_empty() {
+            return Err(AppError::Validation(
+                "the worktype of this work item has no workflow".to_string(),
+            ));
+        }
+
+        let allowed: Vec<String> = match from.and_then(|name| self.status(name)) {
+            Some(current) => current.transitions.clone(),
+            None => self.initial_status.iter().cloned().collect(),
+        };
+        if allowed.iter().any(|target| target == to) {
+            return Ok(());
+        }
+
+        Err(AppError::Validation(format!(
+            "transition from '{}' to '{}' is not allowed, allowed targets: [{}]",
+            from.unwrap_or_default(),
+            to,
+            allowed.join(", ")
+        )))
+    }
+}
+
+impl WorkItem {
+    // Los valores ya deben haber sido validados contra el tipo de trabajo
+    pub fn from_create_request(
+        work_type: &WorkType,
+        workflow: &Workflow,
+        request: CreateWorkItem,
+    ) -> Self {
+        let now = Utc::now();
+        let mut item = Self {
+            id: Uuid::new_v4(),
+            work_type_id: work_type.id,
+            schema_version: work_type.version,
+            status: workflow.initial_status.clone(),
+            work_attributes: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        item.set_values(work_type, request.values);
+        item
+    }
+
+    pub fn set_values(&mut self, work_type: &WorkType, mut values: HashMap<String, String>) {
+        let now = Utc::now();
+        let previous: Vec<WorkAttributeItem> = std::mem::take(&mut self.work_attributes);
+        self.work_attributes = work_type
+            .attributes
+            .iter()
+            .filter_map(|attribute| {
+                let value = values.remove(&attribute.key)?;
+                let item = match previous
+                    .iter()
+                    .find(|p| p.attribute_type_id == attribute.id)
+                {
+                    Some(p) if p.value == value => p.clone(),
+                    Some(p) => WorkAttributeItem {
+                        value,
+                        updated_at: now,
+                        ..p.clone()
+                    },
+                    None => WorkAttributeItem {
+                        id: Uuid::new_v4(),
+                        attribute_type_id: attribute.id,
+                        key: attribute.key.clone(),
+                        name: attribute.name.clone(),
+                        value,
+                        created_at: now,
+                        updated_at: now,
+                    },
+                };
+                Some(item)
+            })
+            .collect();
+        self.schema_version = work_type.version;
+        self.updated_at = now;
+    }
+}
+
+impl LinkType {
+    pub fn from_create_request(request: CreateLinkType) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name: request.name,
+            outward_name: request.outward_name,
+            inward_name: request.inward_name,
+            is_hierarchical: request.is_hierarchical,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn update(&mut self, request: CreateLinkType) {
+        self.name = request.name;
+        self.outward_name = request.outward_name;
+        self.inward_name = request.inward_name;
+        self.is_hierarchical = request.is_hierarchical;
+        self.updated_at = Utc::now();
+    }
+}
+
+impl WorkItemLink {
+    pub fn new(link_type_id: Uuid, source_id: Uuid, target_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            link_type_id,
+            source_id,
+            target_id,
+            created_at: Utc::now(),
+        }
+    }
+
+    // El enlace tal y como se ve desde `item_id`, que debe ser uno de sus extremos
+    pub fn seen_from(&self, item_id: Uuid, link_type: &LinkType) -> ItemLink {
+        let (direction, name, other) = if self.source_id == item_id {
+            (
+                LinkDirection::Outward,
+                &link_type.outward_name,
+                self.target_id,
+            )
+        } else {
+            (
+                LinkDirection::Inward,
+                &link_type.inward_name,
+                self.source_id,
+            )
+        };
+        ItemLink {
+            id: self.id,
+            link_type_id: self.link_type_id,
+            direction,
+            name: name.clone(),
+            item_id: other,
+            created_at: self.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::CreateWorkflowStatus;
+
+    fn attribute_request(name: &str, data_type: DataType) -> CreateWorkAttributeType {
+        CreateWorkAttributeType {
+            name: name.to_string(),
+            key: None,
+            data_type,
+            options: None,
+            is_required: false,
+            is_hidden: false,
+            min_value: None,
+            max_value: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            default_value: None,
+            formula: None,
+        }
+    }
+
+    fn work_type_request(title: &str, attributes: Vec<CreateWorkAttributeType>) -> CreateWorkType {
+        CreateWorkType {
+            title: title.to_string(),
+            description: None,
+            parent_id: None,
+            status: None,
+            attributes,
+        }
+    }
+
+    fn bug() -> WorkType {
+        WorkType::from_create_request(work_type_request(
+            "Bug",
+            vec![
+                attribute_request("Severity", DataType::NumericType),
+                attribute_request("Steps to reproduce", DataType::StringType),
+            ],
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_gives_new_ids_to_the_copy_and_its_attributes() {
+        let bug = WorkType {
+            version: 4,
+            ..bug()
+        };
+
+        let copy = bug.duplicate();
+
+        assert_ne!(copy.id, bug.id);
+        assert_eq!(copy.title, "Bug (copia)");
+        assert_eq!(copy.version, 1);
+        assert_eq!(copy.attributes.len(), bug.attributes.len());
+        for (copied, original) in copy.attributes.iter().zip(&bug.attributes) {
+            assert_ne!(copied.id, original.id);
+            assert_eq!(copied.key, original.key);
+            assert!(copied.same_definition(&WorkAttributeType {
+                id: copied.id,
+                ..original.clone()
+            }));
+        }
+    }
+
+    #[test]
+    fn test_duplicate_skips_inherited_attributes() {
+        let parent_id = Uuid::new_v4();
+        let mut child = WorkType {
+            parent_id: Some(parent_id),
+            ..bug()
+        };
+        child.attributes.push(WorkAttributeType {
+            inherited_from: Some(parent_id),
+            ..WorkAttributeType::new("Estimate".to_string(), DataType::NumericType, false, false)
+        });
+
+        let copy = child.duplicate();
+
+        assert_eq!(copy.parent_id, Some(parent_id));
+        assert!(copy.attributes.iter().all(|a| a.key != "estimate"));
+    }
+
+    #[test]
+    fn test_updated_attributes_keep_the_ids_of_matching_attributes() {
+        let bug = bug();
+        let severity = bug.attributes.iter().find(|a| a.key == "severity").unwrap();
+        let request = work_type_request(
+            "Bug",
+            vec![
+                attribute_request("Priority", DataType::StringType),
+                CreateWorkAttributeType {
+                    is_required: true,
+                    ..attribute_request("severity", DataType::NumericType)
+                },
+            ],
+        );
+
+        let attributes = bug.updated_attributes(&request).unwrap();
+        let keys: Vec<&str> = attributes.iter().map(|a| a.key.as_str()).collect();
+
+        assert_eq!(keys, ["priority", "severity", "summary", "description"]);
+        assert_eq!(attributes[1].id, severity.id);
+        assert!(attributes[1].is_required);
+        assert!(bug.attributes.iter().all(|a| a.id != attributes[0].id));
+        assert_eq!(
+            attributes.iter().map(|a| a.position).collect::<Vec<i32>>(),
+            [0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_updated_attributes_cannot_change_a_key() {
+        let request = work_type_request(
+            "Bug",
+            vec![CreateWorkAttributeType {
+                key: Some("severity".to_string()),
+                ..attribute_request("Severity", DataType::NumericType)
+            }],
+        );
+        assert!(bug().updated_attributes(&request).is_ok());
+
+        let attribute = bug().attributes[0].clone();
+        let renamed = CreateWorkAttributeType {
+            key: Some("level".to_string()),
+            ..attribute_request("Severity", DataType::NumericType)
+        };
+        assert!(matches!(
+            attribute.redefine(&renamed),
+            Err(AppError::Validation(message))
+                if message == "the key of attribute 'Severity' cannot be changed from 'severity'"
+        ));
+    }
+
+    #[test]
+    fn test_set_values_keeps_unchanged_values() {
+        let bug = bug();
+        let workflow = Workflow::default();
+        let request = CreateWorkItem {
+            values: HashMap::from([
+                ("severity".to_string(), "2".to_string()),
+                ("steps_to_reproduce".to_string(), "Open it".to_string()),
+            ]),
+        };
+        let mut item = WorkItem::from_create_request(&bug, &workflow, request);
+        let before = item.work_attributes.clone();
+
+        item.set_values(
+            &bug,
+            HashMap::from([
+                ("severity".to_string(), "3".to_string()),
+                ("steps_to_reproduce".to_string(), "Open it".to_string()),
+            ]),
+        );
+
+        let value = |item: &[WorkAttributeItem], key: &str| {
+            item.iter().find(|a| a.key == key).cloned().unwrap()
+        };
+        assert_eq!(
+            value(&item.work_attributes, "steps_to_reproduce"),
+            value(&before, "steps_to_reproduce")
+        );
+        let severity = value(&item.work_attributes, "severity");
+        assert_eq!(severity.id, value(&before, "severity").id);
+        assert_eq!(severity.value, "3");
+        assert_eq!(item.work_attributes.len(), 2);
+    }
+
+    #[test]
+    fn test_data_types_round_trip_through_their_names() {
+        for name in ["string", "numeric", "url", "decimal(10,3)"] {
+            assert_eq!(name.parse::<DataType>().unwrap().to_string(), name);
+        }
+        assert_eq!(
+            "Decimal".parse::<DataType>().unwrap(),
+            DataType::DEFAULT_DECIMAL
+        );
+        assert!(matches!(
+            "decimal(2,3)".parse::<DataType>(),
+            Err(AppError::Validation(message)) if message == "unknown data type: decimal(2,3)"
+        ));
+        assert!("decimal(0,0)".parse::<DataType>().is_err());
+    }
+
+    #[test]
+    fn test_apply_defaults_only_fills_missing_values() {
+        let bug = WorkType::from_create_request(work_type_request(
+            "Bug",
+            vec![
+                CreateWorkAttributeType {
+                    default_value: Some("3".to_string()),
+                    ..attribute_request("Severity", DataType::NumericType)
+                },
+                CreateWorkAttributeType {
+                    default_value: Some("ui".to_string()),
+                    ..attribute_request("Area", DataType::StringType)
+                },
+            ],
+        ))
+        .unwrap();
+        let mut values = HashMap::from([("area".to_string(), "api".to_string())]);
+
+        bug.apply_defaults(&mut values);
+
+        assert_eq!(
+            values,
+            HashMap::from([
+                ("severity".to_string(), "3".to_string()),
+                ("area".to_string(), "api".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_same_schema_ignores_positions_and_timestamps() {
+        let bug = bug();
+        let mut reordered: Vec<WorkAttributeType> = bug
+            .attributes
+            .iter()
+            .map(|attribute| WorkAttributeType {
+                position: attribute.position + 10,
+                updated_at: attribute.updated_at + chrono::Duration::hours(1),
+                ..attribute.clone()
+            })
+            .collect();
+        reordered.reverse();
+
+        assert!(bug.same_schema(&bug.attributes));
+        assert!(bug.same_schema(&reordered));
+    }
+
+    #[test]
+    fn test_same_schema_detects_definition_changes() {
+        let bug = bug();
+        let mut required = bug.attributes.clone();
+        required[0].is_required = true;
+        let mut removed = bug.attributes.clone();
+        removed.pop();
+        let mut added = bug.attributes.clone();
+        added.push(WorkAttributeType::new(
+            "Estimate".to_string(),
+            DataType::NumericType,
+            false,
+            false,
+        ));
+
+        assert!(!bug.same_schema(&required));
+        assert!(!bug.same_schema(&removed));
+        assert!(!bug.same_schema(&added));
+    }
+
+    #[test]
+    fn test_snapshot_records_the_current_version() {
+        let bug = WorkType {
+            version: 3,
+            ..bug()
+        };
+
+        let snapshot = bug.snapshot();
+
+        assert_eq!(snapshot.work_type_id, bug.id);
+        assert_eq!(snapshot.version, 3);
+        assert_eq!(snapshot.attributes, bug.attributes);
+        assert_eq!(snapshot.created_at, bug.updated_at);
+    }
+
+    fn with_attributes(
+        title: &str,
+        parent_id: Option<Uuid>,
+        attributes: &[(&str, DataType)],
+    ) -> WorkType {
+        WorkType {
+            parent_id,
+            attributes: attributes
+                .iter()
+                .map(|(name, data_type)| {
+                    WorkAttributeType::new(name.to_string(), *data_type, false, false)
+                })
+                .collect(),
+            ..WorkType::default(title.to_string())
+        }
+    }
+
+    #[test]
+    fn test_resolve_inherits_the_attributes_of_every_ancestor() {
+        let base = with_attributes("Base", None, &[("Summary", DataType::StringType)]);
+        let issue = with_attributes(
+            "Issue",
+            Some(base.id),
+            &[("Severity", DataType::NumericType)],
+        );
+        let bug = with_attributes(
+            "Bug",
+            Some(issue.id),
+            &[
+                ("Severity", DataType::DEFAULT_DECIMAL),
+                ("Browser", DataType::StringType),
+            ],
+        );
+        let work_types = HashMap::from([(base.id, base.clone()), (issue.id, issue.clone())]);
+
+        let resolved = bug.resolve(&work_types);
+        let attributes: Vec<(&str, DataType, Option<Uuid>)> = resolved
+            .attributes
+            .iter()
+            .map(|a| (a.key.as_str(), a.data_type, a.inherited_from))
+            .collect();
+
+        assert_eq!(
+            attributes,
+            [
+                ("summary", DataType::StringType, Some(base.id)),
+                ("severity", DataType::DEFAULT_DECIMAL, None),
+                ("browser", DataType::StringType, None),
+            ]
+        );
+        assert_eq!(resolved.own_attributes().count(), 2);
+    }
+
+    #[test]
+    fn test_resolve_stops_at_cycles() {
+        let mut first = with_attributes("First", None, &[("Alpha", DataType::StringType)]);
+        let second = with_attributes("Second", Some(first.id), &[("Beta", DataType::StringType)]);
+        first.parent_id = Some(second.id);
+        let work_types = HashMap::from([(first.id, first.clone()), (second.id, second.clone())]);
+
+        let keys: Vec<String> = first
+            .resolve(&work_types)
+            .attributes
+            .into_iter()
+            .map(|a| a.key)
+            .collect();
+
+        assert_eq!(keys, ["beta", "alpha"]);
+    }
+
+    #[test]
+    fn test_inherited_attributes_are_changed_in_their_owner() {
+        let issue = with_attributes("Issue", None, &[("Severity", DataType::NumericType)]);
+        let bug = with_attributes("Bug", Some(issue.id), &[]);
+        let resolved = bug.resolve(&HashMap::from([(issue.id, issue.clone())]));
+        let severity = issue.attributes[0].id;
+
+        assert!(matches!(
+            resolved.own_attribute(severity),
+            Err(AppError::Validation(message))
+                if message == format!("attribute 'Severity' is inherited from worktype {}", issue.id)
+        ));
+        assert!(matches!(issue.own_attribute(severity), Ok(Some(_))));
+        assert!(matches!(issue.own_attribute(Uuid::new_v4()), Ok(None)));
+    }
+
+    fn workflow() -> Workflow {
+        let status = |name: &str, category, transitions: &[&str]| CreateWorkflowStatus {
+            name: name.to_string(),
+            category,
+            transitions: transitions.iter().map(|t| t.to_string()).collect(),
+        };
+        Workflow::from_create_request(CreateWorkflow {
+            initial_status: Some("open".to_string()),
+            statuses: vec![
+                status("open", StatusCategory::Todo, &["doing"]),
+                status("doing", StatusCategory::InProgress, &["open", "done"]),
+                status("done", StatusCategory::Done, &[]),
+            ],
+        })
+    }
+
+    #[test]
+    fn test_check_transition_follows_the_workflow() {
+        let workflow = workflow();
+
+        assert!(workflow.check_transition(Some("open"), "doing").is_ok());
+        assert!(workflow.check_transition(Some("doing"), "done").is_ok());
+        assert!(matches!(
+            workflow.check_transition(Some("open"), "done"),
+            Err(AppError::Validation(message))
+                if message == "transition from 'open' to 'done' is not allowed, allowed targets: [doing]"
+        ));
+        assert!(matches!(
+            workflow.check_transition(Some("done"), "open"),
+            Err(AppError::Validation(message))
+                if message == "transition from 'done' to 'open' is not allowed, allowed targets: []"
+        ));
+    }
+
+    #[test]
+    fn test_check_transition_without_a_known_status_only_allows_the_initial_one() {
+        let workflow = workflow();
+
+        assert!(workflow.check_transition(None, "open").is_ok());
+        assert!(workflow.check_transition(Some("removed"), "open").is_ok());
+        assert!(workflow.check_transition(None, "doing").is_err());
+        assert!(matches!(
+            Workflow::default().check_transition(None, "open"),
+            Err(AppError::Validation(message))
+                if message == "the worktype of this work item has no workflow"
+        ));
+    }
+
+    #[test]
+    fn test_copy_for_company_keeps_the_title_and_takes_the_new_parent() {
+        let parent_id = Uuid::new_v4();
+        let bug = bug();
+
+        let copy = bug.copy_for_company("acme", Some(parent_id));
+
+        assert_ne!(copy.id, bug.id);
+        assert_eq!(copy.title, "Bug");
+        assert_eq!(copy.company_id.as_deref(), Some("acme"));
+        assert_eq!(copy.parent_id, Some(parent_id));
+        assert!(copy
+            .attributes
+            .iter()
+            .all(|a| bug.attributes.iter().all(|original| original.id != a.id)));
     }
 }
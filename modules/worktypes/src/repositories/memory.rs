@@ -0,0 +1,1225 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use common::error::{AppError, Result};
+
+use crate::bundle::{
+    export_order, plan_import, title_key, BundledWorkType, ImportAction, ImportOutcome,
+    PlannedImport, WorkTypeBundle, BUNDLE_VERSION,
+};
+use crate::filter::parse as parse_filter;
+use crate::history::{ChangeAction, HistoryPage, WorkItemChange};
+use crate::impact::{analyze_impact, ImpactReport, ProposedChange};
+use crate::migration::{migrate_values, MigrationReport, StoredValue};
+use crate::models::{
+    ItemLink, LinkType, WorkAttributeType, WorkItem, WorkItemLink, WorkType, WorkTypeStatus,
+    WorkTypeVersion, Workflow,
+};
+use crate::requests::{
+    ConflictPolicy, CreateLinkType, CreateWorkItem, CreateWorkItemLink, CreateWorkType,
+    CreateWorkflow, HistoryQuery, MigrateAttribute, TransitionWorkItem, WorkAttributeRequest,
+    WorkItemListQuery, WorkTypeQuery,
+};
+use crate::search::{match_item, search_terms, SearchHit};
+use crate::sync::{plan_sync, PlannedSync, SyncPlan};
+use crate::validation::{
+    validate_attribute_names, validate_attribute_request, validate_formulas, validate_link,
+    validate_link_type_request, validate_parent_scope, validate_parent_status,
+    validate_work_type_request, validate_workflow_request,
+};
+
+use super::repository::{LinkRepositoryTrait, WorkItemRepositoryTrait, WorkTypeRepositoryTrait};
+
+// Cada operacion valida todo antes de tocar el almacen y lo modifica bajo un
+// unico bloqueo de escritura, asi que o se aplica entera o no se aplica.
+pub struct MemoryWorkTypeRepository {
+    store: RwLock<MemoryStore>,
+}
+
+// Los tipos de trabajo se guardan solo con sus atributos propios, igual que
+// en las tablas, y se resuelven al leerlos
+#[derive(Clone, Default)]
+struct MemoryStore {
+    work_types: HashMap<Uuid, WorkType>,
+    versions: HashMap<Uuid, Vec<WorkTypeVersion>>,
+    workflows: HashMap<Uuid, Workflow>,
+    items: HashMap<Uuid, WorkItem>,
+    link_types: HashMap<Uuid, LinkType>,
+    links: Vec<WorkItemLink>,
+    // Historial de cada work item, del cambio mas antiguo al mas reciente
+    history: HashMap<Uuid, Vec<WorkItemChange>>,
+    // Compañias que el modulo de compañias ha dado de alta
+    companies: HashSet<String>,
+}
+
+impl MemoryWorkTypeRepository {
+    pub fn new() -> Self {
+        Self {
+            store: RwLock::new(MemoryStore::default()),
+        }
+    }
+}
+
+impl MemoryStore {
+    fn record(&mut self, change: WorkItemChange) {
+        self.history
+            .entry(change.work_item_id)
+            .or_default()
+            .push(change);
+    }
+
+    fn resolve(&self, id: Uuid) -> Option<WorkType> {
+        self.work_types
+            .get(&id)
+            .map(|wt| wt.resolve(&self.work_types))
+    }
+
+    // Tipos de trabajo globales y, si se indica, los propios de una compañia
+    fn work_types(&self, company_id: Option<&str>) -> Vec<WorkType> {
+        self.work_types
+            .values()
+            .filter(|wt| wt.company_id.is_none() || wt.company_id.as_deref() == company_id)
+            .map(|wt| wt.resolve(&self.work_types))
+            .collect()
+    }
+
+    fn descendants(&self, id: Uuid) -> Vec<Uuid> {
+        let mut descendants: Vec<Uuid> = Vec::new();
+        let mut pending: Vec<Uuid> = vec![id];
+        while let Some(current) = pending.pop() {
+            for wt in self.work_types.values() {
+                if wt.parent_id == Some(current) && !descendants.contains(&wt.id) {
+                    descendants.push(wt.id);
+                    pending.push(wt.id);
+                }
+            }
+        }
+        descendants
+    }
+
+    fn workflow(&self, work_type_id: Uuid) -> Workflow {
+        self.workflows
+            .get(&work_type_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn check_parent(
+        &self,
+        id: Option<Uuid>,
+        company_id: Option<&str>,
+        status: WorkTypeStatus,
+        parent_id: Uuid,
+    ) -> Result<()> {
+        let parent = self.resolve(parent_id).ok_or_else(|| {
+            AppError::Validation(format!("parent worktype {} does not exist", parent_id))
+        })?;
+        validate_parent_scope(company_id, &parent)?;
+        validate_parent_status(status, &parent)?;
+
+        if let Some(id) = id {
+            if parent_id == id || self.descendants(id).contains(&parent_id) {
+                return Err(AppError::Validation(format!(
+                    "worktype {} cannot inherit from itself or from one of its descendants",
+                    id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_work_type(
+        &mut self,
+        company_id: Option<String>,
+        request: CreateWorkType,
+    ) -> Result<WorkType> {
+        validate_work_type_request(&request)?;
+        let dao = WorkType {
+            company_id,
+            ..WorkType::from_create_request(request)?
+        };
+        if let Some(parent_id) = dao.parent_id {
+            self.check_parent(None, dao.company_id.as_deref(), dao.status, parent_id)?;
+        }
+
+        let resolved = dao.resolve(&self.work_types);
+        validate_attribute_names(&resolved)?;
+        validate_formulas(&resolved)?;
+        Ok(self.insert_work_type(dao))
+    }
+
+    // Inserta un tipo de trabajo nuevo y registra su primera version con los
+    // atributos ya resueltos
+    fn insert_work_type(&mut self, dao: WorkType) -> WorkType {
+        let own = WorkType {
+            attributes: dao.own_attributes().cloned().collect(),
+            ..dao
+        };
+        let id = own.id;
+        self.work_types.insert(id, own);
+
+        let work_type = self.resolve(id).unwrap();
+        self.versions.insert(id, vec![work_type.snapshot()]);
+        work_type
+    }
+
+    // Cualquier cambio en los atributos resueltos genera una nueva version del esquema
+    fn record_version_if_changed(
+        &mut self,
+        id: Uuid,
+        previous_attributes: &[WorkAttributeType],
+    ) -> WorkType {
+        let mut work_type = self.resolve(id).unwrap();
+
+        if !work_type.same_schema(previous_attributes) {
+            work_type.version += 1;
+            if let Some(stored) = self.work_types.get_mut(&id) {
+                stored.version = work_type.version;
+            }
+            self.versions
+                .entry(id)
+                .or_default()
+                .push(work_type.snapshot());
+        }
+
+        work_type
+    }
+
+    // Cuerpo de `update`, para reutilizarlo en otras operaciones. La peticion
+    // ya debe estar validada.
+    fn update_work_type(&mut self, id: Uuid, request: CreateWorkType) -> Result<Option<WorkType>> {
+        let previous = match self.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        previous.check_update_status(&request)?;
+        if let Some(parent_id) = request.parent_id {
+            self.check_parent(
+                Some(id),
+                previous.company_id.as_deref(),
+                previous.status,
+                parent_id,
+            )?;
+        }
+
+        let attributes = previous.updated_attributes(&request)?;
+
+        let candidate = WorkType {
+            title: request.title,
+            description: request.description,
+            parent_id: request.parent_id,
+            ..self.work_types[&id].clone()
+        };
+        self.save_attributes(&previous, candidate, attributes)
+            .map(Some)
+    }
+
+    // Guarda `candidate` con los atributos propios indicados, emparejados por
+    // id, y registra una nueva version del esquema si cambian
+    fn save_attributes(
+        &mut self,
+        previous: &WorkType,
+        candidate: WorkType,
+        attributes: Vec<WorkAttributeType>,
+    ) -> Result<WorkType> {
+        let id = previous.id;
+        let removed: Vec<Uuid> = previous
+            .own_attributes()
+            .filter(|old| !attributes.iter().any(|a| a.id == old.id))
+            .map(|old| old.id)
+            .collect();
+
+        let candidate = WorkType {
+            attributes,
+            updated_at: Utc::now(),
+            ..candidate
+        };
+
+        // Los nombres y las formulas del tipo y de sus descendientes se
+        // comprueban antes de tocar el almacen
+        let mut work_types = self.work_types.clone();
+        work_types.insert(id, candidate.clone());
+        for affected in std::iter::once(id).chain(self.descendants(id)) {
+            let resolved = work_types[&affected].resolve(&work_types);
+            validate_attribute_names(&resolved)?;
+            validate_formulas(&resolved)?;
+        }
+
+        // Los valores de los atributos eliminados desaparecen con ellos, y
+        // quedan en el historial de cada work item, y los de los renombrados
+        // siguen al nuevo nombre
+        let mut changes: Vec<WorkItemChange> = Vec::new();
+        for item in self.items.values_mut() {
+            let before = item.clone();
+            item.work_attributes
+                .retain(|value| !removed.contains(&value.attribute_type_id));
+            for value in item.work_attributes.iter_mut() {
+                if let Some(attribute) = candidate
+                    .attributes
+                    .iter()
+                    .find(|a| a.id == value.attribute_type_id)
+                {
+                    value.name = attribute.name.clone();
+                }
+            }
+            changes.extend(WorkItemChange::by_schema(&before, item));
+        }
+        for change in changes {
+            self.record(change);
+        }
+        self.work_types.insert(id, candidate);
+
+        let work_type = self.record_version_if_changed(id, &previous.attributes);
+
+        // Los descendientes resuelven sus atributos a traves de este tipo de trabajo
+        if !work_type.same_schema(&previous.attributes) {
+            for descendant_id in self.descendants(id) {
+                let latest = self
+                    .versions
+                    .get(&descendant_id)
+                    .and_then(|versions| versions.last())
+                    .map(|v| v.attributes.clone())
+                    .unwrap_or_default();
+                self.record_version_if_changed(descendant_id, &latest);
+            }
+        }
+
+        Ok(work_type)
+    }
+
+    // Sustituye el flujo de trabajo de un tipo de trabajo existente
+    fn replace_workflow(&mut self, id: Uuid, workflow: Workflow) -> Result<()> {
+        // Quitar el flujo de trabajo deja los work items sin estado; en otro caso
+        // no se puede eliminar un estado en el que todavia hay work items
+        if workflow.statuses.is_empty() {
+            for item in self
+                .items
+                .values_mut()
+                .filter(|item| item.work_type_id == id)
+            {
+                item.status = None;
+            }
+        } else {
+            let mut orphaned: Vec<(String, usize)> = Vec::new();
+            for status in self
+                .items
+                .values()
+                .filter(|item| item.work_type_id == id)
+                .filter_map(|item| item.status.as_ref())
+                .filter(|status| workflow.status(status).is_none())
+            {
+                match orphaned.iter_mut().find(|(name, _)| name == status) {
+                    Some((_, count)) => *count += 1,
+                    None => orphaned.push((status.clone(), 1)),
+                }
+            }
+
+            if !orphaned.is_empty() {
+                let in_use: Vec<String> = orphaned
+                    .iter()
+                    .map(|(status, count)| format!("'{}' ({} work items)", status, count))
+                    .collect();
+                return Err(AppError::Validation(format!(
+                    "statuses still in use cannot be removed: {}",
+                    in_use.join(", ")
+                )));
+            }
+
+            for item in self
+                .items
+                .values_mut()
+                .filter(|item| item.work_type_id == id && item.status.is_none())
+            {
+                item.status = workflow.initial_status.clone();
+            }
+        }
+
+        self.workflows.insert(id, workflow);
+        Ok(())
+    }
+
+    // Ejecuta un paso de una importacion; los padres del paquete ya estan en `imported`
+    fn apply_import_step(
+        &mut self,
+        plan: &PlannedImport,
+        imported: &HashMap<String, Uuid>,
+    ) -> Result<WorkType> {
+        let request = CreateWorkType {
+            parent_id: plan.parent_id(imported),
+            ..plan.request.clone()
+        };
+        let workflow = Workflow::from_create_request(plan.workflow.clone());
+        match (plan.action, plan.existing_id) {
+            (ImportAction::Skipped, Some(id)) => self.resolve(id),
+            (ImportAction::Overwritten, Some(id)) => {
+                let work_type = self.update_work_type(id, request)?;
+                self.replace_workflow(id, workflow)?;
+                work_type
+            }
+            _ => {
+                let work_type = self.create_work_type(None, request)?;
+                self.workflows.insert(work_type.id, workflow);
+                Some(work_type)
+            }
+        }
+        .ok_or_else(|| {
+            AppError::Internal(format!("Worktype '{}' lost while importing", plan.title))
+        })
+    }
+
+    // Cuerpo de `delete`: no se borra un tipo de trabajo del que heredan otros
+    fn delete_work_type(&mut self, id: Uuid) -> Result<bool> {
+        let children = self
+            .work_types
+            .values()
+            .filter(|wt| wt.parent_id == Some(id))
+            .count();
+        if children > 0 {
+            return Err(AppError::Validation(format!(
+                "worktype {} cannot be deleted while {} worktypes inherit from it",
+                id, children
+            )));
+        }
+
+        if !self.work_types.contains_key(&id) {
+            return Ok(false);
+        }
+        self.remove_work_type(id);
+        Ok(true)
+    }
+
+    fn remove_work_type(&mut self, id: Uuid) {
+        self.work_types.remove(&id);
+        self.versions.remove(&id);
+        self.workflows.remove(&id);
+        self.items.retain(|_, item| item.work_type_id != id);
+        let items = &self.items;
+        self.history
+            .retain(|item_id, _| items.contains_key(item_id));
+        self.remove_dangling_links();
+    }
+
+    // Los enlaces desaparecen con cualquiera de sus work items
+    fn remove_dangling_links(&mut self) {
+        let items = &self.items;
+        self.links.retain(|link| {
+            items.contains_key(&link.source_id) && items.contains_key(&link.target_id)
+        });
+    }
+
+    // Los nombres de los tipos de enlace no distinguen mayusculas
+    fn check_link_type_name(&self, id: Uuid, name: &str) -> Result<()> {
+        let taken = self
+            .link_types
+            .values()
+            .any(|lt| lt.id != id && lt.name.to_lowercase() == name.to_lowercase());
+        if taken {
+            return Err(AppError::Validation(format!(
+                "a link type named '{}' already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl WorkTypeRepositoryTrait for MemoryWorkTypeRepository {
+    async fn list(&self, query: WorkTypeQuery) -> Result<Vec<WorkType>> {
+        let store = self.store.read().unwrap();
+        Ok(query.apply(store.work_types(None)))
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<WorkType>> {
+        let store = self.store.read().unwrap();
+        Ok(store.resolve(id))
+    }
+
+    async fn create(&self, request: CreateWorkType) -> Result<WorkType> {
+        let mut store = self.store.write().unwrap();
+        store.create_work_type(None, request)
+    }
+
+    async fn update(&self, id: Uuid, request: CreateWorkType) -> Result<Option<WorkType>> {
+        validate_work_type_request(&request)?;
+        let mut store = self.store.write().unwrap();
+        store.update_work_type(id, request)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let mut store = self.store.write().unwrap();
+        store.delete_work_type(id)
+    }
+
+    async fn duplicate(&self, id: Uuid) -> Result<Option<WorkType>> {
+        let mut store = self.store.write().unwrap();
+
+        let original = match store.resolve(id) {
+            Some(original) => original,
+            None => return Ok(None),
+        };
+        let work_type = store.insert_work_type(original.duplicate());
+
+        let workflow = store.workflow(id);
+        store.workflows.insert(work_type.id, workflow);
+
+        Ok(Some(work_type))
+    }
+
+    async fn list_versions(&self, id: Uuid) -> Result<Option<Vec<WorkTypeVersion>>> {
+        let store = self.store.read().unwrap();
+        if !store.work_types.contains_key(&id) {
+            return Ok(None);
+        }
+        Ok(Some(store.versions.get(&id).cloned().unwrap_or_default()))
+    }
+
+    async fn get_version(&self, id: Uuid, version: i32) -> Result<Option<WorkTypeVersion>> {
+        let store = self.store.read().unwrap();
+        Ok(store
+            .versions
+            .get(&id)
+            .and_then(|versions| versions.iter().find(|v| v.version == version))
+            .cloned())
+    }
+
+    async fn get_workflow(&self, id: Uuid) -> Result<Option<Workflow>> {
+        let store = self.store.read().unwrap();
+        if !store.work_types.contains_key(&id) {
+            return Ok(None);
+        }
+        Ok(Some(store.workflow(id)))
+    }
+
+    async fn set_workflow(&self, id: Uuid, request: CreateWorkflow) -> Result<Option<Workflow>> {
+        validate_workflow_request(&request)?;
+        let mut store = self.store.write().unwrap();
+        if !store.work_types.contains_key(&id) {
+            return Ok(None);
+        }
+
+        let workflow = Workflow::from_create_request(request);
+        store.replace_workflow(id, workflow.clone())?;
+        Ok(Some(workflow))
+    }
+
+    async fn set_status(&self, id: Uuid, status: WorkTypeStatus) -> Result<Option<WorkType>> {
+        let mut store = self.store.write().unwrap();
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        previous.check_status_change(status)?;
+        if previous.status == status {
+            return Ok(Some(previous));
+        }
+        if let Some(parent) = previous
+            .parent_id
+            .and_then(|parent_id| store.resolve(parent_id))
+        {
+            validate_parent_status(status, &parent)?;
+        }
+
+        let work_type = store.work_types.get_mut(&id).unwrap();
+        work_type.status = status;
+        work_type.updated_at = Utc::now();
+        Ok(store.resolve(id))
+    }
+
+    async fn create_attribute(
+        &self,
+        id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>> {
+        validate_attribute_request(&request.attribute)?;
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = WorkAttributeType::from_create_request(&request.attribute);
+        let attributes = previous.place_attribute(attribute.clone(), request.position)?;
+        let candidate = store.work_types[&id].clone();
+        let work_type = store.save_attributes(&previous, candidate, attributes)?;
+
+        Ok(work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute.id))
+    }
+
+    async fn update_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>> {
+        validate_attribute_request(&request.attribute)?;
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = match previous.own_attribute(attribute_id)? {
+            Some(attribute) => attribute.redefine(&request.attribute)?,
+            None => return Ok(None),
+        };
+        let attributes = previous.place_attribute(attribute, request.position)?;
+        let candidate = store.work_types[&id].clone();
+        let work_type = store.save_attributes(&previous, candidate, attributes)?;
+
+        Ok(work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute_id))
+    }
+
+    async fn delete_attribute(&self, id: Uuid, attribute_id: Uuid) -> Result<bool> {
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(false),
+        };
+        if previous.own_attribute(attribute_id)?.is_none() {
+            return Ok(false);
+        }
+        let attributes = previous.remove_attribute(attribute_id)?;
+        let candidate = store.work_types[&id].clone();
+        store.save_attributes(&previous, candidate, attributes)?;
+
+        Ok(true)
+    }
+
+    async fn migrate_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: MigrateAttribute,
+    ) -> Result<Option<MigrationReport>> {
+        validate_attribute_request(&request.attribute.attribute)?;
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = match previous.own_attribute(attribute_id)? {
+            Some(attribute) => attribute.redefine(&request.attribute.attribute)?,
+            None => return Ok(None),
+        };
+        let attributes = previous.place_attribute(attribute, request.attribute.position)?;
+
+        // Si la migracion se aborta el almacen queda como estaba
+        let mut draft = store.clone();
+        let candidate = draft.work_types[&id].clone();
+        let work_type = draft.save_attributes(&previous, candidate, attributes)?;
+        let attribute = work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute_id)
+            .ok_or_else(|| AppError::Internal("migrated attribute not saved".to_string()))?;
+
+        let mut items: Vec<&WorkItem> = draft.items.values().collect();
+        items.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        let values: Vec<StoredValue> = items
+            .iter()
+            .flat_map(|item| {
+                item.work_attributes
+                    .iter()
+                    .filter(|value| value.attribute_type_id == attribute_id)
+                    .map(|value| StoredValue {
+                        id: value.id,
+                        work_item_id: item.id,
+                        value: value.value.clone(),
+                    })
+            })
+            .collect();
+        let (report, changes) = migrate_values(&attribute, &values, request.on_failure)?;
+
+        let now = Utc::now();
+        let mut history: Vec<WorkItemChange> = Vec::new();
+        for item in draft.items.values_mut() {
+            let before = item.clone();
+            item.work_attributes.retain_mut(|value| {
+                match changes.iter().find(|(id, _)| *id == value.id) {
+                    Some((_, Some(migrated))) => {
+                        value.value = migrated.clone();
+                        value.updated_at = now;
+                        true
+                    }
+                    Some((_, None)) => false,
+                    None => true,
+                }
+            });
+            history.extend(WorkItemChange::by_schema(&before, item));
+        }
+        for change in history {
+            draft.record(change);
+        }
+        *store = draft;
+
+        Ok(Some(report))
+    }
+
+    async fn analyze_impact(
+        &self,
+        id: Uuid,
+        change: ProposedChange,
+    ) -> Result<Option<ImpactReport>> {
+        let store = self.store.read().unwrap();
+
+        let previous = match store.resolve(id) {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attributes = match previous.proposed_attributes(&change)? {
+            Some(attributes) => attributes,
+            None => return Ok(None),
+        };
+        let affected: Vec<Uuid> = std::iter::once(id).chain(store.descendants(id)).collect();
+        let mut items: Vec<WorkItem> = store
+            .items
+            .values()
+            .filter(|item| affected.contains(&item.work_type_id))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        // El cambio se aplica sobre una copia del almacen que luego se descarta
+        let mut draft = store.clone();
+        let candidate = draft.work_types[&id].clone();
+        draft.save_attributes(&previous, candidate, attributes)?;
+
+        let resolve = |store: &MemoryStore| -> HashMap<Uuid, WorkType> {
+            affected
+                .iter()
+                .filter_map(|id| store.resolve(*id).map(|wt| (*id, wt)))
+                .collect()
+        };
+        Ok(Some(analyze_impact(
+            &resolve(&store),
+            &resolve(&draft),
+            &items,
+        )))
+    }
+
+    async fn export_bundle(&self, ids: Vec<Uuid>) -> Result<WorkTypeBundle> {
+        let store = self.store.read().unwrap();
+
+        let work_types = store.work_types(None);
+        let work_types = export_order(&work_types, &ids)?
+            .into_iter()
+            .map(|work_type| {
+                let parent = work_type
+                    .parent_id
+                    .and_then(|parent_id| store.resolve(parent_id));
+                BundledWorkType::new(work_type, parent.as_ref(), &store.workflow(work_type.id))
+            })
+            .collect();
+
+        Ok(WorkTypeBundle {
+            version: BUNDLE_VERSION,
+            work_types,
+        })
+    }
+
+    // Se importa sobre una copia del almacen que solo sustituye al original si
+    // todos los pasos salen bien
+    async fn import_bundle(
+        &self,
+        bundle: WorkTypeBundle,
+        policy: ConflictPolicy,
+    ) -> Result<Vec<ImportOutcome>> {
+        let mut store = self.store.write().unwrap();
+        let plans = plan_import(bundle, &store.work_types(None), policy)?;
+
+        let mut draft = store.clone();
+        let mut imported: HashMap<String, Uuid> = HashMap::new();
+        let mut outcomes: Vec<ImportOutcome> = Vec::new();
+        for plan in plans {
+            let work_type = draft.apply_import_step(&plan, &imported)?;
+            imported.insert(title_key(&plan.title), work_type.id);
+            outcomes.push(ImportOutcome {
+                title: plan.title,
+                action: plan.action,
+                work_type,
+            });
+        }
+
+        *store = draft;
+        Ok(outcomes)
+    }
+
+    // Igual que la importacion, se trabaja sobre una copia del almacen
+    async fn sync(&self, document: WorkTypeBundle, apply: bool) -> Result<SyncPlan> {
+        let mut store = self.store.write().unwrap();
+        let existing = store.work_types(None);
+        let workflows: HashMap<Uuid, Workflow> = existing
+            .iter()
+            .map(|wt| (wt.id, store.workflow(wt.id)))
+            .collect();
+        let PlannedSync {
+            mut plan,
+            steps,
+            deletions,
+        } = plan_sync(document, &existing, &workflows)?;
+        if !apply {
+            return Ok(plan);
+        }
+
+        let mut draft = store.clone();
+        let mut imported: HashMap<String, Uuid> = HashMap::new();
+        for step in steps {
+            let id = match (step.change, step.import.existing_id) {
+                (None, Some(id)) => id,
+                (change, _) => {
+                    let work_type = draft.apply_import_step(&step.import, &imported)?;
+                    if let Some(change) = change {
+                        plan.changes[change].work_type_id = Some(work_type.id);
+                    }
+                    work_type.id
+                }
+            };
+            imported.insert(title_key(&step.import.title), id);
+        }
+        for id in deletions {
+            draft.delete_work_type(id)?;
+        }
+
+        *store = draft;
+        plan.applied = true;
+        Ok(plan)
+    }
+
+    // Las compañias existen si el modulo de compañias las ha dado de alta
+    async fn list_for_company(
+        &self,
+        company_id: &str,
+        query: WorkTypeQuery,
+    ) -> Result<Option<Vec<WorkType>>> {
+        let store = self.store.read().unwrap();
+        if !store.companies.contains(company_id) {
+            return Ok(None);
+        }
+        Ok(Some(query.apply(store.work_types(Some(company_id)))))
+    }
+
+    async fn create_for_company(
+        &self,
+        company_id: &str,
+        request: CreateWorkType,
+    ) -> Result<Option<WorkType>> {
+        let mut store = self.store.write().unwrap();
+        if !store.companies.contains(company_id) {
+            return Ok(None);
+        }
+        store
+            .create_work_type(Some(company_id.to_string()), request)
+            .map(Some)
+    }
+
+    async fn add_company(&self, company_id: &str) -> Result<()> {
+        let mut store = self.store.write().unwrap();
+        store.companies.insert(company_id.to_string());
+        Ok(())
+    }
+
+    async fn duplicate_for_company(
+        &self,
+        from_company_id: &str,
+        to_company_id: &str,
+    ) -> Result<u64> {
+        let mut store = self.store.write().unwrap();
+
+        let mut pending: Vec<WorkType> = store
+            .work_types
+            .values()
+            .filter(|wt| wt.company_id.as_deref() == Some(from_company_id))
+            .cloned()
+            .collect();
+        pending.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+
+        // Los padres se copian antes que sus hijos para que la herencia
+        // apunte a las copias
+        let mut draft = store.clone();
+        let mut copies: HashMap<Uuid, Uuid> = HashMap::new();
+        while !pending.is_empty() {
+            let position = pending
+                .iter()
+                .position(|wt| {
+                    wt.parent_id
+                        .is_none_or(|parent_id| !pending.iter().any(|other| other.id == parent_id))
+                })
+                .ok_or_else(|| {
+                    AppError::Internal(format!(
+                        "Inheritance cycle in the worktypes of the company {}",
+                        from_company_id
+                    ))
+                })?;
+            let original = pending.remove(position);
+
+            let parent_id = original
+                .parent_id
+                .map(|parent_id| copies.get(&parent_id).copied().unwrap_or(parent_id));
+            let dao = original.copy_for_company(to_company_id, parent_id);
+            let work_type = draft.insert_work_type(dao);
+
+            let workflow = draft.workflow(original.id);
+            draft.workflows.insert(work_type.id, workflow);
+
+            copies.insert(original.id, work_type.id);
+        }
+        *store = draft;
+
+        Ok(copies.len() as u64)
+    }
+
+    async fn delete_for_company(&self, company_id: &str) -> Result<u64> {
+        let mut store = self.store.write().unwrap();
+
+        let ids: Vec<Uuid> = store
+            .work_types
+            .values()
+            .filter(|wt| wt.company_id.as_deref() == Some(company_id))
+            .map(|wt| wt.id)
+            .collect();
+        for id in &ids {
+            store.remove_work_type(*id);
+        }
+        store.companies.remove(company_id);
+
+        Ok(ids.len() as u64)
+    }
+}
+
+#[async_trait]
+impl WorkItemRepositoryTrait for MemoryWorkTypeRepository {
+    async fn list(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<WorkItem>>> {
+        let store = self.store.read().unwrap();
+        let work_type = match store.resolve(work_type_id) {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        let filter = query
+            .filter
+            .as_deref()
+            .map(|filter| parse_filter(filter, &work_type))
+            .transpose()?;
+
+        let mut items: Vec<WorkItem> = store
+            .items
+            .values()
+            .filter(|item| item.work_type_id == work_type_id)
+            .filter(|item| filter.as_ref().is_none_or(|filter| filter.matches(item)))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(Some(items))
+    }
+
+    async fn search(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<SearchHit>>> {
+        let terms = search_terms(query.q.as_deref().unwrap_or_default())?;
+        let store = self.store.read().unwrap();
+        let work_type = match store.resolve(work_type_id) {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        let filter = query
+            .filter
+            .as_deref()
+            .map(|filter| parse_filter(filter, &work_type))
+            .transpose()?;
+
+        let mut hits: Vec<SearchHit> = store
+            .items
+            .values()
+            .filter(|item| item.work_type_id == work_type_id)
+            .filter(|item| filter.as_ref().is_none_or(|filter| filter.matches(item)))
+            .filter_map(|item| match_item(&work_type, item, &terms))
+            .collect();
+        hits.sort_by(|a, b| {
+            b.rank
+                .total_cmp(&a.rank)
+                .then(a.item.created_at.cmp(&b.item.created_at))
+                .then(a.item.id.cmp(&b.item.id))
+        });
+        Ok(Some(hits))
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<WorkItem>> {
+        let store = self.store.read().unwrap();
+        Ok(store.items.get(&id).cloned())
+    }
+
+    async fn create(
+        &self,
+        work_type_id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        let mut store = self.store.write().unwrap();
+
+        let work_type = match store.resolve(work_type_id) {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        work_type.check_accepts_items()?;
+        let mut request = request;
+        work_type.prepare_values(&mut request.values)?;
+
+        let workflow = store.workflow(work_type_id);
+        let dao = WorkItem::from_create_request(&work_type, &workflow, request);
+        store.record(WorkItemChange::between(
+            ChangeAction::Created,
+            None,
+            &dao,
+            changed_by,
+        ));
+        store.items.insert(dao.id, dao.clone());
+        Ok(Some(dao))
+    }
+
+    async fn update(
+        &self,
+        id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.items.get(&id) {
+            Some(item) => item.clone(),
+            None => return Ok(None),
+        };
+        let work_type = store.resolve(previous.work_type_id).ok_or_else(|| {
+            AppError::Internal(format!("Work item {} without worktype", previous.id))
+        })?;
+        // Sin `check_accepts_items`: un tipo obsoleto no admite work items
+        // nuevos pero los que ya tiene se pueden seguir editando, y un
+        // borrador nunca tiene work items
+        let mut values = request.values;
+        work_type.prepare_values(&mut values)?;
+
+        let mut dao = previous.clone();
+        dao.set_values(&work_type, values);
+        store.record(WorkItemChange::between(
+            ChangeAction::Updated,
+            Some(&previous),
+            &dao,
+            changed_by,
+        ));
+        store.items.insert(dao.id, dao.clone());
+        Ok(Some(dao))
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        let mut store = self.store.write().unwrap();
+        let deleted = store.items.remove(&id).is_some();
+        store.history.remove(&id);
+        store.remove_dangling_links();
+        Ok(deleted)
+    }
+
+    async fn transition(
+        &self,
+        id: Uuid,
+        request: TransitionWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        let mut store = self.store.write().unwrap();
+
+        let previous = match store.items.get(&id) {
+            Some(item) => item.clone(),
+            None => return Ok(None),
+        };
+        let workflow = store.workflow(previous.work_type_id);
+        workflow.check_transition(previous.status.as_deref(), &request.to)?;
+
+        let mut dao = previous.clone();
+        dao.status = Some(request.to);
+        dao.updated_at = Utc::now();
+        store.record(WorkItemChange::between(
+            ChangeAction::Transitioned,
+            Some(&previous),
+            &dao,
+            changed_by,
+        ));
+        store.items.insert(dao.id, dao.clone());
+        Ok(Some(dao))
+    }
+
+    async fn history(&self, id: Uuid, query: HistoryQuery) -> Result<Option<HistoryPage>> {
+        let (page, per_page) = query.pagination()?;
+        let store = self.store.read().unwrap();
+        if !store.items.contains_key(&id) {
+            return Ok(None);
+        }
+
+        let history = store
+            .history
+            .get(&id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let entries = history
+            .iter()
+            .rev()
+            .skip((page as usize - 1) * per_page as usize)
+            .take(per_page as usize)
+            .cloned()
+            .collect();
+        Ok(Some(HistoryPage {
+            page,
+            per_page,
+            total: history.len() as u64,
+            entries,
+        }))
+    }
+}
+
+#[async_trait]
+impl LinkRepositoryTrait for MemoryWorkTypeRepository {
+    async fn list_link_types(&self) -> Result<Vec<LinkType>> {
+        let store = self.store.read().unwrap();
+        let mut link_types: Vec<LinkType> = store.link_types.values().cloned().collect();
+        link_types.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then(a.id.cmp(&b.id))
+        });
+        Ok(link_types)
+    }
+
+    async fn get_link_type(&self, id: Uuid) -> Result<Option<LinkType>> {
+        let store = self.store.read().unwrap();
+        Ok(store.link_types.get(&id).cloned())
+    }
+
+    async fn create_link_type(&self, request: CreateLinkType) -> Result<LinkType> {
+        validate_link_type_request(&request)?;
+        let mut store = self.store.write().unwrap();
+
+        let dao = LinkType::from_create_request(request);
+        store.check_link_type_name(dao.id, &dao.name)?;
+        store.link_types.insert(dao.id, dao.clone());
+        Ok(dao)
+    }
+
+    async fn update_link_type(
+        &self,
+        id: Uuid,
+        request: CreateLinkType,
+    ) -> Result<Option<LinkType>> {
+        validate_link_type_request(&request)?;
+        let mut store = self.store.write().unwrap();
+
+        let mut dao = match store.link_types.get(&id) {
+            Some(link_type) => link_type.clone(),
+            None => return Ok(None),
+        };
+        store.check_link_type_name(id, &request.name)?;
+
+        // Los enlaces existentes se validaron con la jerarquia actual
+        let links = store
+            .links
+            .iter()
+            .filter(|link| link.link_type_id == id)
+            .count();
+        if dao.is_hierarchical != request.is_hierarchical && links > 0 {
+            return Err(AppError::Validation(format!(
+                "link type '{}' cannot change its hierarchy while it has {} links",
+                dao.name, links
+            )));
+        }
+
+        dao.update(request);
+        store.link_types.insert(id, dao.clone());
+        Ok(Some(dao))
+    }
+
+    async fn delete_link_type(&self, id: Uuid) -> Result<bool> {
+        let mut store = self.store.write().unwrap();
+        if store.link_types.remove(&id).is_none() {
+            return Ok(false);
+        }
+        store.links.retain(|link| link.link_type_id != id);
+        Ok(true)
+    }
+
+    async fn list_links(&self, item_id: Uuid) -> Result<Option<Vec<ItemLink>>> {
+        let store = self.store.read().unwrap();
+        if !store.items.contains_key(&item_id) {
+            return Ok(None);
+        }
+
+        // Los enlaces se guardan en orden de creacion
+        let links = store
+            .links
+            .iter()
+            .filter(|link| link.source_id == item_id || link.target_id == item_id)
+            .map(|link| link.seen_from(item_id, &store.link_types[&link.link_type_id]))
+            .collect();
+        Ok(Some(links))
+    }
+
+    async fn add_link(
+        &self,
+        item_id: Uuid,
+        request: CreateWorkItemLink,
+    ) -> Result<Option<WorkItemLink>> {
+        let mut store = self.store.write().unwrap();
+        if !store.items.contains_key(&item_id) {
+            return Ok(None);
+        }
+        let link_type = store
+            .link_types
+            .get(&request.link_type_id)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::Validation(format!("link type {} does not exist", request.link_type_id))
+            })?;
+        if !store.items.contains_key(&request.target_id) {
+            return Err(AppError::Validation(format!(
+                "work item {} does not exist",
+                request.target_id
+            )));
+        }
+
+        let links: Vec<WorkItemLink> = store
+            .links
+            .iter()
+            .filter(|link| link.link_type_id == link_type.id)
+            .cloned()
+            .collect();
+        validate_link(&link_type, item_id, request.target_id, &links)?;
+
+        let dao = WorkItemLink::new(link_type.id, item_id, request.target_id);
+        store.links.push(dao.clone());
+        Ok(Some(dao))
+    }
+
+    async fn remove_link(&self, item_id: Uuid, link_id: Uuid) -> Result<bool> {
+        let mut store = self.store.write().unwrap();
+        let before = store.links.len();
+        store.links.retain(|link| {
+            link.id != link_id || (link.source_id != item_id && link.target_id != item_id)
+        });
+        Ok(store.links.len() < before)
+    }
+}
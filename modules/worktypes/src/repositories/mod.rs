@@ -1,2 +1,9 @@
+pub mod memory;
 pub mod postgres;
+pub mod postgres_filter;
+pub mod postgres_history;
+pub mod postgres_items;
+pub mod postgres_links;
+pub mod postgres_search;
+pub mod postgres_values;
 pub mod repository;
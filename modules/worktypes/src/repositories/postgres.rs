@@ -3,13 +3,36 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::query::Query;
-use sqlx::{query_as, Database};
+use sqlx::types::Json;
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::models::{DataType, WorkAttributeType, WorkType};
-use crate::requests::CreateWorkType;
+use crate::bundle::{
+    export_order, plan_import, title_key, BundledWorkType, ImportAction, ImportOutcome,
+    PlannedImport, WorkTypeBundle, BUNDLE_VERSION,
+};
+use crate::formula::legacy_references_to_keys;
+use crate::impact::{analyze_impact, ImpactReport, ProposedChange};
+use crate::migration::{migrate_values, MigrationReport, StoredValue};
+use crate::models::{
+    DataType, StatusCategory, WorkAttributeType, WorkType, WorkTypeStatus, WorkTypeVersion,
+    Workflow, WorkflowStatus,
+};
+use crate::requests::{
+    ConflictPolicy, CreateWorkType, CreateWorkflow, MigrateAttribute, SortOrder,
+    WorkAttributeRequest, WorkTypeQuery, WorkTypeSort,
+};
+use crate::sync::{plan_sync, PlannedSync, SyncPlan};
+use crate::system::SystemAttributes;
+use crate::validation::{
+    validate_attribute_names, validate_attribute_request, validate_formulas, validate_parent_scope,
+    validate_parent_status, validate_work_type_request, validate_workflow_request,
+};
 
+use super::postgres_filter::escape_like;
+use super::postgres_history::{record_value_changes, snapshot_items};
+use super::postgres_items::fetch_work_items;
+use super::postgres_values::{added_value_keys, refresh_documents, spawn_value_indexes};
 use super::repository::WorkTypeRepositoryTrait;
 use common::error::AppError;
 use common::error::Result;
@@ -34,6 +57,238 @@ pub static QUERY: &str = "
                         created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
                         updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
                     );
+
+                    CREATE TABLE IF NOT EXISTS work_item (
+                        id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                        work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                    );
+
+                    CREATE TABLE IF NOT EXISTS work_attribute_item (
+                        id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                        work_item_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+                        attribute_type_id UUID NOT NULL REFERENCES work_attribute_type(id) ON DELETE CASCADE,
+                        value TEXT NOT NULL,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_work_item_work_type ON work_item(work_type_id);
+                    CREATE INDEX IF NOT EXISTS idx_work_attribute_item_work_item ON work_attribute_item(work_item_id);
+
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS options TEXT[];
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS min_value DOUBLE PRECISION;
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS max_value DOUBLE PRECISION;
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS min_length INTEGER;
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS max_length INTEGER;
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS pattern TEXT;
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS default_value TEXT;
+
+                    ALTER TABLE work_type ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+                    CREATE TABLE IF NOT EXISTS work_type_version (
+                        work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+                        version INTEGER NOT NULL,
+                        attributes JSONB NOT NULL,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        PRIMARY KEY (work_type_id, version)
+                    );
+
+                    ALTER TABLE work_item ADD COLUMN IF NOT EXISTS schema_version INTEGER NOT NULL DEFAULT 1;
+
+                    -- Version inicial para los tipos de trabajo creados antes del versionado
+                    INSERT INTO work_type_version (work_type_id, version, attributes, created_at)
+                    SELECT
+                        wt.id,
+                        wt.version,
+                        COALESCE(
+                            (
+                                SELECT jsonb_agg(jsonb_build_object(
+                                    'id', wat.id,
+                                    'name', wat.name,
+                                    'data_type', wat.data_type,
+                                    'options', wat.options,
+                                    'is_required', wat.is_required,
+                                    'is_hidden', wat.is_hidden,
+                                    'min_value', wat.min_value,
+                                    'max_value', wat.max_value,
+                                    'min_length', wat.min_length,
+                                    'max_length', wat.max_length,
+                                    'pattern', wat.pattern,
+                                    'default_value', wat.default_value,
+                                    'created_at', wat.created_at,
+                                    'updated_at', wat.updated_at
+                                ))
+                                FROM work_attribute_type wat
+                                WHERE wat.work_type_id = wt.id
+                            ),
+                            '[]'::jsonb
+                        ),
+                        wt.updated_at
+                    FROM work_type wt
+                    WHERE NOT EXISTS (SELECT 1 FROM work_type_version v WHERE v.work_type_id = wt.id);
+
+                    ALTER TABLE work_type ADD COLUMN IF NOT EXISTS parent_id UUID REFERENCES work_type(id);
+                    CREATE INDEX IF NOT EXISTS idx_work_type_parent ON work_type(parent_id);
+
+                    CREATE TABLE IF NOT EXISTS work_type_status (
+                        id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                        work_type_id UUID NOT NULL REFERENCES work_type(id) ON DELETE CASCADE,
+                        name VARCHAR(100) NOT NULL,
+                        category VARCHAR(20) NOT NULL,
+                        is_initial BOOLEAN NOT NULL DEFAULT FALSE,
+                        transitions TEXT[] NOT NULL DEFAULT '{}',
+                        position INTEGER NOT NULL,
+                        UNIQUE (work_type_id, name)
+                    );
+
+                    ALTER TABLE work_item ADD COLUMN IF NOT EXISTS status VARCHAR(100);
+
+                    ALTER TABLE work_type ADD COLUMN IF NOT EXISTS company_id TEXT;
+                    CREATE INDEX IF NOT EXISTS idx_work_type_company ON work_type(company_id);
+
+                    DO $$
+                    BEGIN
+                        IF to_regclass('company') IS NOT NULL AND NOT EXISTS (
+                            SELECT 1 FROM pg_constraint WHERE conname = 'work_type_company_id_fkey'
+                        ) THEN
+                            ALTER TABLE work_type
+                                ADD CONSTRAINT work_type_company_id_fkey
+                                FOREIGN KEY (company_id) REFERENCES company(id) ON DELETE CASCADE;
+                        END IF;
+                    END
+                    $$;
+
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS formula TEXT;
+
+                    CREATE TABLE IF NOT EXISTS link_type (
+                        id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                        name VARCHAR(100) NOT NULL,
+                        outward_name VARCHAR(100) NOT NULL,
+                        inward_name VARCHAR(100) NOT NULL,
+                        is_hierarchical BOOLEAN NOT NULL DEFAULT FALSE,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+                    );
+
+                    CREATE UNIQUE INDEX IF NOT EXISTS idx_link_type_name ON link_type(LOWER(name));
+
+                    CREATE TABLE IF NOT EXISTS work_item_link (
+                        id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                        link_type_id UUID NOT NULL REFERENCES link_type(id) ON DELETE CASCADE,
+                        source_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+                        target_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+                        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        UNIQUE (link_type_id, source_id, target_id),
+                        CHECK (source_id <> target_id)
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_work_item_link_source ON work_item_link(source_id);
+                    CREATE INDEX IF NOT EXISTS idx_work_item_link_target ON work_item_link(target_id);
+
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS position INTEGER;
+
+                    -- Los atributos existentes conservan el orden en que se crearon
+                    UPDATE work_attribute_type wat
+                    SET position = ordered.position
+                    FROM (
+                        SELECT id, ROW_NUMBER() OVER (PARTITION BY work_type_id ORDER BY created_at, id) - 1 AS position
+                        FROM work_attribute_type
+                    ) ordered
+                    WHERE wat.id = ordered.id AND wat.position IS NULL;
+
+                    ALTER TABLE work_attribute_type ALTER COLUMN position SET NOT NULL;
+
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS is_system BOOLEAN NOT NULL DEFAULT FALSE;
+
+                    ALTER TABLE work_attribute_type ADD COLUMN IF NOT EXISTS key VARCHAR(100);
+
+                    -- Los atributos existentes reciben una clave derivada de su nombre. Si dos
+                    -- dan la misma, la segunda y siguientes llevan un sufijo numerico.
+                    UPDATE work_attribute_type wat
+                    SET key = LEFT(derived.slug || CASE WHEN derived.n > 1 THEN '_' || derived.n ELSE '' END, 100)
+                    FROM (
+                        SELECT id, slug, ROW_NUMBER() OVER (PARTITION BY work_type_id, slug ORDER BY position, id) AS n
+                        FROM (
+                            SELECT id, work_type_id, position,
+                                CASE
+                                    WHEN cleaned = '' THEN 'attribute'
+                                    WHEN cleaned ~ '^[0-9]' THEN 'attr_' || cleaned
+                                    ELSE cleaned
+                                END AS slug
+                            FROM (
+                                SELECT id, work_type_id, position,
+                                    TRIM(BOTH '_' FROM REGEXP_REPLACE(
+                                        TRANSLATE(LOWER(name), 'áàäâéèëêíìïîóòöôúùüûñç', 'aaaaeeeeiiiioooouuuunc'),
+                                        '[^a-z0-9]+', '_', 'g'
+                                    )) AS cleaned
+                                FROM work_attribute_type
+                            ) cleaned_names
+                        ) slugs
+                    ) derived
+                    WHERE wat.id = derived.id AND wat.key IS NULL;
+
+                    ALTER TABLE work_attribute_type ALTER COLUMN key SET NOT NULL;
+
+                    CREATE UNIQUE INDEX IF NOT EXISTS idx_work_attribute_type_key ON work_attribute_type(work_type_id, key);
+
+                    ALTER TABLE work_type ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published';
+
+                    CREATE INDEX IF NOT EXISTS idx_work_attribute_item_attribute_type ON work_attribute_item(attribute_type_id);
+
+                    ALTER TABLE work_item ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+                    CREATE OR REPLACE FUNCTION work_item_typed_value(data_type TEXT, value TEXT) RETURNS JSONB
+                    LANGUAGE sql IMMUTABLE AS $$
+                        SELECT CASE
+                            WHEN (data_type = 'numeric' OR data_type LIKE 'decimal%')
+                                AND btrim(value) ~ '^[-+]?([0-9]+[.]?[0-9]*|[.][0-9]+)([eE][-+]?[0-9]+)?$'
+                                THEN to_jsonb(btrim(value)::numeric)
+                            WHEN data_type = 'boolean' AND lower(btrim(value)) IN ('true', 'false')
+                                THEN to_jsonb(lower(btrim(value))::boolean)
+                            WHEN data_type = 'multi_select'
+                                THEN COALESCE((SELECT jsonb_agg(btrim(part)) FROM regexp_split_to_table(value, ',') AS part
+                                               WHERE btrim(part) <> ''), '[]'::jsonb)
+                            ELSE to_jsonb(value)
+                        END
+                    $$;
+
+                    CREATE INDEX IF NOT EXISTS idx_work_item_attributes ON work_item USING GIN (attributes jsonb_path_ops);
+
+                    UPDATE work_item wi
+                    SET attributes = (
+                        SELECT jsonb_object_agg(wat.key, work_item_typed_value(wat.data_type, wai.value))
+                        FROM work_attribute_item wai
+                        JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+                        WHERE wai.work_item_id = wi.id
+                    )
+                    WHERE wi.attributes = '{}'::jsonb
+                      AND EXISTS (SELECT 1 FROM work_attribute_item wai WHERE wai.work_item_id = wi.id);
+
+                    ALTER TABLE work_item ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+                    ALTER TABLE work_item ADD COLUMN IF NOT EXISTS search_language VARCHAR(20);
+                    CREATE INDEX IF NOT EXISTS idx_work_item_search ON work_item USING GIN (search_vector);
+
+                    CREATE TABLE IF NOT EXISTS work_item_history (
+                        id UUID PRIMARY KEY,
+                        work_item_id UUID NOT NULL REFERENCES work_item(id) ON DELETE CASCADE,
+                        action VARCHAR(20) NOT NULL,
+                        changed_by VARCHAR(255),
+                        changed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                        status_change JSONB,
+                        attribute_changes JSONB NOT NULL DEFAULT '[]'::jsonb
+                    );
+                    CREATE INDEX IF NOT EXISTS idx_work_item_history_item ON work_item_history(work_item_id, changed_at DESC);
+                    CREATE OR REPLACE FUNCTION work_item_history_immutable() RETURNS trigger AS $$
+                    BEGIN
+                        RAISE EXCEPTION 'work item history entries cannot be modified';
+                    END
+                    $$ LANGUAGE plpgsql;
+                    DROP RULE IF EXISTS work_item_history_immutable ON work_item_history;
+                    CREATE OR REPLACE TRIGGER work_item_history_immutable
+                        BEFORE UPDATE ON work_item_history
+                        FOR EACH ROW EXECUTE FUNCTION work_item_history_immutable()
             ";
 
 #[derive(Debug)]
@@ -41,13 +296,28 @@ struct FlatWorkTypeRow {
     work_type_id: Uuid,
     title: String,
     description: Option<String>,
+    parent_id: Option<Uuid>,
+    company_id: Option<String>,
+    status: String,
+    version: i32,
     work_type_created_at: DateTime<Utc>,
     work_type_updated_at: DateTime<Utc>,
     attribute_id: Option<Uuid>,
     attribute_name: Option<String>,
+    key: Option<String>,
     data_type: Option<String>,
+    options: Option<Vec<String>>,
     is_required: Option<bool>,
     is_hidden: Option<bool>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    min_length: Option<i32>,
+    max_length: Option<i32>,
+    pattern: Option<String>,
+    default_value: Option<String>,
+    formula: Option<String>,
+    position: Option<i32>,
+    is_system: Option<bool>,
     attribute_created_at: Option<DateTime<Utc>>,
     attribute_updated_at: Option<DateTime<Utc>>,
 }
@@ -55,124 +325,1584 @@ struct FlatWorkTypeRow {
 #[async_trait]
 impl WorkTypeRepositoryTrait for PostgresRepository {
     #[instrument]
-    async fn list(&self) -> Result<Vec<WorkType>> {
-        tracing::info!("Listing all worktypes");
+    async fn list(&self, query: WorkTypeQuery) -> Result<Vec<WorkType>> {
+        tracing::info!("Listing the global worktypes with {:?}", query);
+        let pool = self.pool.lock().await;
+        list_work_types(&pool, None, &query).await
+    }
+
+    #[instrument]
+    async fn get(&self, id: Uuid) -> Result<Option<WorkType>> {
+        tracing::info!("Getting the worktype {}", id);
+        let pool = self.pool.lock().await;
+        fetch_work_type(&*pool, id).await
+    }
+
+    #[instrument]
+    async fn create(&self, request: CreateWorkType) -> Result<WorkType> {
+        tracing::info!("Creating the worktype {:?}", request);
+        validate_work_type_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let work_type = create_work_type(&mut tx, None, request).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(&pool, Some(added_value_keys([], &work_type.attributes)));
+        Ok(work_type)
+    }
+
+    #[instrument]
+    async fn update(&self, id: Uuid, request: CreateWorkType) -> Result<Option<WorkType>> {
+        tracing::info!("Updating the worktype {} with {:?}", id, request);
+        validate_work_type_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let (previous, work_type) = match update_work_type(&mut tx, id, request).await? {
+            Some(updated) => updated,
+            None => return Ok(None),
+        };
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(
+            &pool,
+            Some(added_value_keys(
+                &previous.attributes,
+                &work_type.attributes,
+            )),
+        );
+        Ok(Some(work_type))
+    }
+
+    #[instrument]
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        tracing::info!("Deleting the worktype {}", id);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let deleted = delete_work_type(&mut tx, id).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(deleted)
+    }
+
+    #[instrument]
+    async fn duplicate(&self, id: Uuid) -> Result<Option<WorkType>> {
+        tracing::info!("Duplicating the worktype {}", id);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let original = match fetch_work_type(&mut *tx, id).await? {
+            Some(original) => original,
+            None => return Ok(None),
+        };
+        let dao = original.duplicate();
+        let work_type = insert_work_type(&mut tx, &dao).await?;
+
+        let workflow = fetch_workflow(&mut *tx, id).await?;
+        insert_workflow(&mut tx, work_type.id, &workflow).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(work_type))
+    }
+
+    #[instrument]
+    async fn list_versions(&self, id: Uuid) -> Result<Option<Vec<WorkTypeVersion>>> {
+        tracing::info!("Listing the versions of the worktype {}", id);
         let pool = self.pool.lock().await;
-        let rows: Vec<FlatWorkTypeRow> = sqlx::query_as!(
-            FlatWorkTypeRow,
+
+        let exists = sqlx::query!(r#"SELECT id FROM work_type WHERE id = $1"#, id)
+            .fetch_optional(&*pool)
+            .await
+            .map_err(AppError::Database)?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        let versions: Vec<DbWorkTypeVersion> = sqlx::query_as!(
+            DbWorkTypeVersion,
             r#"
-                SELECT
-                    wt.id AS work_type_id,
-                    wt.title,
-                    wt.description,
-                    wt.created_at AS work_type_created_at,
-                    wt.updated_at AS work_type_updated_at,
-                    wat.id AS attribute_id,
-                    wat.name AS attribute_name,
-                    wat.data_type,
-                    wat.is_required,
-                    wat.is_hidden,
-                    wat.created_at AS attribute_created_at,
-                    wat.updated_at AS attribute_updated_at
-                FROM work_type wt
-                LEFT JOIN work_attribute_type wat ON wt.id = wat.work_type_id
-                ORDER BY wt.id
-    "#
+            SELECT work_type_id, version, attributes AS "attributes: Json<Vec<WorkAttributeType>>", created_at
+            FROM work_type_version
+            WHERE work_type_id = $1
+            ORDER BY version
+            "#,
+            id
         )
         .fetch_all(&*pool)
-        .await?;
-        let mut map: HashMap<Uuid, WorkType> = HashMap::new();
+        .await
+        .map_err(AppError::Database)?;
 
-        for row in rows {
-            let entry: &mut WorkType = map.entry(row.work_type_id).or_insert_with(|| WorkType {
-                id: row.work_type_id,
-                title: row.title.clone(),
-                description: row.description.clone(),
-                created_at: row.work_type_created_at,
-                updated_at: row.work_type_updated_at,
-                attributes: Vec::new(),
-            });
+        Ok(Some(versions.into_iter().map(|v| v.into()).collect()))
+    }
 
-            let data_type: Option<DataType> = row
-                .data_type
-                .as_ref()
-                .and_then(|dt| dt.parse::<DataType>().ok());
-
-            if let (Some(attribute_id), Some(dt)) = (row.attribute_id, data_type) {
-                entry.attributes.push(WorkAttributeType {
-                    id: attribute_id,
-                    name: row.attribute_name.unwrap(),
-                    data_type: dt,
-                    is_required: row.is_required.unwrap(),
-                    is_hidden: row.is_hidden.unwrap(),
-                    created_at: row.attribute_created_at.unwrap(),
-                    updated_at: row.attribute_updated_at.unwrap(),
-                });
-            }
+    #[instrument]
+    async fn get_version(&self, id: Uuid, version: i32) -> Result<Option<WorkTypeVersion>> {
+        tracing::info!("Getting the version {} of the worktype {}", version, id);
+        let pool = self.pool.lock().await;
+
+        fetch_work_type_version(&*pool, id, Some(version)).await
+    }
+
+    #[instrument]
+    async fn get_workflow(&self, id: Uuid) -> Result<Option<Workflow>> {
+        tracing::info!("Getting the workflow of the worktype {}", id);
+        let pool = self.pool.lock().await;
+
+        let exists = sqlx::query!(r#"SELECT id FROM work_type WHERE id = $1"#, id)
+            .fetch_optional(&*pool)
+            .await
+            .map_err(AppError::Database)?;
+        if exists.is_none() {
+            return Ok(None);
         }
 
-        let work_types = map.into_values().collect();
-        Ok(work_types)
+        fetch_workflow(&*pool, id).await.map(Some)
     }
 
     #[instrument]
-    async fn create(&self, request: CreateWorkType) -> Result<WorkType> {
-        tracing::info!("Creating the worktype {:?}", request);
+    async fn set_workflow(&self, id: Uuid, request: CreateWorkflow) -> Result<Option<Workflow>> {
+        tracing::info!(
+            "Setting the workflow of the worktype {} to {:?}",
+            id,
+            request
+        );
+        validate_workflow_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let exists = sqlx::query!(r#"SELECT id FROM work_type WHERE id = $1"#, id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        let workflow = Workflow::from_create_request(request);
+        replace_workflow(&mut tx, id, &workflow).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(workflow))
+    }
+
+    #[instrument]
+    async fn set_status(&self, id: Uuid, status: WorkTypeStatus) -> Result<Option<WorkType>> {
+        tracing::info!("Setting the worktype {} as {}", id, status);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        previous.check_status_change(status)?;
+        if previous.status == status {
+            return Ok(Some(previous));
+        }
+        if let Some(parent_id) = previous.parent_id {
+            let parent = fetch_work_type(&mut *tx, parent_id)
+                .await?
+                .ok_or_else(|| parent_not_found(parent_id))?;
+            validate_parent_status(status, &parent)?;
+        }
+
+        let now = Utc::now();
+        sqlx::query!(
+            r#"UPDATE work_type SET status = $1, updated_at = $2 WHERE id = $3"#,
+            status.to_string(),
+            now,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(WorkType {
+            status,
+            updated_at: now,
+            ..previous
+        }))
+    }
+
+    #[instrument]
+    async fn create_attribute(
+        &self,
+        id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>> {
+        tracing::info!(
+            "Adding the attribute {} to the worktype {}",
+            request.attribute.name,
+            id
+        );
+        validate_attribute_request(&request.attribute)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = WorkAttributeType::from_create_request(&request.attribute);
+        let attributes = previous.place_attribute(attribute.clone(), request.position)?;
+        let work_type = save_attributes(&mut tx, &previous, &attributes).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(
+            &pool,
+            Some(added_value_keys(
+                &previous.attributes,
+                &work_type.attributes,
+            )),
+        );
+        Ok(work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute.id))
+    }
+
+    #[instrument]
+    async fn update_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>> {
+        tracing::info!(
+            "Updating the attribute {} of the worktype {}",
+            attribute_id,
+            id
+        );
+        validate_attribute_request(&request.attribute)?;
         let pool = self.pool.lock().await;
         let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
             pool.begin().await.map_err(AppError::Database)?;
 
-        let dao = WorkType::from_create_request(request);
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = match previous.own_attribute(attribute_id)? {
+            Some(attribute) => attribute.redefine(&request.attribute)?,
+            None => return Ok(None),
+        };
+        let attributes = previous.place_attribute(attribute, request.position)?;
+        let work_type = save_attributes(&mut tx, &previous, &attributes).await?;
 
-        create_work_type_query(&dao)
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(
+            &pool,
+            Some(added_value_keys(
+                &previous.attributes,
+                &work_type.attributes,
+            )),
+        );
+        Ok(work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute_id))
+    }
+
+    #[instrument]
+    async fn delete_attribute(&self, id: Uuid, attribute_id: Uuid) -> Result<bool> {
+        tracing::info!(
+            "Deleting the attribute {} of the worktype {}",
+            attribute_id,
+            id
+        );
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => return Ok(false),
+        };
+        if previous.own_attribute(attribute_id)?.is_none() {
+            return Ok(false);
+        }
+        let attributes = previous.remove_attribute(attribute_id)?;
+        save_attributes(&mut tx, &previous, &attributes).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(true)
+    }
+
+    #[instrument]
+    async fn migrate_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: MigrateAttribute,
+    ) -> Result<Option<MigrationReport>> {
+        tracing::info!(
+            "Migrating the attribute {} of the worktype {}",
+            attribute_id,
+            id
+        );
+        validate_attribute_request(&request.attribute.attribute)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => return Ok(None),
+        };
+        let attribute = match previous.own_attribute(attribute_id)? {
+            Some(attribute) => attribute.redefine(&request.attribute.attribute)?,
+            None => return Ok(None),
+        };
+        let attributes = previous.place_attribute(attribute, request.attribute.position)?;
+        let work_type = save_attributes(&mut tx, &previous, &attributes).await?;
+        let added_keys = added_value_keys(&previous.attributes, &work_type.attributes);
+        let attribute = work_type
+            .attributes
+            .into_iter()
+            .find(|a| a.id == attribute_id)
+            .ok_or_else(|| AppError::Internal("migrated attribute not saved".to_string()))?;
+
+        let values = sqlx::query_as!(
+            StoredValue,
+            r#"
+                SELECT wai.id, wai.work_item_id, wai.value
+                FROM work_attribute_item wai
+                JOIN work_item wi ON wi.id = wai.work_item_id
+                WHERE wai.attribute_type_id = $1
+                ORDER BY wi.created_at, wi.id
+                FOR UPDATE OF wai
+                "#,
+            attribute_id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        // Un error aqui, tambien el de `abort`, descarta la transaccion entera
+        let (report, changes) = migrate_values(&attribute, &values, request.on_failure)?;
+        let before = snapshot_items(&mut tx, &[attribute_id]).await?;
+        let now = Utc::now();
+        for (value_id, value) in changes {
+            match value {
+                Some(value) => sqlx::query!(
+                    r#"UPDATE work_attribute_item SET value = $1, updated_at = $2 WHERE id = $3"#,
+                    value,
+                    now,
+                    value_id
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?,
+                None => sqlx::query!(r#"DELETE FROM work_attribute_item WHERE id = $1"#, value_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(AppError::Database)?,
+            };
+        }
+        let migrated: Vec<Uuid> = values.iter().map(|v| v.work_item_id).collect();
+        refresh_documents(&mut *tx, &migrated, &[]).await?;
+        record_value_changes(&mut tx, &before).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(&pool, Some(added_keys));
+        Ok(Some(report))
+    }
+
+    #[instrument]
+    async fn analyze_impact(
+        &self,
+        id: Uuid,
+        change: ProposedChange,
+    ) -> Result<Option<ImpactReport>> {
+        tracing::info!(
+            "Analyzing the impact of {:?} on the worktype {}",
+            change,
+            id
+        );
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+        // Todas las lecturas ven la misma foto: un cambio que llegue entre
+        // ellas no puede mezclar tipos de trabajo y work items de momentos
+        // distintos
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
             .execute(&mut *tx)
             .await
             .map_err(AppError::Database)?;
 
-        for att in &dao.attributes {
-            let query = create_work_attribute_type_query(dao.id, att);
-            query.execute(&mut *tx).await.map_err(AppError::Database)?;
+        let mut affected = vec![id];
+        affected.extend(fetch_descendants(&mut *tx, id).await?);
+        let mut work_types = fetch_work_type_chain(&mut *tx, &affected).await?;
+        let previous = match work_types.get(&id) {
+            Some(previous) => previous.resolve(&work_types),
+            None => return Ok(None),
+        };
+        let attributes = match previous.proposed_attributes(&change)? {
+            Some(attributes) => attributes,
+            None => return Ok(None),
+        };
+        let items = fetch_work_items(&mut *tx, &affected, None).await?;
+        tx.commit().await.map_err(AppError::Database)?;
+
+        let resolve = |work_types: &HashMap<Uuid, WorkType>| -> HashMap<Uuid, WorkType> {
+            affected
+                .iter()
+                .filter_map(|id| work_types.get(id).map(|wt| (*id, wt.resolve(work_types))))
+                .collect()
+        };
+        let current = resolve(&work_types);
+
+        // El cambio se aplica sobre los tipos de trabajo cargados, sin tocar
+        // la base de datos, y se comprueba igual que al guardarlo
+        let candidate = WorkType {
+            attributes,
+            ..work_types[&id].clone()
+        };
+        work_types.insert(id, candidate);
+        let proposed = resolve(&work_types);
+        for work_type in proposed.values() {
+            validate_attribute_names(work_type)?;
+            validate_formulas(work_type)?;
+        }
+
+        Ok(Some(analyze_impact(&current, &proposed, &items)))
+    }
+
+    #[instrument]
+    async fn export_bundle(&self, ids: Vec<Uuid>) -> Result<WorkTypeBundle> {
+        tracing::info!("Exporting the worktypes {:?}", ids);
+        let pool = self.pool.lock().await;
+
+        let work_types = fetch_work_types(&*pool, None).await?;
+        let mut bundled: Vec<BundledWorkType> = Vec::new();
+        for work_type in export_order(&work_types, &ids)? {
+            let parent = work_type
+                .parent_id
+                .and_then(|parent_id| work_types.iter().find(|wt| wt.id == parent_id));
+            let workflow = fetch_workflow(&*pool, work_type.id).await?;
+            bundled.push(BundledWorkType::new(work_type, parent, &workflow));
+        }
+
+        Ok(WorkTypeBundle {
+            version: BUNDLE_VERSION,
+            work_types: bundled,
+        })
+    }
+
+    #[instrument]
+    async fn import_bundle(
+        &self,
+        bundle: WorkTypeBundle,
+        policy: ConflictPolicy,
+    ) -> Result<Vec<ImportOutcome>> {
+        tracing::info!(
+            "Importing {} worktypes with the {:?} policy",
+            bundle.work_types.len(),
+            policy
+        );
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let existing = fetch_work_types(&mut *tx, None).await?;
+        let plans = plan_import(bundle, &existing, policy)?;
+
+        let mut imported: HashMap<String, Uuid> = HashMap::new();
+        let mut outcomes: Vec<ImportOutcome> = Vec::new();
+        for plan in plans {
+            let work_type = apply_import_step(&mut tx, &plan, &imported).await?;
+            imported.insert(title_key(&plan.title), work_type.id);
+            outcomes.push(ImportOutcome {
+                title: plan.title,
+                action: plan.action,
+                work_type,
+            });
         }
 
         tx.commit().await.map_err(AppError::Database)?;
-        Ok(dao)
+        spawn_value_indexes(
+            &pool,
+            Some(added_value_keys(
+                existing.iter().flat_map(|wt| &wt.attributes),
+                outcomes.iter().flat_map(|o| &o.work_type.attributes),
+            )),
+        );
+        Ok(outcomes)
     }
+
+    #[instrument]
+    async fn sync(&self, document: WorkTypeBundle, apply: bool) -> Result<SyncPlan> {
+        tracing::info!(
+            "Syncing {} worktypes (apply: {})",
+            document.work_types.len(),
+            apply
+        );
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let existing = fetch_work_types(&mut *tx, None).await?;
+        let mut workflows: HashMap<Uuid, Workflow> = HashMap::new();
+        for work_type in &existing {
+            workflows.insert(work_type.id, fetch_workflow(&mut *tx, work_type.id).await?);
+        }
+        let PlannedSync {
+            mut plan,
+            steps,
+            deletions,
+        } = plan_sync(document, &existing, &workflows)?;
+        if !apply {
+            return Ok(plan);
+        }
+
+        let mut imported: HashMap<String, Uuid> = HashMap::new();
+        let mut applied: Vec<WorkType> = Vec::new();
+        for step in steps {
+            let id = match (step.change, step.import.existing_id) {
+                (None, Some(id)) => id,
+                (change, _) => {
+                    let work_type = apply_import_step(&mut tx, &step.import, &imported).await?;
+                    if let Some(change) = change {
+                        plan.changes[change].work_type_id = Some(work_type.id);
+                    }
+                    let id = work_type.id;
+                    applied.push(work_type);
+                    id
+                }
+            };
+            imported.insert(title_key(&step.import.title), id);
+        }
+        for id in deletions {
+            delete_work_type(&mut tx, id).await?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(
+            &pool,
+            Some(added_value_keys(
+                existing.iter().flat_map(|wt| &wt.attributes),
+                applied.iter().flat_map(|wt| &wt.attributes),
+            )),
+        );
+        plan.applied = true;
+        Ok(plan)
+    }
+
+    #[instrument]
+    async fn list_for_company(
+        &self,
+        company_id: &str,
+        query: WorkTypeQuery,
+    ) -> Result<Option<Vec<WorkType>>> {
+        tracing::info!(
+            "Listing the worktypes of the company {} with {:?}",
+            company_id,
+            query
+        );
+        let pool = self.pool.lock().await;
+
+        if !company_exists(&*pool, company_id).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            list_work_types(&pool, Some(company_id), &query).await?,
+        ))
+    }
+
+    #[instrument]
+    async fn create_for_company(
+        &self,
+        company_id: &str,
+        request: CreateWorkType,
+    ) -> Result<Option<WorkType>> {
+        tracing::info!(
+            "Creating the worktype {:?} for the company {}",
+            request,
+            company_id
+        );
+        validate_work_type_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        if !company_exists(&mut *tx, company_id).await? {
+            return Ok(None);
+        }
+
+        let work_type = create_work_type(&mut tx, Some(company_id.to_string()), request).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        spawn_value_indexes(&pool, Some(added_value_keys([], &work_type.attributes)));
+        Ok(Some(work_type))
+    }
+
+    // Las compañias ya estan en la tabla `company`, que consulta
+    // `company_exists`
+    async fn add_company(&self, _company_id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    #[instrument]
+    async fn duplicate_for_company(
+        &self,
+        from_company_id: &str,
+        to_company_id: &str,
+    ) -> Result<u64> {
+        tracing::info!(
+            "Copying the worktypes of the company {} to the company {}",
+            from_company_id,
+            to_company_id
+        );
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let copied = duplicate_company_work_types(&mut tx, from_company_id, to_company_id).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(copied)
+    }
+
+    // El catalogo desaparece con la compañia por la clave foranea de
+    // `work_type.company_id` con ON DELETE CASCADE, que es la que lo borra
+    async fn delete_for_company(&self, _company_id: &str) -> Result<u64> {
+        Ok(0)
+    }
+}
+
+// Los tipos de trabajo que no heredan de otro reciben los atributos de sistema
+// que les falten, por ejemplo tras cambiar su configuracion. Si alguno tiene un
+// atributo con la clave de uno de sistema pero otro tipo de dato, se deja como esta.
+pub async fn ensure_system_attributes(repository: &PostgresRepository) -> Result<()> {
+    let pool = repository.pool.lock().await;
+    let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+        pool.begin().await.map_err(AppError::Database)?;
+
+    let roots = sqlx::query_scalar!(r#"SELECT id FROM work_type WHERE parent_id IS NULL"#)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+    for id in roots {
+        let previous = match fetch_work_type(&mut *tx, id).await? {
+            Some(previous) => previous,
+            None => continue,
+        };
+        let own: Vec<WorkAttributeType> = previous.own_attributes().cloned().collect();
+        match SystemAttributes::get().merge(false, own.clone()) {
+            Ok(attributes) if attributes != own => {
+                save_attributes(&mut tx, &previous, &attributes).await?;
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Worktype {} keeps its attributes: {:?}", id, e),
+        }
+    }
+
+    tx.commit().await.map_err(AppError::Database)?;
+    Ok(())
+}
+
+// Las formulas guardadas antes de las claves citan los atributos por nombre;
+// se reescriben con sus claves para que sigan funcionando
+pub async fn ensure_formula_keys(repository: &PostgresRepository) -> Result<()> {
+    let pool = repository.pool.lock().await;
+    let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+        pool.begin().await.map_err(AppError::Database)?;
+
+    let ids = sqlx::query_scalar!(
+        r#"SELECT DISTINCT work_type_id FROM work_attribute_type WHERE formula IS NOT NULL"#
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(AppError::Database)?;
+    for work_type in fetch_work_types_by_ids(&mut *tx, &ids).await?.into_values() {
+        for attribute in work_type.own_attributes() {
+            let Some(formula) = attribute
+                .formula
+                .as_deref()
+                .and_then(|formula| legacy_references_to_keys(formula, &work_type.attributes))
+            else {
+                continue;
+            };
+            tracing::info!(
+                "Rewriting the formula of attribute {} with keys: {}",
+                attribute.id,
+                formula
+            );
+            sqlx::query!(
+                r#"UPDATE work_attribute_type SET formula = $1 WHERE id = $2"#,
+                formula,
+                attribute.id
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+    }
+
+    tx.commit().await.map_err(AppError::Database)?;
+    Ok(())
+}
+
+// Copia el catalogo propio de una compañia en otra. Los padres se copian antes
+// que sus hijos para que la herencia apunte a las copias.
+async fn duplicate_company_work_types(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    from_company_id: &str,
+    to_company_id: &str,
+) -> Result<u64> {
+    let mut pending = sqlx::query!(
+        r#"SELECT id, parent_id FROM work_type WHERE company_id = $1 ORDER BY created_at, id"#,
+        from_company_id
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+
+    let mut copies: HashMap<Uuid, Uuid> = HashMap::new();
+    while !pending.is_empty() {
+        let position = pending
+            .iter()
+            .position(|row| {
+                row.parent_id
+                    .is_none_or(|parent_id| !pending.iter().any(|other| other.id == parent_id))
+            })
+            .ok_or_else(|| {
+                AppError::Internal(format!(
+                    "Inheritance cycle in the worktypes of the company {}",
+                    from_company_id
+                ))
+            })?;
+        let row = pending.remove(position);
+
+        let original = fetch_work_type(&mut **tx, row.id)
+            .await?
+            .ok_or_else(|| AppError::Internal(format!("Worktype {} lost while copying", row.id)))?;
+        let parent_id = original
+            .parent_id
+            .map(|parent_id| copies.get(&parent_id).copied().unwrap_or(parent_id));
+        let dao = original.copy_for_company(to_company_id, parent_id);
+        let work_type = insert_work_type(tx, &dao).await?;
+
+        let workflow = fetch_workflow(&mut **tx, original.id).await?;
+        insert_workflow(tx, work_type.id, &workflow).await?;
+
+        copies.insert(original.id, work_type.id);
+    }
+
+    Ok(copies.len() as u64)
+}
+
+// Cuerpo de `update`, para reutilizarlo dentro de otras transacciones. La
+// peticion ya debe estar validada. Devuelve el tipo de trabajo antes y despues.
+async fn update_work_type(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    id: Uuid,
+    request: CreateWorkType,
+) -> Result<Option<(WorkType, WorkType)>> {
+    let now = Utc::now();
+
+    let previous = match fetch_work_type(&mut **tx, id).await? {
+        Some(previous) => previous,
+        None => return Ok(None),
+    };
+    previous.check_update_status(&request)?;
+
+    if let Some(parent_id) = request.parent_id {
+        let parent = fetch_work_type(&mut **tx, parent_id)
+            .await?
+            .ok_or_else(|| parent_not_found(parent_id))?;
+        validate_parent_scope(previous.company_id.as_deref(), &parent)?;
+        validate_parent_status(previous.status, &parent)?;
+        if parent_id == id || fetch_descendants(&mut **tx, id).await?.contains(&parent_id) {
+            return Err(AppError::Validation(format!(
+                "worktype {} cannot inherit from itself or from one of its descendants",
+                id
+            )));
+        }
+    }
+
+    let attributes = previous.updated_attributes(&request)?;
+
+    sqlx::query!(
+        r#"
+            UPDATE work_type
+            SET title = $1, description = $2, parent_id = $3, updated_at = $4
+            WHERE id = $5
+            "#,
+        request.title,
+        request.description,
+        request.parent_id,
+        now,
+        id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+
+    let work_type = save_attributes(tx, &previous, &attributes).await?;
+    Ok(Some((previous, work_type)))
+}
+
+// Sustituye los atributos propios de un tipo de trabajo, emparejados por id, y
+// registra una nueva version del esquema si cambian
+async fn save_attributes(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    previous: &WorkType,
+    attributes: &[WorkAttributeType],
+) -> Result<WorkType> {
+    let id = previous.id;
+    let kept: Vec<Uuid> = attributes.iter().map(|a| a.id).collect();
+    let removed: Vec<Uuid> = previous
+        .own_attributes()
+        .filter(|old| !kept.contains(&old.id))
+        .map(|old| old.id)
+        .collect();
+
+    // Los valores de los atributos eliminados se borran con ellos y quedan
+    // en el historial de cada work item
+    let before = snapshot_items(tx, &removed).await?;
+    sqlx::query!(
+        r#"
+            DELETE FROM work_attribute_type
+            WHERE work_type_id = $1 AND NOT (id = ANY($2))
+            "#,
+        id,
+        &kept
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+    record_value_changes(tx, &before).await?;
+
+    for att in attributes {
+        sqlx::query!(
+            r#"
+                INSERT INTO work_attribute_type
+                (id, work_type_id, name, key, data_type, options, is_required, is_hidden,
+                 min_value, max_value, min_length, max_length, pattern, default_value, formula,
+                 position, is_system, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
+                ON CONFLICT (id) DO UPDATE
+                SET name = EXCLUDED.name, key = EXCLUDED.key, data_type = EXCLUDED.data_type,
+                    options = EXCLUDED.options, is_required = EXCLUDED.is_required,
+                    is_hidden = EXCLUDED.is_hidden, min_value = EXCLUDED.min_value,
+                    max_value = EXCLUDED.max_value, min_length = EXCLUDED.min_length,
+                    max_length = EXCLUDED.max_length, pattern = EXCLUDED.pattern,
+                    default_value = EXCLUDED.default_value, formula = EXCLUDED.formula,
+                    position = EXCLUDED.position, is_system = EXCLUDED.is_system,
+                    updated_at = EXCLUDED.updated_at
+                "#,
+            att.id,
+            id,
+            att.name,
+            att.key,
+            att.data_type.to_string(),
+            att.options.as_deref(),
+            att.is_required,
+            att.is_hidden,
+            att.min_value,
+            att.max_value,
+            att.min_length,
+            att.max_length,
+            att.pattern,
+            att.default_value,
+            att.formula,
+            att.position,
+            att.is_system,
+            att.created_at,
+            att.updated_at
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    }
+
+    sqlx::query!(
+        r#"UPDATE work_type SET updated_at = $1 WHERE id = $2"#,
+        Utc::now(),
+        id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+
+    // Los documentos de los work items pierden los valores de los atributos
+    // eliminados y cambian de tipo con el atributo
+    let retyped = previous.own_attributes().any(|old| {
+        !attributes
+            .iter()
+            .any(|a| a.id == old.id && a.data_type == old.data_type)
+    });
+    if retyped {
+        let mut affected = vec![id];
+        affected.extend(fetch_descendants(&mut **tx, id).await?);
+        refresh_documents(&mut **tx, &[], &affected).await?;
+    }
+
+    let work_type = record_version_if_changed(tx, id, &previous.attributes).await?;
+
+    // Los descendientes resuelven sus atributos a traves de este tipo de trabajo
+    if !work_type.same_schema(&previous.attributes) {
+        for descendant_id in fetch_descendants(&mut **tx, id).await? {
+            let latest = fetch_work_type_version(&mut **tx, descendant_id, None)
+                .await?
+                .map(|v| v.attributes)
+                .unwrap_or_default();
+            record_version_if_changed(tx, descendant_id, &latest).await?;
+        }
+    }
+
+    Ok(work_type)
+}
+
+// Ejecuta un paso de una importacion; los padres del paquete ya estan en `imported`
+async fn apply_import_step(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    plan: &PlannedImport,
+    imported: &HashMap<String, Uuid>,
+) -> Result<WorkType> {
+    let request = CreateWorkType {
+        parent_id: plan.parent_id(imported),
+        ..plan.request.clone()
+    };
+    let workflow = Workflow::from_create_request(plan.workflow.clone());
+    match (plan.action, plan.existing_id) {
+        (ImportAction::Skipped, Some(id)) => fetch_work_type(&mut **tx, id).await?,
+        (ImportAction::Overwritten, Some(id)) => {
+            let updated = update_work_type(tx, id, request).await?;
+            replace_workflow(tx, id, &workflow).await?;
+            updated.map(|(_, work_type)| work_type)
+        }
+        _ => {
+            let work_type = create_work_type(tx, None, request).await?;
+            insert_workflow(tx, work_type.id, &workflow).await?;
+            Some(work_type)
+        }
+    }
+    .ok_or_else(|| AppError::Internal(format!("Worktype '{}' lost while importing", plan.title)))
+}
+
+// Cuerpo de `delete`: no se borra un tipo de trabajo del que heredan otros
+async fn delete_work_type(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    id: Uuid,
+) -> Result<bool> {
+    let children = sqlx::query!(r#"SELECT id FROM work_type WHERE parent_id = $1"#, id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    if !children.is_empty() {
+        return Err(AppError::Validation(format!(
+            "worktype {} cannot be deleted while {} worktypes inherit from it",
+            id,
+            children.len()
+        )));
+    }
+
+    let deleted = sqlx::query!(r#"DELETE FROM work_type WHERE id = $1"#, id)
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(deleted.rows_affected() > 0)
+}
+
+// Sustituye el flujo de trabajo de un tipo de trabajo existente
+async fn replace_workflow(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    id: Uuid,
+    workflow: &Workflow,
+) -> Result<()> {
+    let names: Vec<String> = workflow.statuses.iter().map(|s| s.name.clone()).collect();
+
+    // Quitar el flujo de trabajo deja los work items sin estado; en otro caso
+    // no se puede eliminar un estado en el que todavia hay work items
+    if workflow.statuses.is_empty() {
+        sqlx::query!(
+            r#"UPDATE work_item SET status = NULL WHERE work_type_id = $1"#,
+            id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    } else {
+        let orphaned = sqlx::query!(
+            r#"
+                SELECT status AS "status!", COUNT(*) AS "count!"
+                FROM work_item
+                WHERE work_type_id = $1 AND status IS NOT NULL AND NOT (status = ANY($2))
+                GROUP BY status
+                "#,
+            id,
+            &names
+        )
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        if !orphaned.is_empty() {
+            let in_use: Vec<String> = orphaned
+                .iter()
+                .map(|row| format!("'{}' ({} work items)", row.status, row.count))
+                .collect();
+            return Err(AppError::Validation(format!(
+                "statuses still in use cannot be removed: {}",
+                in_use.join(", ")
+            )));
+        }
+
+        sqlx::query!(
+            r#"UPDATE work_item SET status = $1 WHERE work_type_id = $2 AND status IS NULL"#,
+            workflow.initial_status,
+            id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    }
+
+    sqlx::query!(
+        r#"DELETE FROM work_type_status WHERE work_type_id = $1"#,
+        id
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+    insert_workflow(tx, id, workflow).await?;
+
+    Ok(())
+}
+
+// Tipos de trabajo globales y, si se indica, los propios de una compañia
+async fn fetch_work_types<'e, E>(executor: E, company_id: Option<&str>) -> Result<Vec<WorkType>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<FlatWorkTypeRow> = sqlx::query_as!(
+        FlatWorkTypeRow,
+        r#"
+            SELECT
+                wt.id AS work_type_id,
+                wt.title,
+                wt.description,
+                wt.parent_id,
+                wt.company_id,
+                wt.status,
+                wt.version,
+                wt.created_at AS work_type_created_at,
+                wt.updated_at AS work_type_updated_at,
+                wat.id AS "attribute_id?",
+                wat.name AS "attribute_name?",
+                wat.key AS "key?",
+                wat.data_type AS "data_type?",
+                wat.options,
+                wat.is_required AS "is_required?",
+                wat.is_hidden AS "is_hidden?",
+                wat.min_value,
+                wat.max_value,
+                wat.min_length,
+                wat.max_length,
+                wat.pattern,
+                wat.default_value,
+                wat.formula,
+                wat.position AS "position?",
+                wat.is_system AS "is_system?",
+                wat.created_at AS "attribute_created_at?",
+                wat.updated_at AS "attribute_updated_at?"
+            FROM work_type wt
+            LEFT JOIN work_attribute_type wat ON wt.id = wat.work_type_id
+            WHERE wt.company_id IS NULL OR wt.company_id = $1
+            ORDER BY wt.id, wat.position, wat.id
+"#,
+        company_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    let work_types: Vec<WorkType> = into_work_types(rows);
+    let by_id: HashMap<Uuid, WorkType> = work_types.iter().map(|wt| (wt.id, wt.clone())).collect();
+    Ok(work_types.iter().map(|wt| wt.resolve(&by_id)).collect())
+}
+
+async fn company_exists<'e, E>(executor: E, company_id: &str) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let company = sqlx::query!(r#"SELECT id FROM company WHERE id = $1"#, company_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(company.is_some())
+}
+
+async fn create_work_type(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    company_id: Option<String>,
+    request: CreateWorkType,
+) -> Result<WorkType> {
+    let dao = WorkType {
+        company_id,
+        ..WorkType::from_create_request(request)?
+    };
+    if let Some(parent_id) = dao.parent_id {
+        let parent = fetch_work_type(&mut **tx, parent_id)
+            .await?
+            .ok_or_else(|| parent_not_found(parent_id))?;
+        validate_parent_scope(dao.company_id.as_deref(), &parent)?;
+        validate_parent_status(dao.status, &parent)?;
+    }
+
+    insert_work_type(tx, &dao).await
+}
+
+struct DbWorkTypeVersion {
+    work_type_id: Uuid,
+    version: i32,
+    attributes: Json<Vec<WorkAttributeType>>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<DbWorkTypeVersion> for WorkTypeVersion {
+    fn from(db_version: DbWorkTypeVersion) -> Self {
+        Self {
+            work_type_id: db_version.work_type_id,
+            version: db_version.version,
+            attributes: db_version.attributes.0,
+            created_at: db_version.created_at,
+        }
+    }
+}
+
+// Devuelve el tipo de trabajo con sus atributos heredados ya resueltos
+pub(crate) async fn fetch_work_type<'e, E>(executor: E, id: Uuid) -> Result<Option<WorkType>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    Ok(fetch_work_types_by_ids(executor, &[id]).await?.remove(&id))
+}
+
+// Los tipos de trabajo indicados, ya resueltos
+async fn fetch_work_types_by_ids<'e, E>(
+    executor: E,
+    ids: &[Uuid],
+) -> Result<HashMap<Uuid, WorkType>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let work_types = fetch_work_type_chain(executor, ids).await?;
+    Ok(ids
+        .iter()
+        .filter_map(|id| work_types.get(id))
+        .map(|wt| (wt.id, wt.resolve(&work_types)))
+        .collect())
+}
+
+// Los tipos de trabajo indicados y sus ancestros, que hacen falta para
+// resolver la herencia, sin resolver
+async fn fetch_work_type_chain<'e, E>(executor: E, ids: &[Uuid]) -> Result<HashMap<Uuid, WorkType>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<FlatWorkTypeRow> = sqlx::query_as!(
+        FlatWorkTypeRow,
+        r#"
+            WITH RECURSIVE chain AS (
+                SELECT id, parent_id FROM work_type WHERE id = ANY($1)
+                UNION
+                SELECT wt.id, wt.parent_id FROM work_type wt JOIN chain c ON wt.id = c.parent_id
+            )
+            SELECT
+                wt.id AS "work_type_id!",
+                wt.title AS "title!",
+                wt.description,
+                wt.parent_id,
+                wt.company_id,
+                wt.status AS "status!",
+                wt.version AS "version!",
+                wt.created_at AS "work_type_created_at!",
+                wt.updated_at AS "work_type_updated_at!",
+                wat.id AS "attribute_id?",
+                wat.name AS "attribute_name?",
+                wat.key AS "key?",
+                wat.data_type AS "data_type?",
+                wat.options,
+                wat.is_required AS "is_required?",
+                wat.is_hidden AS "is_hidden?",
+                wat.min_value,
+                wat.max_value,
+                wat.min_length,
+                wat.max_length,
+                wat.pattern,
+                wat.default_value,
+                wat.formula,
+                wat.position AS "position?",
+                wat.is_system AS "is_system?",
+                wat.created_at AS "attribute_created_at?",
+                wat.updated_at AS "attribute_updated_at?"
+            FROM work_type wt
+            JOIN chain ON chain.id = wt.id
+            LEFT JOIN work_attribute_type wat ON wt.id = wat.work_type_id
+            ORDER BY wt.id, wat.position, wat.id
+"#,
+        ids
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(into_work_types(rows)
+        .into_iter()
+        .map(|wt| (wt.id, wt))
+        .collect())
+}
+
+// Listado de tipos de trabajo globales y, si se indica, los propios de una
+// compañia. El filtro y el orden se aplican en la consulta, igual que
+// `WorkTypeQuery::apply` en memoria, y despues se cargan solo los que salen.
+async fn list_work_types(
+    pool: &sqlx::PgPool,
+    company_id: Option<&str>,
+    query: &WorkTypeQuery,
+) -> Result<Vec<WorkType>> {
+    let column = match query.sort.unwrap_or_default() {
+        WorkTypeSort::Title => "LOWER(wt.title) COLLATE \"C\"",
+        WorkTypeSort::CreatedAt => "wt.created_at",
+        WorkTypeSort::UpdatedAt => "wt.updated_at",
+    };
+    let direction = match query.order.unwrap_or_default() {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+    let statement = format!(
+        "SELECT wt.id FROM work_type wt \
+         WHERE (wt.company_id IS NULL OR wt.company_id = $1) \
+           AND ($2::text IS NULL OR wt.title ILIKE '%' || $2 || '%') \
+           AND CASE WHEN $3::text IS NULL THEN wt.status <> 'draft' ELSE wt.status = $3 END \
+         ORDER BY {column} {direction}, wt.id {direction}"
+    );
+    let ids: Vec<Uuid> = sqlx::query_scalar(&statement)
+        .bind(company_id)
+        .bind(query.title.as_deref().map(escape_like))
+        .bind(query.status.map(|status| status.to_string()))
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+    let mut work_types = fetch_work_types_by_ids(pool, &ids).await?;
+    Ok(ids.iter().filter_map(|id| work_types.remove(id)).collect())
+}
+
+async fn fetch_descendants<'e, E>(executor: E, id: Uuid) -> Result<Vec<Uuid>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let descendants = sqlx::query_scalar!(
+        r#"
+            WITH RECURSIVE descendants AS (
+                SELECT id FROM work_type WHERE parent_id = $1
+                UNION
+                SELECT wt.id FROM work_type wt JOIN descendants d ON wt.parent_id = d.id
+            )
+            SELECT id AS "id!" FROM descendants
+"#,
+        id
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    Ok(descendants)
+}
+
+// `version` a `None` devuelve la ultima version registrada
+async fn fetch_work_type_version<'e, E>(
+    executor: E,
+    id: Uuid,
+    version: Option<i32>,
+) -> Result<Option<WorkTypeVersion>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let version: Option<DbWorkTypeVersion> = sqlx::query_as!(
+        DbWorkTypeVersion,
+        r#"
+            SELECT work_type_id, version, attributes AS "attributes: Json<Vec<WorkAttributeType>>", created_at
+            FROM work_type_version
+            WHERE work_type_id = $1 AND ($2::INTEGER IS NULL OR version = $2)
+            ORDER BY version DESC
+            LIMIT 1
+"#,
+        id,
+        version
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    Ok(version.map(|v| v.into()))
+}
+
+// Inserta un tipo de trabajo nuevo con sus atributos propios y registra su
+// primera version con los atributos ya resueltos
+async fn insert_work_type(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    dao: &WorkType,
+) -> Result<WorkType> {
+    create_work_type_query(dao)
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+
+    for att in dao.own_attributes() {
+        let query = create_work_attribute_type_query(dao.id, att);
+        query.execute(&mut **tx).await.map_err(AppError::Database)?;
+    }
+
+    let work_type = fetch_work_type(&mut **tx, dao.id)
+        .await?
+        .ok_or_else(|| AppError::Internal(format!("Worktype {} lost while creating", dao.id)))?;
+    validate_attribute_names(&work_type)?;
+    validate_formulas(&work_type)?;
+
+    create_work_type_version_query(&work_type.snapshot())
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(work_type)
+}
+
+// Cualquier cambio en los atributos resueltos genera una nueva version del esquema
+async fn record_version_if_changed(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    id: Uuid,
+    previous_attributes: &[WorkAttributeType],
+) -> Result<WorkType> {
+    let mut work_type = fetch_work_type(&mut **tx, id)
+        .await?
+        .ok_or_else(|| AppError::Internal(format!("Worktype {} lost while updating", id)))?;
+
+    if !work_type.same_schema(previous_attributes) {
+        validate_attribute_names(&work_type)?;
+        validate_formulas(&work_type)?;
+        work_type.version += 1;
+        sqlx::query!(
+            r#"UPDATE work_type SET version = $1 WHERE id = $2"#,
+            work_type.version,
+            id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        create_work_type_version_query(&work_type.snapshot())
+            .execute(&mut **tx)
+            .await
+            .map_err(AppError::Database)?;
+    }
+
+    Ok(work_type)
+}
+
+pub(crate) async fn fetch_workflow<'e, E>(executor: E, work_type_id: Uuid) -> Result<Workflow>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows = sqlx::query!(
+        r#"
+            SELECT name, category, is_initial, transitions
+            FROM work_type_status
+            WHERE work_type_id = $1
+            ORDER BY position
+"#,
+        work_type_id
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    let mut workflow = Workflow {
+        initial_status: None,
+        statuses: Vec::new(),
+    };
+    for row in rows {
+        if row.is_initial {
+            workflow.initial_status = Some(row.name.clone());
+        }
+        workflow.statuses.push(WorkflowStatus {
+            name: row.name,
+            category: row.category.parse::<StatusCategory>()?,
+            transitions: row.transitions,
+        });
+    }
+
+    Ok(workflow)
+}
+
+async fn insert_workflow(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    work_type_id: Uuid,
+    workflow: &Workflow,
+) -> Result<()> {
+    for (position, status) in workflow.statuses.iter().enumerate() {
+        sqlx::query!(
+            r#"
+            INSERT INTO work_type_status
+            (id, work_type_id, name, category, is_initial, transitions, position)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            Uuid::new_v4(),
+            work_type_id,
+            status.name,
+            status.category.to_string(),
+            workflow.initial_status.as_ref() == Some(&status.name),
+            &status.transitions,
+            position as i32
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::Database)?;
+    }
+
+    Ok(())
+}
+
+fn parent_not_found(parent_id: Uuid) -> AppError {
+    AppError::Validation(format!("parent worktype {} does not exist", parent_id))
+}
+
+// Las filas llegan ordenadas por tipo de trabajo, asi que se agrupan conservando el orden
+fn into_work_types(rows: Vec<FlatWorkTypeRow>) -> Vec<WorkType> {
+    let mut work_types: Vec<WorkType> = Vec::new();
+
+    for row in rows {
+        if work_types.last().map(|wt| wt.id) != Some(row.work_type_id) {
+            work_types.push(WorkType {
+                id: row.work_type_id,
+                title: row.title.clone(),
+                description: row.description.clone(),
+                parent_id: row.parent_id,
+                company_id: row.company_id.clone(),
+                status: row.status.parse().unwrap_or_default(),
+                version: row.version,
+                created_at: row.work_type_created_at,
+                updated_at: row.work_type_updated_at,
+                attributes: Vec::new(),
+            });
+        }
+        let entry: &mut WorkType = work_types.last_mut().unwrap();
+
+        let data_type: Option<DataType> = row
+            .data_type
+            .as_ref()
+            .and_then(|dt| dt.parse::<DataType>().ok());
+
+        if let (Some(attribute_id), Some(dt)) = (row.attribute_id, data_type) {
+            entry.attributes.push(WorkAttributeType {
+                id: attribute_id,
+                name: row.attribute_name.unwrap(),
+                key: row.key.unwrap(),
+                data_type: dt,
+                options: row.options,
+                is_required: row.is_required.unwrap(),
+                is_hidden: row.is_hidden.unwrap(),
+                min_value: row.min_value,
+                max_value: row.max_value,
+                min_length: row.min_length,
+                max_length: row.max_length,
+                pattern: row.pattern,
+                default_value: row.default_value,
+                formula: row.formula,
+                position: row.position.unwrap(),
+                is_system: row.is_system.unwrap(),
+                inherited_from: None,
+                created_at: row.attribute_created_at.unwrap(),
+                updated_at: row.attribute_updated_at.unwrap(),
+            });
+        }
+    }
+
+    work_types
 }
 
 pub fn create_work_attribute_type_query(
     work_type_id: Uuid,
     att: &WorkAttributeType,
-) -> Query<sqlx::Postgres, sqlx::postgres::PgArguments> {
+) -> Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
     sqlx::query(
         r#"
 INSERT INTO work_attribute_type
-(id, work_type_id, name, data_type, is_required, is_hidden, created_at, updated_at)
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+(id, work_type_id, name, key, data_type, options, is_required, is_hidden,
+ min_value, max_value, min_length, max_length, pattern, default_value, formula,
+ position, is_system, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
 "#,
     )
     .bind(att.id)
     .bind(work_type_id)
     .bind(&att.name)
+    .bind(&att.key)
     .bind(att.data_type.to_string())
+    .bind(&att.options)
     .bind(att.is_required)
     .bind(att.is_hidden)
+    .bind(att.min_value)
+    .bind(att.max_value)
+    .bind(att.min_length)
+    .bind(att.max_length)
+    .bind(&att.pattern)
+    .bind(&att.default_value)
+    .bind(&att.formula)
+    .bind(att.position)
+    .bind(att.is_system)
     .bind(att.created_at)
     .bind(att.updated_at)
 }
 
 pub fn create_work_type_query(
     work_type: &WorkType,
-) -> Query<sqlx::Postgres, sqlx::postgres::PgArguments> {
+) -> Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
     sqlx::query(
         r#"
 INSERT INTO work_type
-(id, title, description, created_at, updated_at)
-VALUES ($1, $2, $3, $4, $5)
+(id, title, description, parent_id, company_id, status, version, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
 "#,
     )
     .bind(work_type.id)
     .bind(&work_type.title)
     .bind(&work_type.description)
+    .bind(work_type.parent_id)
+    .bind(&work_type.company_id)
+    .bind(work_type.status.to_string())
+    .bind(work_type.version)
     .bind(work_type.created_at)
     .bind(work_type.updated_at)
 }
+
+pub fn create_work_type_version_query(
+    version: &WorkTypeVersion,
+) -> Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    sqlx::query(
+        r#"
+INSERT INTO work_type_version
+(work_type_id, version, attributes, created_at)
+VALUES ($1, $2, $3, $4)
+"#,
+    )
+    .bind(version.work_type_id)
+    .bind(version.version)
+    .bind(Json(&version.attributes))
+    .bind(version.created_at)
+}
@@ -0,0 +1,344 @@
+use chrono::{DateTime, FixedOffset};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::filter::{Comparator, Condition, Field, Filter, Operand};
+use crate::models::DataType;
+use common::error::AppError;
+use common::error::Result;
+
+// Parametros de la consulta compilada, en el orden de sus `$n`
+#[derive(Debug, PartialEq)]
+enum FilterParam {
+    Json(Value),
+    Text(String),
+    Timestamp(DateTime<FixedOffset>),
+}
+
+// Traduce el filtro a una condicion SQL sobre el documento JSONB de los work
+// items. Los literales van siempre como parametros; las claves, que solo
+// tienen minusculas, digitos y guiones bajos, van en la sentencia para que
+// PostgreSQL use sus indices de expresion.
+struct Compiler {
+    first: usize,
+    params: Vec<FilterParam>,
+}
+
+impl Compiler {
+    fn param(&mut self, param: FilterParam) -> String {
+        self.params.push(param);
+        format!("${}", self.first + self.params.len() - 1)
+    }
+
+    fn compile(&mut self, filter: &Filter) -> String {
+        match filter {
+            Filter::And(left, right) => {
+                format!("({} AND {})", self.compile(left), self.compile(right))
+            }
+            Filter::Or(left, right) => {
+                format!("({} OR {})", self.compile(left), self.compile(right))
+            }
+            Filter::Not(inner) => format!("NOT {}", self.compile(inner)),
+            // Las condiciones nunca son NULL, asi NOT se comporta como en memoria
+            Filter::Condition(condition) => {
+                format!("COALESCE({}, false)", self.condition(condition))
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Condition) -> String {
+        let Condition {
+            field,
+            comparator,
+            operand,
+        } = condition;
+
+        let (key, data_type) = match field {
+            Field::Status => {
+                return match (comparator, operand) {
+                    (Comparator::Equal, Operand::Null) => "wi.status IS NULL".to_string(),
+                    (_, Operand::Null) => "wi.status IS NOT NULL".to_string(),
+                    (Comparator::Equal, Operand::Text(text)) => {
+                        format!(
+                            "wi.status = {}",
+                            self.param(FilterParam::Text(text.clone()))
+                        )
+                    }
+                    (_, Operand::Text(text)) => format!(
+                        "wi.status IS DISTINCT FROM {}",
+                        self.param(FilterParam::Text(text.clone()))
+                    ),
+                    _ => "false".to_string(),
+                };
+            }
+            Field::Attribute { key, data_type } => (key.replace('\'', "''"), *data_type),
+        };
+        let value = format!("wi.attributes -> '{}'", key);
+
+        match (comparator, operand) {
+            (Comparator::Equal, Operand::Null) => format!("NOT (wi.attributes ? '{}')", key),
+            (Comparator::NotEqual, Operand::Null) => format!("(wi.attributes ? '{}')", key),
+            // `!=` tambien se cumple si falta el valor o no encaja con su tipo
+            (Comparator::NotEqual, _) => {
+                let equal = Condition {
+                    comparator: Comparator::Equal,
+                    ..condition.clone()
+                };
+                format!("NOT COALESCE({}, false)", self.condition(&equal))
+            }
+            (Comparator::Contains, Operand::Text(text)) => match data_type {
+                DataType::MultiSelectType => format!(
+                    "wi.attributes @> {}",
+                    self.param(FilterParam::Json(json!({ key: [text] })))
+                ),
+                _ => format!(
+                    "(wi.attributes ->> '{}') ILIKE {}",
+                    key,
+                    self.param(FilterParam::Text(format!("%{}%", escape_like(text))))
+                ),
+            },
+            (comparator, Operand::Text(text)) if data_type == DataType::DateTimeType => {
+                let Ok(instant) = DateTime::parse_from_rfc3339(text.trim()) else {
+                    return "false".to_string();
+                };
+                format!(
+                    "(CASE WHEN jsonb_typeof({value}) = 'string' \
+                     AND ({value} #>> '{{}}') ~ '^[0-9]{{4}}-[0-9]{{2}}-[0-9]{{2}}T' \
+                     THEN ({value} #>> '{{}}')::timestamptz END) {} {}",
+                    sql_symbol(*comparator),
+                    self.param(FilterParam::Timestamp(instant)),
+                    value = value
+                )
+            }
+            (Comparator::Equal, operand) => match json_operand(operand) {
+                Some(literal) => format!(
+                    "wi.attributes @> {}",
+                    self.param(FilterParam::Json(json!({ key: literal })))
+                ),
+                None => "false".to_string(),
+            },
+            // Solo se ordenan numeros con numeros y fechas con fechas
+            (comparator, operand) => {
+                let json_type = match operand {
+                    Operand::Number(_) => "number",
+                    _ => "string",
+                };
+                match json_operand(operand) {
+                    Some(literal) => format!(
+                        "(jsonb_typeof({value}) = '{}' AND {value} {} {})",
+                        json_type,
+                        sql_symbol(*comparator),
+                        self.param(FilterParam::Json(literal)),
+                        value = value
+                    ),
+                    None => "false".to_string(),
+                }
+            }
+        }
+    }
+}
+
+fn json_operand(operand: &Operand) -> Option<Value> {
+    match operand {
+        Operand::Number(n) => serde_json::Number::from_f64(*n).map(Value::Number),
+        Operand::Text(text) => Some(Value::String(text.clone())),
+        Operand::Boolean(b) => Some(Value::Bool(*b)),
+        Operand::Null => None,
+    }
+}
+
+fn sql_symbol(comparator: Comparator) -> &'static str {
+    match comparator {
+        Comparator::Equal => "=",
+        Comparator::NotEqual => "<>",
+        Comparator::Greater => ">",
+        Comparator::GreaterOrEqual => ">=",
+        Comparator::Less => "<",
+        Comparator::LessOrEqual => "<=",
+        Comparator::Contains => "ILIKE",
+    }
+}
+
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Ids de los work items del tipo de trabajo que cumplen el filtro
+pub(crate) async fn filter_work_item_ids<'e, E>(
+    executor: E,
+    work_type_id: Uuid,
+    filter: &Filter,
+) -> Result<Vec<Uuid>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let mut compiler = Compiler {
+        first: 2,
+        params: Vec::new(),
+    };
+    let condition = compiler.compile(filter);
+    let statement = format!(
+        "SELECT wi.id FROM work_item wi WHERE wi.work_type_id = $1 AND {} \
+         ORDER BY wi.created_at, wi.id",
+        condition
+    );
+
+    let mut query = sqlx::query_scalar::<_, Uuid>(&statement).bind(work_type_id);
+    for param in compiler.params {
+        query = match param {
+            FilterParam::Json(value) => query.bind(value),
+            FilterParam::Text(text) => query.bind(text),
+            FilterParam::Timestamp(instant) => query.bind(instant),
+        };
+    }
+
+    query.fetch_all(executor).await.map_err(AppError::Database)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(filter: &Filter) -> (String, Vec<FilterParam>) {
+        let mut compiler = Compiler {
+            first: 2,
+            params: Vec::new(),
+        };
+        let sql = compiler.compile(filter);
+        (sql, compiler.params)
+    }
+
+    fn condition(
+        key: &str,
+        data_type: DataType,
+        comparator: Comparator,
+        operand: Operand,
+    ) -> Filter {
+        Filter::Condition(Condition {
+            field: Field::Attribute {
+                key: key.to_string(),
+                data_type,
+            },
+            comparator,
+            operand,
+        })
+    }
+
+    #[test]
+    fn test_compile_not_equal_matches_missing_values() {
+        let filter = condition(
+            "severity",
+            DataType::NumericType,
+            Comparator::NotEqual,
+            Operand::Number(3.0),
+        );
+
+        assert_eq!(
+            compile(&filter),
+            (
+                "COALESCE(NOT COALESCE(wi.attributes @> $2, false), false)".to_string(),
+                vec![FilterParam::Json(json!({ "severity": 3.0 }))]
+            )
+        );
+    }
+
+    #[test]
+    fn test_compile_wraps_negated_conditions_in_coalesce() {
+        let filter = Filter::Not(Box::new(Filter::And(
+            Box::new(condition(
+                "summary",
+                DataType::StringType,
+                Comparator::Equal,
+                Operand::Null,
+            )),
+            Box::new(Filter::Condition(Condition {
+                field: Field::Status,
+                comparator: Comparator::Equal,
+                operand: Operand::Text("open".to_string()),
+            })),
+        )));
+
+        assert_eq!(
+            compile(&filter),
+            (
+                "NOT (COALESCE(NOT (wi.attributes ? 'summary'), false) \
+                 AND COALESCE(wi.status = $2, false))"
+                    .to_string(),
+                vec![FilterParam::Text("open".to_string())]
+            )
+        );
+    }
+
+    #[test]
+    fn test_compile_contains_escapes_like_wildcards() {
+        let filter = condition(
+            "summary",
+            DataType::StringType,
+            Comparator::Contains,
+            Operand::Text("50%_off\\".to_string()),
+        );
+
+        assert_eq!(
+            compile(&filter),
+            (
+                "COALESCE((wi.attributes ->> 'summary') ILIKE $2, false)".to_string(),
+                vec![FilterParam::Text("%50\\%\\_off\\\\%".to_string())]
+            )
+        );
+    }
+
+    #[test]
+    fn test_compile_datetime_comparisons_cast_to_timestamptz() {
+        let filter = condition(
+            "reported_at",
+            DataType::DateTimeType,
+            Comparator::GreaterOrEqual,
+            Operand::Text("2024-01-01T10:00:00+02:00".to_string()),
+        );
+        let instant = DateTime::parse_from_rfc3339("2024-01-01T10:00:00+02:00").unwrap();
+
+        assert_eq!(
+            compile(&filter),
+            (
+                "COALESCE((CASE WHEN jsonb_typeof(wi.attributes -> 'reported_at') = 'string' \
+                 AND (wi.attributes -> 'reported_at' #>> '{}') ~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}T' \
+                 THEN (wi.attributes -> 'reported_at' #>> '{}')::timestamptz END) >= $2, false)"
+                    .to_string(),
+                vec![FilterParam::Timestamp(instant)]
+            )
+        );
+
+        let unparsable = condition(
+            "reported_at",
+            DataType::DateTimeType,
+            Comparator::Less,
+            Operand::Text("yesterday".to_string()),
+        );
+        assert_eq!(
+            compile(&unparsable),
+            ("COALESCE(false, false)".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_compile_escapes_quotes_in_keys() {
+        let filter = condition(
+            "it's",
+            DataType::NumericType,
+            Comparator::Greater,
+            Operand::Number(1.0),
+        );
+
+        assert_eq!(
+            compile(&filter),
+            (
+                "COALESCE((jsonb_typeof(wi.attributes -> 'it''s') = 'number' \
+                 AND wi.attributes -> 'it''s' > $2), false)"
+                    .to_string(),
+                vec![FilterParam::Json(json!(1.0))]
+            )
+        );
+    }
+}
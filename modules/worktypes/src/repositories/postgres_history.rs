@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::history::{AttributeChange, StatusChange, WorkItemChange};
+use crate::models::WorkItem;
+use common::error::AppError;
+use common::error::Result;
+
+use super::postgres_items::fetch_work_items;
+
+#[derive(Debug)]
+struct WorkItemChangeRow {
+    id: Uuid,
+    work_item_id: Uuid,
+    action: String,
+    changed_by: Option<String>,
+    changed_at: DateTime<Utc>,
+    status_change: Option<Json<StatusChange>>,
+    attribute_changes: Json<Vec<AttributeChange>>,
+}
+
+pub(crate) async fn insert_change<'e, E>(executor: E, change: &WorkItemChange) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        r#"
+INSERT INTO work_item_history
+(id, work_item_id, action, changed_by, changed_at, status_change, attribute_changes)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+"#,
+    )
+    .bind(change.id)
+    .bind(change.work_item_id)
+    .bind(change.action.to_string())
+    .bind(&change.changed_by)
+    .bind(change.changed_at)
+    .bind(change.status.as_ref().map(Json))
+    .bind(Json(&change.attributes))
+    .execute(executor)
+    .await
+    .map_err(AppError::Database)?;
+    Ok(())
+}
+
+// Una pagina del historial del work item, de la entrada mas reciente a la mas
+// antigua, y el total de entradas
+pub(crate) async fn fetch_history(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    work_item_id: Uuid,
+    page: u32,
+    per_page: u32,
+) -> Result<(Vec<WorkItemChange>, u64)> {
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM work_item_history WHERE work_item_id = $1"#,
+        work_item_id
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+
+    let rows: Vec<WorkItemChangeRow> = sqlx::query_as!(
+        WorkItemChangeRow,
+        r#"
+            SELECT
+                id,
+                work_item_id,
+                action,
+                changed_by,
+                changed_at,
+                status_change AS "status_change: Json<StatusChange>",
+                attribute_changes AS "attribute_changes: Json<Vec<AttributeChange>>"
+            FROM work_item_history
+            WHERE work_item_id = $1
+            ORDER BY changed_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        work_item_id,
+        per_page as i64,
+        (page as i64 - 1) * per_page as i64
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+
+    let entries = rows
+        .into_iter()
+        .map(|row| {
+            Ok(WorkItemChange {
+                id: row.id,
+                work_item_id: row.work_item_id,
+                action: row.action.parse()?,
+                changed_by: row.changed_by,
+                changed_at: row.changed_at,
+                status: row.status_change.map(|status| status.0),
+                attributes: row.attribute_changes.0,
+            })
+        })
+        .collect::<Result<Vec<WorkItemChange>>>()?;
+    Ok((entries, total as u64))
+}
+
+// Los work items con valores de los atributos indicados tal y como estan antes
+// de que un cambio en su tipo de trabajo los modifique
+pub(crate) async fn snapshot_items(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    attribute_ids: &[Uuid],
+) -> Result<Vec<WorkItem>> {
+    if attribute_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = sqlx::query!(
+        r#"
+            SELECT DISTINCT wi.id, wi.work_type_id
+            FROM work_attribute_item wai
+            JOIN work_item wi ON wi.id = wai.work_item_id
+            WHERE wai.attribute_type_id = ANY($1)
+            "#,
+        attribute_ids
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(AppError::Database)?;
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let item_ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    let work_type_ids: Vec<Uuid> = rows.iter().map(|row| row.work_type_id).collect();
+    fetch_work_items(&mut **tx, &work_type_ids, Some(&item_ids)).await
+}
+
+// Registra los valores que cambiaron en los work items de `before`
+pub(crate) async fn record_value_changes(
+    tx: &mut sqlx::Transaction<'static, sqlx::Postgres>,
+    before: &[WorkItem],
+) -> Result<()> {
+    if before.is_empty() {
+        return Ok(());
+    }
+    let item_ids: Vec<Uuid> = before.iter().map(|item| item.id).collect();
+    let work_type_ids: Vec<Uuid> = before.iter().map(|item| item.work_type_id).collect();
+    let after = fetch_work_items(&mut **tx, &work_type_ids, Some(&item_ids)).await?;
+
+    for item in &after {
+        let change = before
+            .iter()
+            .find(|previous| previous.id == item.id)
+            .and_then(|previous| WorkItemChange::by_schema(previous, item));
+        if let Some(change) = change {
+            insert_change(&mut **tx, &change).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::query::Query;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::filter::parse as parse_filter;
+use crate::history::{ChangeAction, HistoryPage, WorkItemChange};
+use crate::models::{WorkAttributeItem, WorkItem};
+use crate::requests::{CreateWorkItem, HistoryQuery, TransitionWorkItem, WorkItemListQuery};
+use crate::search::{search_terms, SearchHit};
+
+use super::postgres::{fetch_work_type, fetch_workflow};
+use super::postgres_filter::filter_work_item_ids;
+use super::postgres_history::{fetch_history, insert_change};
+use super::postgres_search::{fetch_highlights, search_work_item_ids};
+use super::postgres_values::refresh_documents;
+use super::repository::WorkItemRepositoryTrait;
+use common::error::AppError;
+use common::error::Result;
+use common::repositories::postgres::PostgresRepository;
+
+#[derive(Debug)]
+struct FlatWorkItemRow {
+    work_item_id: Uuid,
+    work_type_id: Uuid,
+    schema_version: i32,
+    status: Option<String>,
+    work_item_created_at: DateTime<Utc>,
+    work_item_updated_at: DateTime<Utc>,
+    attribute_item_id: Option<Uuid>,
+    attribute_type_id: Option<Uuid>,
+    attribute_key: Option<String>,
+    attribute_name: Option<String>,
+    value: Option<String>,
+    attribute_created_at: Option<DateTime<Utc>>,
+    attribute_updated_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl WorkItemRepositoryTrait for PostgresRepository {
+    #[instrument]
+    async fn list(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<WorkItem>>> {
+        tracing::info!("Listing the work items of the worktype {}", work_type_id);
+        let pool = self.pool.lock().await;
+
+        let work_type = match fetch_work_type(&*pool, work_type_id).await? {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        let filter = match query.filter.as_deref() {
+            Some(filter) => parse_filter(filter, &work_type)?,
+            None => {
+                return fetch_work_items(&*pool, &[work_type_id], None)
+                    .await
+                    .map(Some)
+            }
+        };
+
+        let ids = filter_work_item_ids(&*pool, work_type_id, &filter).await?;
+        fetch_work_items(&*pool, &[work_type_id], Some(&ids))
+            .await
+            .map(Some)
+    }
+
+    #[instrument]
+    async fn search(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<SearchHit>>> {
+        tracing::info!("Searching the work items of the worktype {}", work_type_id);
+        let q = query.q.as_deref().unwrap_or_default();
+        search_terms(q)?;
+        let pool = self.pool.lock().await;
+
+        let work_type = match fetch_work_type(&*pool, work_type_id).await? {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        let filtered = match query.filter.as_deref() {
+            Some(filter) => {
+                let filter = parse_filter(filter, &work_type)?;
+                Some(filter_work_item_ids(&*pool, work_type_id, &filter).await?)
+            }
+            None => None,
+        };
+
+        let ranked = search_work_item_ids(&*pool, work_type_id, q, filtered.as_deref()).await?;
+        let ids: Vec<Uuid> = ranked.iter().map(|(id, _)| *id).collect();
+        let mut items: HashMap<Uuid, WorkItem> =
+            fetch_work_items(&*pool, &[work_type_id], Some(&ids))
+                .await?
+                .into_iter()
+                .map(|item| (item.id, item))
+                .collect();
+        let mut highlights = fetch_highlights(&*pool, q, &ids).await?;
+
+        let hits = ranked
+            .into_iter()
+            .filter_map(|(id, rank)| {
+                items.remove(&id).map(|item| SearchHit {
+                    item,
+                    rank,
+                    highlights: highlights.remove(&id).unwrap_or_default(),
+                })
+            })
+            .collect();
+        Ok(Some(hits))
+    }
+
+    #[instrument]
+    async fn get(&self, id: Uuid) -> Result<Option<WorkItem>> {
+        tracing::info!("Getting the work item {}", id);
+        let pool = self.pool.lock().await;
+        fetch_work_item(&*pool, id).await
+    }
+
+    #[instrument]
+    async fn create(
+        &self,
+        work_type_id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        tracing::info!("Creating a work item of the worktype {}", work_type_id);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let work_type = match fetch_work_type(&mut *tx, work_type_id).await? {
+            Some(work_type) => work_type,
+            None => return Ok(None),
+        };
+        work_type.check_accepts_items()?;
+        let mut request = request;
+        work_type.prepare_values(&mut request.values)?;
+
+        let workflow = fetch_workflow(&mut *tx, work_type_id).await?;
+        let dao = WorkItem::from_create_request(&work_type, &workflow, request);
+
+        create_work_item_query(&dao)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        for att in &dao.work_attributes {
+            let query = create_work_attribute_item_query(dao.id, att);
+            query.execute(&mut *tx).await.map_err(AppError::Database)?;
+        }
+        refresh_documents(&mut *tx, &[dao.id], &[]).await?;
+        let change = WorkItemChange::between(ChangeAction::Created, None, &dao, changed_by);
+        insert_change(&mut *tx, &change).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(dao))
+    }
+
+    #[instrument]
+    async fn update(
+        &self,
+        id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        tracing::info!("Updating the work item {}", id);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_item(&mut *tx, id).await? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let work_type = fetch_work_type(&mut *tx, previous.work_type_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Internal(format!("Work item {} without worktype", previous.id))
+            })?;
+        // Sin `check_accepts_items`: un tipo obsoleto no admite work items
+        // nuevos pero los que ya tiene se pueden seguir editando, y un
+        // borrador nunca tiene work items
+        let mut values = request.values;
+        work_type.prepare_values(&mut values)?;
+
+        let mut dao = previous.clone();
+        dao.set_values(&work_type, values);
+
+        sqlx::query!(
+            r#"UPDATE work_item SET schema_version = $1, updated_at = $2 WHERE id = $3"#,
+            dao.schema_version,
+            dao.updated_at,
+            dao.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query!(
+            r#"DELETE FROM work_attribute_item WHERE work_item_id = $1"#,
+            dao.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        for att in &dao.work_attributes {
+            let query = create_work_attribute_item_query(dao.id, att);
+            query.execute(&mut *tx).await.map_err(AppError::Database)?;
+        }
+        refresh_documents(&mut *tx, &[dao.id], &[]).await?;
+        let change =
+            WorkItemChange::between(ChangeAction::Updated, Some(&previous), &dao, changed_by);
+        insert_change(&mut *tx, &change).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(dao))
+    }
+
+    #[instrument]
+    async fn delete(&self, id: Uuid) -> Result<bool> {
+        tracing::info!("Deleting the work item {}", id);
+        let pool = self.pool.lock().await;
+        let deleted = sqlx::query!(r#"DELETE FROM work_item WHERE id = $1"#, id)
+            .execute(&*pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    #[instrument]
+    async fn transition(
+        &self,
+        id: Uuid,
+        request: TransitionWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>> {
+        tracing::info!("Moving the work item {} to {}", id, request.to);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let previous = match fetch_work_item(&mut *tx, id).await? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let workflow = fetch_workflow(&mut *tx, previous.work_type_id).await?;
+        workflow.check_transition(previous.status.as_deref(), &request.to)?;
+
+        let mut dao = previous.clone();
+        dao.status = Some(request.to);
+        dao.updated_at = Utc::now();
+        sqlx::query!(
+            r#"UPDATE work_item SET status = $1, updated_at = $2 WHERE id = $3"#,
+            dao.status,
+            dao.updated_at,
+            dao.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        let change = WorkItemChange::between(
+            ChangeAction::Transitioned,
+            Some(&previous),
+            &dao,
+            changed_by,
+        );
+        insert_change(&mut *tx, &change).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(dao))
+    }
+
+    #[instrument]
+    async fn history(&self, id: Uuid, query: HistoryQuery) -> Result<Option<HistoryPage>> {
+        tracing::info!("Getting the history of the work item {}", id);
+        let (page, per_page) = query.pagination()?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM work_item WHERE id = $1) AS "exists!""#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        if !exists {
+            return Ok(None);
+        }
+        let (entries, total) = fetch_history(&mut tx, id, page, per_page).await?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(HistoryPage {
+            page,
+            per_page,
+            total,
+            entries,
+        }))
+    }
+}
+
+// Work items de varios tipos de trabajo, ordenados por fecha de creacion. Con
+// `item_ids` solo los indicados.
+pub(crate) async fn fetch_work_items<'e, E>(
+    executor: E,
+    work_type_ids: &[Uuid],
+    item_ids: Option<&[Uuid]>,
+) -> Result<Vec<WorkItem>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<FlatWorkItemRow> = sqlx::query_as!(
+        FlatWorkItemRow,
+        r#"
+            SELECT
+                wi.id AS work_item_id,
+                wi.work_type_id,
+                wi.schema_version,
+                wi.status,
+                wi.created_at AS work_item_created_at,
+                wi.updated_at AS work_item_updated_at,
+                wai.id AS "attribute_item_id?",
+                wai.attribute_type_id AS "attribute_type_id?",
+                wat.key AS "attribute_key?",
+                wat.name AS "attribute_name?",
+                wai.value AS "value?",
+                wai.created_at AS "attribute_created_at?",
+                wai.updated_at AS "attribute_updated_at?"
+            FROM work_item wi
+            LEFT JOIN work_attribute_item wai ON wi.id = wai.work_item_id
+            LEFT JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+            WHERE wi.work_type_id = ANY($1) AND ($2::uuid[] IS NULL OR wi.id = ANY($2))
+            ORDER BY wi.created_at, wi.id, wat.position, wat.id
+"#,
+        work_type_ids,
+        item_ids as Option<&[Uuid]>
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(into_work_items(rows))
+}
+
+async fn fetch_work_item<'e, E>(executor: E, id: Uuid) -> Result<Option<WorkItem>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<FlatWorkItemRow> = sqlx::query_as!(
+        FlatWorkItemRow,
+        r#"
+            SELECT
+                wi.id AS work_item_id,
+                wi.work_type_id,
+                wi.schema_version,
+                wi.status,
+                wi.created_at AS work_item_created_at,
+                wi.updated_at AS work_item_updated_at,
+                wai.id AS "attribute_item_id?",
+                wai.attribute_type_id AS "attribute_type_id?",
+                wat.key AS "attribute_key?",
+                wat.name AS "attribute_name?",
+                wai.value AS "value?",
+                wai.created_at AS "attribute_created_at?",
+                wai.updated_at AS "attribute_updated_at?"
+            FROM work_item wi
+            LEFT JOIN work_attribute_item wai ON wi.id = wai.work_item_id
+            LEFT JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+            WHERE wi.id = $1
+            ORDER BY wat.position, wat.id
+"#,
+        id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(into_work_items(rows).into_iter().next())
+}
+
+// Las filas llegan ordenadas por work item, asi que se agrupan conservando el orden
+fn into_work_items(rows: Vec<FlatWorkItemRow>) -> Vec<WorkItem> {
+    let mut items: Vec<WorkItem> = Vec::new();
+
+    for row in rows {
+        if items.last().map(|item| item.id) != Some(row.work_item_id) {
+            items.push(WorkItem {
+                id: row.work_item_id,
+                work_type_id: row.work_type_id,
+                schema_version: row.schema_version,
+                status: row.status,
+                work_attributes: Vec::new(),
+                created_at: row.work_item_created_at,
+                updated_at: row.work_item_updated_at,
+            });
+        }
+        let entry: &mut WorkItem = items.last_mut().unwrap();
+
+        if let (Some(attribute_item_id), Some(attribute_type_id)) =
+            (row.attribute_item_id, row.attribute_type_id)
+        {
+            entry.work_attributes.push(WorkAttributeItem {
+                id: attribute_item_id,
+                attribute_type_id,
+                key: row.attribute_key.unwrap(),
+                name: row.attribute_name.unwrap(),
+                value: row.value.unwrap(),
+                created_at: row.attribute_created_at.unwrap(),
+                updated_at: row.attribute_updated_at.unwrap(),
+            });
+        }
+    }
+
+    items
+}
+
+pub fn create_work_item_query(
+    work_item: &WorkItem,
+) -> Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    sqlx::query(
+        r#"
+INSERT INTO work_item
+(id, work_type_id, schema_version, status, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+"#,
+    )
+    .bind(work_item.id)
+    .bind(work_item.work_type_id)
+    .bind(work_item.schema_version)
+    .bind(&work_item.status)
+    .bind(work_item.created_at)
+    .bind(work_item.updated_at)
+}
+
+pub fn create_work_attribute_item_query(
+    work_item_id: Uuid,
+    att: &WorkAttributeItem,
+) -> Query<'_, sqlx::Postgres, sqlx::postgres::PgArguments> {
+    sqlx::query(
+        r#"
+INSERT INTO work_attribute_item
+(id, work_item_id, attribute_type_id, value, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+"#,
+    )
+    .bind(att.id)
+    .bind(work_item_id)
+    .bind(att.attribute_type_id)
+    .bind(&att.value)
+    .bind(att.created_at)
+    .bind(att.updated_at)
+}
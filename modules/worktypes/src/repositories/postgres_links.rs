@@ -0,0 +1,366 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::{ItemLink, LinkType, WorkItemLink};
+use crate::requests::{CreateLinkType, CreateWorkItemLink};
+use crate::validation::{validate_link, validate_link_type_request};
+
+use super::repository::LinkRepositoryTrait;
+use common::error::AppError;
+use common::error::Result;
+use common::repositories::postgres::PostgresRepository;
+
+#[derive(Debug)]
+struct FlatItemLinkRow {
+    id: Uuid,
+    link_type_id: Uuid,
+    source_id: Uuid,
+    target_id: Uuid,
+    created_at: DateTime<Utc>,
+    name: String,
+    outward_name: String,
+    inward_name: String,
+    is_hierarchical: bool,
+    link_type_created_at: DateTime<Utc>,
+    link_type_updated_at: DateTime<Utc>,
+}
+
+#[async_trait]
+impl LinkRepositoryTrait for PostgresRepository {
+    #[instrument]
+    async fn list_link_types(&self) -> Result<Vec<LinkType>> {
+        tracing::info!("Listing link types");
+        let pool = self.pool.lock().await;
+        let link_types = sqlx::query_as!(
+            LinkType,
+            r#"
+                SELECT id, name, outward_name, inward_name, is_hierarchical, created_at, updated_at
+                FROM link_type
+                ORDER BY LOWER(name), id
+            "#
+        )
+        .fetch_all(&*pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(link_types)
+    }
+
+    #[instrument]
+    async fn get_link_type(&self, id: Uuid) -> Result<Option<LinkType>> {
+        tracing::info!("Getting the link type {}", id);
+        let pool = self.pool.lock().await;
+        fetch_link_type(&*pool, id).await
+    }
+
+    #[instrument]
+    async fn create_link_type(&self, request: CreateLinkType) -> Result<LinkType> {
+        tracing::info!("Creating the link type {}", request.name);
+        validate_link_type_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let dao = LinkType::from_create_request(request);
+        check_link_type_name(&mut *tx, dao.id, &dao.name).await?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO link_type
+                (id, name, outward_name, inward_name, is_hierarchical, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            dao.id,
+            dao.name,
+            dao.outward_name,
+            dao.inward_name,
+            dao.is_hierarchical,
+            dao.created_at,
+            dao.updated_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(dao)
+    }
+
+    #[instrument]
+    async fn update_link_type(
+        &self,
+        id: Uuid,
+        request: CreateLinkType,
+    ) -> Result<Option<LinkType>> {
+        tracing::info!("Updating the link type {}", id);
+        validate_link_type_request(&request)?;
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        let mut dao = match fetch_link_type(&mut *tx, id).await? {
+            Some(link_type) => link_type,
+            None => return Ok(None),
+        };
+        check_link_type_name(&mut *tx, id, &request.name).await?;
+
+        // Los enlaces existentes se validaron con la jerarquia actual
+        if dao.is_hierarchical != request.is_hierarchical {
+            let links = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM work_item_link WHERE link_type_id = $1"#,
+                id
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+            if links > 0 {
+                return Err(AppError::Validation(format!(
+                    "link type '{}' cannot change its hierarchy while it has {} links",
+                    dao.name, links
+                )));
+            }
+        }
+
+        dao.update(request);
+        sqlx::query!(
+            r#"
+                UPDATE link_type
+                SET name = $1, outward_name = $2, inward_name = $3, is_hierarchical = $4, updated_at = $5
+                WHERE id = $6
+            "#,
+            dao.name,
+            dao.outward_name,
+            dao.inward_name,
+            dao.is_hierarchical,
+            dao.updated_at,
+            dao.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(dao))
+    }
+
+    #[instrument]
+    async fn delete_link_type(&self, id: Uuid) -> Result<bool> {
+        tracing::info!("Deleting the link type {}", id);
+        let pool = self.pool.lock().await;
+        let deleted = sqlx::query!(r#"DELETE FROM link_type WHERE id = $1"#, id)
+            .execute(&*pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    #[instrument]
+    async fn list_links(&self, item_id: Uuid) -> Result<Option<Vec<ItemLink>>> {
+        tracing::info!("Listing the links of the work item {}", item_id);
+        let pool = self.pool.lock().await;
+        if !work_item_exists(&*pool, item_id).await? {
+            return Ok(None);
+        }
+
+        let rows: Vec<FlatItemLinkRow> = sqlx::query_as!(
+            FlatItemLinkRow,
+            r#"
+                SELECT
+                    l.id,
+                    l.link_type_id,
+                    l.source_id,
+                    l.target_id,
+                    l.created_at,
+                    lt.name,
+                    lt.outward_name,
+                    lt.inward_name,
+                    lt.is_hierarchical,
+                    lt.created_at AS link_type_created_at,
+                    lt.updated_at AS link_type_updated_at
+                FROM work_item_link l
+                JOIN link_type lt ON lt.id = l.link_type_id
+                WHERE l.source_id = $1 OR l.target_id = $1
+                ORDER BY l.created_at, l.id
+            "#,
+            item_id
+        )
+        .fetch_all(&*pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let links = rows
+            .into_iter()
+            .map(|row| {
+                let link_type = LinkType {
+                    id: row.link_type_id,
+                    name: row.name,
+                    outward_name: row.outward_name,
+                    inward_name: row.inward_name,
+                    is_hierarchical: row.is_hierarchical,
+                    created_at: row.link_type_created_at,
+                    updated_at: row.link_type_updated_at,
+                };
+                let link = WorkItemLink {
+                    id: row.id,
+                    link_type_id: row.link_type_id,
+                    source_id: row.source_id,
+                    target_id: row.target_id,
+                    created_at: row.created_at,
+                };
+                link.seen_from(item_id, &link_type)
+            })
+            .collect();
+
+        Ok(Some(links))
+    }
+
+    #[instrument]
+    async fn add_link(
+        &self,
+        item_id: Uuid,
+        request: CreateWorkItemLink,
+    ) -> Result<Option<WorkItemLink>> {
+        tracing::info!("Linking the work item {} to {}", item_id, request.target_id);
+        let pool = self.pool.lock().await;
+        let mut tx: sqlx::Transaction<'static, sqlx::Postgres> =
+            pool.begin().await.map_err(AppError::Database)?;
+
+        if !work_item_exists(&mut *tx, item_id).await? {
+            return Ok(None);
+        }
+        let link_type = fetch_link_type(&mut *tx, request.link_type_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Validation(format!("link type {} does not exist", request.link_type_id))
+            })?;
+        if !work_item_exists(&mut *tx, request.target_id).await? {
+            return Err(AppError::Validation(format!(
+                "work item {} does not exist",
+                request.target_id
+            )));
+        }
+
+        // Enlaces que llegan al destino y, en los jerarquicos, todos los que
+        // cuelgan de el: son los unicos que pueden cerrar un ciclo
+        let links: Vec<WorkItemLink> = sqlx::query_as!(
+            WorkItemLink,
+            r#"
+                WITH RECURSIVE reachable AS (
+                    SELECT l.id, l.link_type_id, l.source_id, l.target_id, l.created_at
+                    FROM work_item_link l
+                    WHERE l.link_type_id = $1 AND (l.source_id = $2 OR l.target_id = $2)
+                    UNION
+                    SELECT l.id, l.link_type_id, l.source_id, l.target_id, l.created_at
+                    FROM work_item_link l
+                    JOIN reachable r ON l.source_id = r.target_id
+                    WHERE l.link_type_id = $1 AND $3
+                )
+                SELECT
+                    id AS "id!",
+                    link_type_id AS "link_type_id!",
+                    source_id AS "source_id!",
+                    target_id AS "target_id!",
+                    created_at AS "created_at!"
+                FROM reachable
+            "#,
+            link_type.id,
+            request.target_id,
+            link_type.is_hierarchical
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+        validate_link(&link_type, item_id, request.target_id, &links)?;
+
+        let dao = WorkItemLink::new(link_type.id, item_id, request.target_id);
+        sqlx::query!(
+            r#"
+                INSERT INTO work_item_link (id, link_type_id, source_id, target_id, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            dao.id,
+            dao.link_type_id,
+            dao.source_id,
+            dao.target_id,
+            dao.created_at
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(Some(dao))
+    }
+
+    #[instrument]
+    async fn remove_link(&self, item_id: Uuid, link_id: Uuid) -> Result<bool> {
+        tracing::info!("Removing the link {} of the work item {}", link_id, item_id);
+        let pool = self.pool.lock().await;
+        let deleted = sqlx::query!(
+            r#"DELETE FROM work_item_link WHERE id = $1 AND (source_id = $2 OR target_id = $2)"#,
+            link_id,
+            item_id
+        )
+        .execute(&*pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+}
+
+async fn fetch_link_type<'e, E>(executor: E, id: Uuid) -> Result<Option<LinkType>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as!(
+        LinkType,
+        r#"
+            SELECT id, name, outward_name, inward_name, is_hierarchical, created_at, updated_at
+            FROM link_type
+            WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::Database)
+}
+
+// Los nombres de los tipos de enlace no distinguen mayusculas
+async fn check_link_type_name<'e, E>(executor: E, id: Uuid, name: &str) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let existing = sqlx::query_scalar!(
+        r#"SELECT id FROM link_type WHERE LOWER(name) = LOWER($1) AND id <> $2"#,
+        name,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    match existing {
+        Some(_) => Err(AppError::Validation(format!(
+            "a link type named '{}' already exists",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
+async fn work_item_exists<'e, E>(executor: E, id: Uuid) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let exists = sqlx::query_scalar!(r#"SELECT id FROM work_item WHERE id = $1"#, id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::Database)?;
+
+    Ok(exists.is_some())
+}
@@ -0,0 +1,90 @@
+use std::collections::{BTreeMap, HashMap};
+
+use uuid::Uuid;
+
+use crate::search::{mark_html, SearchLanguage, MARK_START, MARK_STOP};
+use common::error::AppError;
+use common::error::Result;
+
+// Ids de los work items del tipo de trabajo que coinciden con la busqueda, de
+// mas a menos relevantes. Con `item_ids` solo se buscan entre esos.
+pub(crate) async fn search_work_item_ids<'e, E>(
+    executor: E,
+    work_type_id: Uuid,
+    q: &str,
+    item_ids: Option<&[Uuid]>,
+) -> Result<Vec<(Uuid, f32)>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let ranked = sqlx::query!(
+        r#"
+            WITH search AS (SELECT websearch_to_tsquery($2::text::regconfig, $3) AS query)
+            SELECT wi.id, ts_rank_cd(wi.search_vector, search.query) AS "rank!"
+            FROM work_item wi, search
+            WHERE wi.work_type_id = $1
+              AND wi.search_vector @@ search.query
+              AND ($4::uuid[] IS NULL OR wi.id = ANY($4))
+            ORDER BY 2 DESC, wi.created_at, wi.id
+            "#,
+        work_type_id,
+        SearchLanguage::get().regconfig(),
+        q,
+        item_ids as Option<&[Uuid]>
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    Ok(ranked.into_iter().map(|row| (row.id, row.rank)).collect())
+}
+
+// Valores de los atributos de texto con alguna de las palabras buscadas, por
+// work item y clave de atributo, marcados igual que en memoria
+pub(crate) async fn fetch_highlights<'e, E>(
+    executor: E,
+    q: &str,
+    item_ids: &[Uuid],
+) -> Result<HashMap<Uuid, BTreeMap<String, String>>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows = sqlx::query!(
+        r#"
+            WITH search AS (SELECT websearch_to_tsquery($1::text::regconfig, $2) AS query)
+            SELECT
+                wai.work_item_id,
+                wat.key,
+                ts_headline(
+                    $1::text::regconfig,
+                    translate(wai.value, $4 || $5, ''),
+                    search.query,
+                    'HighlightAll=true, StartSel=' || $4 || ', StopSel=' || $5
+                ) AS "snippet!"
+            FROM work_attribute_item wai
+            JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+            CROSS JOIN search
+            WHERE wai.work_item_id = ANY($3)
+              AND wat.data_type = 'string'
+            "#,
+        SearchLanguage::get().regconfig(),
+        q,
+        item_ids,
+        MARK_START.to_string(),
+        MARK_STOP.to_string()
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    let mut highlights: HashMap<Uuid, BTreeMap<String, String>> = HashMap::new();
+    for row in rows {
+        if let Some(snippet) = mark_html(&row.snippet) {
+            highlights
+                .entry(row.work_item_id)
+                .or_default()
+                .insert(row.key, snippet);
+        }
+    }
+    Ok(highlights)
+}
@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::models::{DataType, WorkAttributeType};
+use crate::search::SearchLanguage;
+use common::error::AppError;
+use common::error::Result;
+use common::repositories::postgres::PostgresRepository;
+
+// Longitud maxima de un identificador en PostgreSQL
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+const VALUE_INDEX_PREFIX: &str = "idx_work_item_value_";
+
+static VALUE_INDEXES: Mutex<()> = Mutex::const_new(());
+
+// Reconstruye el documento JSONB tipado y el vector de busqueda de los work
+// items indicados, o de todos los de los tipos de trabajo indicados, a partir
+// de sus valores. El vector recoge los atributos de texto.
+pub(crate) async fn refresh_documents<'e, E>(
+    executor: E,
+    work_item_ids: &[Uuid],
+    work_type_ids: &[Uuid],
+) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query!(
+        r#"
+            UPDATE work_item wi
+            SET attributes = COALESCE((
+                SELECT jsonb_object_agg(wat.key, work_item_typed_value(wat.data_type, wai.value))
+                FROM work_attribute_item wai
+                JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+                WHERE wai.work_item_id = wi.id
+            ), '{}'::jsonb),
+            search_vector = to_tsvector($3::text::regconfig, COALESCE((
+                SELECT string_agg(wai.value, ' ' ORDER BY wat.position)
+                FROM work_attribute_item wai
+                JOIN work_attribute_type wat ON wat.id = wai.attribute_type_id
+                WHERE wai.work_item_id = wi.id AND wat.data_type = 'string'
+            ), '')),
+            search_language = $3
+            WHERE wi.id = ANY($1) OR wi.work_type_id = ANY($2)
+            "#,
+        work_item_ids,
+        work_type_ids,
+        SearchLanguage::get().regconfig()
+    )
+    .execute(executor)
+    .await
+    .map_err(AppError::Database)?;
+
+    Ok(())
+}
+
+// Indexa los work items que aun no tienen vector de busqueda o que se
+// indexaron con otro idioma
+pub async fn ensure_search_index(repository: &PostgresRepository) -> Result<()> {
+    let pool = repository.pool.lock().await;
+    let language = SearchLanguage::get();
+
+    let stale = sqlx::query_scalar!(
+        r#"SELECT id FROM work_item WHERE search_language IS DISTINCT FROM $1"#,
+        language.regconfig()
+    )
+    .fetch_all(&*pool)
+    .await
+    .map_err(AppError::Database)?;
+    if !stale.is_empty() {
+        tracing::info!(
+            "Indexing {} work items for the {} search",
+            stale.len(),
+            language
+        );
+        refresh_documents(&*pool, &stale, &[]).await?;
+    }
+
+    Ok(())
+}
+
+// Los atributos numericos y de fecha tienen un indice de expresion por clave
+// para compararlos y ordenarlos; se comparte entre los tipos de trabajo que
+// usan la misma clave. Se crean fuera de las transacciones de la API y con
+// CONCURRENTLY, para no bloquear las escrituras en work_item mientras se
+// construyen. Los fallos solo se registran: el indice se vuelve a intentar
+// al arrancar. `keys` son las claves que acaban de pasar a ser comparables;
+// sin ellas se revisan todas, como al arrancar.
+pub fn spawn_value_indexes(pool: &sqlx::PgPool, keys: Option<Vec<String>>) {
+    if keys.as_ref().is_some_and(Vec::is_empty) {
+        return;
+    }
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ensure_value_indexes(&pool, keys.as_deref()).await {
+            tracing::warn!("The value indexes could not be created: {:?}", e);
+        }
+    });
+}
+
+// Claves comparables de `after` que no lo eran en `before`: las unicas que
+// pueden no tener aun su indice
+pub(crate) fn added_value_keys<'a, 'b>(
+    before: impl IntoIterator<Item = &'a WorkAttributeType>,
+    after: impl IntoIterator<Item = &'b WorkAttributeType>,
+) -> Vec<String> {
+    let indexed: HashSet<&str> = before
+        .into_iter()
+        .filter(|a| has_value_index(a.data_type))
+        .map(|a| a.key.as_str())
+        .collect();
+    let mut keys: Vec<String> = after
+        .into_iter()
+        .filter(|a| has_value_index(a.data_type) && !indexed.contains(a.key.as_str()))
+        .map(|a| a.key.clone())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+fn has_value_index(data_type: DataType) -> bool {
+    data_type.is_numeric() || matches!(data_type, DataType::DateType | DataType::DateTimeType)
+}
+
+async fn ensure_value_indexes(pool: &sqlx::PgPool, keys: Option<&[String]>) -> Result<()> {
+    // Una sola construccion a la vez en este proceso
+    let _guard = VALUE_INDEXES.lock().await;
+
+    let keys = sqlx::query!(
+        r#"
+            SELECT DISTINCT key, left(md5(key), 12) AS "hash!"
+            FROM work_attribute_type
+            WHERE (data_type IN ('numeric', 'date', 'datetime') OR data_type LIKE 'decimal%')
+            AND ($1::text[] IS NULL OR key = ANY($1))
+            "#,
+        keys
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::Database)?;
+
+    for row in keys.into_iter().filter(|row| is_plain_key(&row.key)) {
+        // Las claves largas se recortan y se distinguen con su hash para no
+        // pasar del limite de los identificadores, igual que en la migracion
+        let key = &row.key;
+        let index_name = if VALUE_INDEX_PREFIX.len() + key.len() <= MAX_IDENTIFIER_LENGTH {
+            format!("{}{}", VALUE_INDEX_PREFIX, key)
+        } else {
+            format!("{}{}_{}", VALUE_INDEX_PREFIX, &key[..30], row.hash)
+        };
+
+        let valid = sqlx::query_scalar!(
+            r#"
+                SELECT i.indisvalid
+                FROM pg_class c
+                JOIN pg_index i ON i.indexrelid = c.oid
+                WHERE c.relname = $1
+                "#,
+            index_name
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+        match valid {
+            Some(true) => continue,
+            // Una construccion concurrente interrumpida deja el indice invalido
+            Some(false) => {
+                sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", index_name))
+                    .execute(pool)
+                    .await
+                    .map_err(AppError::Database)?;
+            }
+            None => {}
+        }
+
+        // La clave solo tiene minusculas, digitos y guiones bajos, asi que se
+        // puede escribir tal cual en la sentencia
+        tracing::info!("Creating the value index {}", index_name);
+        let statement = format!(
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS {} ON work_item ((attributes -> '{}'))",
+            index_name, key
+        );
+        sqlx::query(&statement)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+    }
+
+    Ok(())
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(name: &str, data_type: DataType) -> WorkAttributeType {
+        WorkAttributeType::new(name.to_string(), data_type, false, false)
+    }
+
+    #[test]
+    fn test_added_value_keys_only_returns_new_comparable_keys() {
+        let before = [
+            attribute("Severity", DataType::NumericType),
+            attribute("Due", DataType::StringType),
+        ];
+        let after = [
+            attribute("Severity", DataType::DEFAULT_DECIMAL),
+            attribute("Due", DataType::DateType),
+            attribute("Reported", DataType::DateTimeType),
+            attribute("Summary", DataType::StringType),
+            attribute("Reported", DataType::DateTimeType),
+        ];
+
+        assert_eq!(added_value_keys(&before, &after), ["due", "reported"]);
+        assert!(added_value_keys(&after, &before).is_empty());
+    }
+}
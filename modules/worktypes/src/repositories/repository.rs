@@ -1,10 +1,173 @@
 use async_trait::async_trait;
+use common::config::Storage;
+use uuid::Uuid;
 
-use crate::{models::WorkType, requests::CreateWorkType};
+use crate::{
+    bundle::{ImportOutcome, WorkTypeBundle},
+    history::HistoryPage,
+    impact::{ImpactReport, ProposedChange},
+    migration::MigrationReport,
+    models::{
+        ItemLink, LinkType, WorkAttributeType, WorkItem, WorkItemLink, WorkType, WorkTypeStatus,
+        WorkTypeVersion, Workflow,
+    },
+    requests::{
+        ConflictPolicy, CreateLinkType, CreateWorkItem, CreateWorkItemLink, CreateWorkType,
+        CreateWorkflow, HistoryQuery, MigrateAttribute, TransitionWorkItem, WorkAttributeRequest,
+        WorkItemListQuery, WorkTypeQuery,
+    },
+    search::SearchHit,
+    sync::SyncPlan,
+};
 use common::error::Result;
 
 #[async_trait]
 pub trait WorkTypeRepositoryTrait {
-    async fn list(&self) -> Result<Vec<WorkType>>;
+    async fn list(&self, query: WorkTypeQuery) -> Result<Vec<WorkType>>;
+    async fn get(&self, id: Uuid) -> Result<Option<WorkType>>;
     async fn create(&self, request: CreateWorkType) -> Result<WorkType>;
+    async fn update(&self, id: Uuid, request: CreateWorkType) -> Result<Option<WorkType>>;
+    async fn delete(&self, id: Uuid) -> Result<bool>;
+    async fn duplicate(&self, id: Uuid) -> Result<Option<WorkType>>;
+    async fn list_versions(&self, id: Uuid) -> Result<Option<Vec<WorkTypeVersion>>>;
+    async fn get_version(&self, id: Uuid, version: i32) -> Result<Option<WorkTypeVersion>>;
+    async fn get_workflow(&self, id: Uuid) -> Result<Option<Workflow>>;
+    async fn set_workflow(&self, id: Uuid, request: CreateWorkflow) -> Result<Option<Workflow>>;
+    // Publica o deja obsoleto un tipo de trabajo
+    async fn set_status(&self, id: Uuid, status: WorkTypeStatus) -> Result<Option<WorkType>>;
+    // Atributos propios sueltos; los heredados se cambian en el tipo de trabajo
+    // que los define. Devuelven `None` si no existe el tipo de trabajo o el atributo.
+    async fn create_attribute(
+        &self,
+        id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>>;
+    async fn update_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: WorkAttributeRequest,
+    ) -> Result<Option<WorkAttributeType>>;
+    async fn delete_attribute(&self, id: Uuid, attribute_id: Uuid) -> Result<bool>;
+    // Cambia la definicion de un atributo convirtiendo los valores guardados
+    async fn migrate_attribute(
+        &self,
+        id: Uuid,
+        attribute_id: Uuid,
+        request: MigrateAttribute,
+    ) -> Result<Option<MigrationReport>>;
+    // Simula el cambio de un atributo sin aplicarlo
+    async fn analyze_impact(
+        &self,
+        id: Uuid,
+        change: ProposedChange,
+    ) -> Result<Option<ImpactReport>>;
+    // Solo trabajan con los tipos de trabajo globales. La importacion se aplica
+    // entera o no se aplica.
+    async fn export_bundle(&self, ids: Vec<Uuid>) -> Result<WorkTypeBundle>;
+    async fn import_bundle(
+        &self,
+        bundle: WorkTypeBundle,
+        policy: ConflictPolicy,
+    ) -> Result<Vec<ImportOutcome>>;
+    // Deja los tipos de trabajo globales como dice `document`; sin `apply`
+    // solo devuelve el plan
+    async fn sync(&self, document: WorkTypeBundle, apply: bool) -> Result<SyncPlan>;
+    // Devuelven `None` si la compañia no existe
+    async fn list_for_company(
+        &self,
+        company_id: &str,
+        query: WorkTypeQuery,
+    ) -> Result<Option<Vec<WorkType>>>;
+    async fn create_for_company(
+        &self,
+        company_id: &str,
+        request: CreateWorkType,
+    ) -> Result<Option<WorkType>>;
+    // Da de alta una compañia nueva. En PostgreSQL ya la recoge la tabla
+    // `company`.
+    async fn add_company(&self, company_id: &str) -> Result<()>;
+    // Copian o borran el catalogo propio de una compañia cuando se duplica o
+    // se elimina. Devuelven cuantos tipos de trabajo se copian o se borran.
+    async fn duplicate_for_company(
+        &self,
+        from_company_id: &str,
+        to_company_id: &str,
+    ) -> Result<u64>;
+    // Se llama despues de borrar la compañia. En PostgreSQL la clave foranea
+    // ya ha borrado el catalogo.
+    async fn delete_for_company(&self, company_id: &str) -> Result<u64>;
+}
+
+// Los metodos que reciben un `work_type_id` devuelven `None` si el tipo de
+// trabajo no existe
+#[async_trait]
+pub trait WorkItemRepositoryTrait {
+    async fn list(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<WorkItem>>>;
+    // Busqueda de texto `q`, ordenada por relevancia y combinada con `filter`
+    async fn search(
+        &self,
+        work_type_id: Uuid,
+        query: WorkItemListQuery,
+    ) -> Result<Option<Vec<SearchHit>>>;
+    async fn get(&self, id: Uuid) -> Result<Option<WorkItem>>;
+    // `changed_by` es quien hace el cambio, para el historial del work item
+    async fn create(
+        &self,
+        work_type_id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>>;
+    async fn update(
+        &self,
+        id: Uuid,
+        request: CreateWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>>;
+    async fn delete(&self, id: Uuid) -> Result<bool>;
+    async fn transition(
+        &self,
+        id: Uuid,
+        request: TransitionWorkItem,
+        changed_by: Option<String>,
+    ) -> Result<Option<WorkItem>>;
+    async fn history(&self, id: Uuid, query: HistoryQuery) -> Result<Option<HistoryPage>>;
+}
+
+// Los metodos de enlaces reciben el work item desde el que se miran y devuelven
+// `None` si no existe
+#[async_trait]
+pub trait LinkRepositoryTrait {
+    async fn list_link_types(&self) -> Result<Vec<LinkType>>;
+    async fn get_link_type(&self, id: Uuid) -> Result<Option<LinkType>>;
+    async fn create_link_type(&self, request: CreateLinkType) -> Result<LinkType>;
+    async fn update_link_type(&self, id: Uuid, request: CreateLinkType)
+        -> Result<Option<LinkType>>;
+    async fn delete_link_type(&self, id: Uuid) -> Result<bool>;
+    async fn list_links(&self, item_id: Uuid) -> Result<Option<Vec<ItemLink>>>;
+    async fn add_link(
+        &self,
+        item_id: Uuid,
+        request: CreateWorkItemLink,
+    ) -> Result<Option<WorkItemLink>>;
+    async fn remove_link(&self, item_id: Uuid, link_id: Uuid) -> Result<bool>;
+}
+
+// Enum para seleccionar el tipo de repositorio
+pub enum RepositoryProvider {
+    Memory,
+    Postgres(String),
+}
+
+impl From<Storage> for RepositoryProvider {
+    fn from(storage: Storage) -> Self {
+        match storage {
+            Storage::Postgres(database_url) => RepositoryProvider::Postgres(database_url),
+            Storage::Memory => RepositoryProvider::Memory,
+        }
+    }
 }
@@ -1,18 +1,396 @@
-use crate::models::DataType;
+use std::collections::HashMap;
 
+use crate::models::{attribute_key, DataType, StatusCategory, WorkType, WorkTypeStatus};
+
+use common::error::AppError;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreateWorkType {
     pub title: String,
     pub description: Option<String>,
+    pub parent_id: Option<Uuid>,
+    // Solo se tiene en cuenta al crear; despues se cambia con publish y deprecate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<WorkTypeStatus>,
     pub attributes: Vec<CreateWorkAttributeType>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreateWorkAttributeType {
     pub name: String,
+    // Clave con la que se leen y escriben los valores; sin ella se deriva del nombre
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
     pub data_type: DataType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
     pub is_required: bool,
     pub is_hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formula: Option<String>,
+}
+
+impl CreateWorkAttributeType {
+    pub fn key(&self) -> String {
+        self.key
+            .clone()
+            .unwrap_or_else(|| attribute_key(&self.name))
+    }
+}
+
+// Cuerpo de los endpoints de un atributo suelto. Sin `position` un atributo
+// nuevo va al final y uno existente se queda donde esta.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkAttributeRequest {
+    #[serde(flatten)]
+    pub attribute: CreateWorkAttributeType,
+    #[serde(default)]
+    pub position: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateWorkItem {
+    pub values: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateWorkflow {
+    pub initial_status: Option<String>,
+    pub statuses: Vec<CreateWorkflowStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateWorkflowStatus {
+    pub name: String,
+    pub category: StatusCategory,
+    pub transitions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionWorkItem {
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateLinkType {
+    pub name: String,
+    pub outward_name: String,
+    pub inward_name: String,
+    #[serde(default)]
+    pub is_hierarchical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CreateWorkItemLink {
+    pub link_type_id: Uuid,
+    pub target_id: Uuid,
+}
+
+// Parametros del listado de work items: `filter` es una expresion sobre sus
+// valores, como `severity >= 3 AND status = "open"`, y `q` un texto a buscar
+// en sus atributos de texto
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct WorkItemListQuery {
+    pub filter: Option<String>,
+    pub q: Option<String>,
+}
+
+// Paginacion del historial de un work item, desde la pagina 1
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HistoryQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl HistoryQuery {
+    pub const DEFAULT_PER_PAGE: u32 = 20;
+    pub const MAX_PER_PAGE: u32 = 100;
+
+    // Pagina y tamaño de pagina pedidos, o los de por defecto
+    pub fn pagination(&self) -> Result<(u32, u32), AppError> {
+        let page = self.page.unwrap_or(1);
+        let per_page = self.per_page.unwrap_or(Self::DEFAULT_PER_PAGE);
+        if page == 0 {
+            return Err(AppError::Validation("page must be at least 1".to_string()));
+        }
+        if per_page == 0 || per_page > Self::MAX_PER_PAGE {
+            return Err(AppError::Validation(format!(
+                "per_page must be between 1 and {}",
+                Self::MAX_PER_PAGE
+            )));
+        }
+        Ok((page, per_page))
+    }
+}
+
+// Parametros de consulta de un work item: `include=links` añade sus enlaces
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct WorkItemQuery {
+    pub include: Option<String>,
+}
+
+impl WorkItemQuery {
+    pub fn includes(&self, relation: &str) -> bool {
+        self.include
+            .as_deref()
+            .is_some_and(|include| include.split(',').any(|r| r.trim() == relation))
+    }
+}
+
+// Parametros de la exportacion de tipos de trabajo: `ids` separados por comas,
+// todos los globales si no se indican
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct BundleExportQuery {
+    pub ids: Option<String>,
+    pub format: Option<BundleFormat>,
+}
+
+impl BundleExportQuery {
+    pub fn ids(&self) -> Result<Vec<Uuid>, AppError> {
+        self.ids
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.parse()
+                    .map_err(|_| AppError::Validation(format!("'{}' is not a worktype id", id)))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct BundleImportQuery {
+    pub conflict: Option<ConflictPolicy>,
+}
+
+// Sin `apply=true` la sincronizacion solo calcula el plan
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SyncQuery {
+    pub apply: Option<bool>,
+}
+
+// Que hacer con los tipos de trabajo del paquete cuyo titulo ya existe
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+// Cuerpo de la migracion de un atributo: la nueva definicion, como en la
+// actualizacion, y que hacer con los valores que no se pueden convertir
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrateAttribute {
+    #[serde(flatten)]
+    pub attribute: WorkAttributeRequest,
+    #[serde(default)]
+    pub on_failure: FailureStrategy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureStrategy {
+    #[default]
+    Abort,
+    Null,
+    Default,
+}
+
+// Parametros de consulta del listado de tipos de trabajo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct WorkTypeQuery {
+    pub title: Option<String>,
+    // Sin indicarlo se listan todos menos los borradores
+    pub status: Option<WorkTypeStatus>,
+    pub sort: Option<WorkTypeSort>,
+    pub order: Option<SortOrder>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkTypeSort {
+    Title,
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl WorkTypeQuery {
+    // Filtro y orden del repositorio en memoria; PostgreSQL los aplica en la
+    // consulta con los mismos criterios. El id desempata para que el orden sea
+    // siempre el mismo.
+    pub fn apply(&self, work_types: Vec<WorkType>) -> Vec<WorkType> {
+        let title_filter: Option<String> = self.title.as_ref().map(|t| t.to_lowercase());
+        let mut work_types: Vec<WorkType> = work_types
+            .into_iter()
+            .filter(|wt| match self.status {
+                Some(status) => wt.status == status,
+                None => wt.status != WorkTypeStatus::Draft,
+            })
+            .filter(|wt| {
+                title_filter
+                    .as_ref()
+                    .is_none_or(|filter| wt.title.to_lowercase().contains(filter))
+            })
+            .collect();
+
+        work_types.sort_by(|a, b| {
+            let ordering = match self.sort.unwrap_or_default() {
+                WorkTypeSort::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+                WorkTypeSort::CreatedAt => a.created_at.cmp(&b.created_at),
+                WorkTypeSort::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            };
+            let ordering = ordering.then_with(|| a.id.cmp(&b.id));
+            match self.order.unwrap_or_default() {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+
+        work_types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn work_types() -> Vec<WorkType> {
+        let now = Utc::now();
+        let work_type = |title: &str, status, age: i64| WorkType {
+            status,
+            created_at: now - Duration::days(age),
+            updated_at: now - Duration::days(10 - age),
+            ..WorkType::default(title.to_string())
+        };
+        vec![
+            work_type("bug", WorkTypeStatus::Published, 3),
+            work_type("Epic", WorkTypeStatus::Deprecated, 1),
+            work_type("Story", WorkTypeStatus::Published, 2),
+            work_type("Debug session", WorkTypeStatus::Draft, 4),
+        ]
+    }
+
+    fn titles(query: WorkTypeQuery) -> Vec<String> {
+        query
+            .apply(work_types())
+            .into_iter()
+            .map(|wt| wt.title)
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_hides_drafts_unless_asked_for() {
+        assert_eq!(titles(WorkTypeQuery::default()), ["bug", "Story", "Epic"]);
+        assert_eq!(
+            titles(WorkTypeQuery {
+                status: Some(WorkTypeStatus::Draft),
+                ..Default::default()
+            }),
+            ["Debug session"]
+        );
+    }
+
+    #[test]
+    fn test_apply_filters_titles_ignoring_case() {
+        assert_eq!(
+            titles(WorkTypeQuery {
+                title: Some("BUG".to_string()),
+                ..Default::default()
+            }),
+            ["bug"]
+        );
+        assert_eq!(
+            titles(WorkTypeQuery {
+                title: Some("bug".to_string()),
+                status: Some(WorkTypeStatus::Draft),
+                ..Default::default()
+            }),
+            ["Debug session"]
+        );
+    }
+
+    #[test]
+    fn test_apply_sorts_by_the_requested_field() {
+        assert_eq!(
+            titles(WorkTypeQuery {
+                sort: Some(WorkTypeSort::Title),
+                ..Default::default()
+            }),
+            ["bug", "Epic", "Story"]
+        );
+        assert_eq!(
+            titles(WorkTypeQuery {
+                sort: Some(WorkTypeSort::UpdatedAt),
+                order: Some(SortOrder::Desc),
+                ..Default::default()
+            }),
+            ["bug", "Story", "Epic"]
+        );
+        assert_eq!(
+            titles(WorkTypeQuery {
+                order: Some(SortOrder::Desc),
+                ..Default::default()
+            }),
+            ["Epic", "Story", "bug"]
+        );
+    }
+
+    #[test]
+    fn test_apply_breaks_ties_by_id() {
+        let now = Utc::now();
+        let mut work_types: Vec<WorkType> = (0..5)
+            .map(|_| WorkType {
+                created_at: now,
+                ..WorkType::default("Bug".to_string())
+            })
+            .collect();
+        let mut ids: Vec<Uuid> = work_types.iter().map(|wt| wt.id).collect();
+        ids.sort();
+        work_types.reverse();
+
+        let sorted: Vec<Uuid> = WorkTypeQuery::default()
+            .apply(work_types)
+            .into_iter()
+            .map(|wt| wt.id)
+            .collect();
+
+        assert_eq!(sorted, ids);
+    }
 }
@@ -1,17 +1,127 @@
 use std::sync::Arc;
 
 use axum::{
-    routing::{get, post, put},
+    extract::FromRef,
+    routing::{delete, get, post},
     Router,
 };
 
 use crate::{
-    handlers::{create_worktype, list_worktypes},
-    repositories::repository::WorkTypeRepositoryTrait,
+    handlers::{
+        add_work_item_link, analyze_worktype_impact, create_company_worktype, create_link_type,
+        create_work_item, create_worktype, create_worktype_attribute, delete_link_type,
+        delete_work_item, delete_worktype, delete_worktype_attribute, deprecate_worktype,
+        duplicate_worktype, export_worktypes, get_link_type, get_work_item, get_work_item_history,
+        get_worktype, get_worktype_attribute, get_worktype_schema, get_worktype_version,
+        get_worktype_workflow, import_worktypes, list_company_worktypes, list_link_types,
+        list_work_item_links, list_work_items, list_worktype_attributes, list_worktype_versions,
+        list_worktypes, migrate_worktype_attribute, publish_worktype, remove_work_item_link,
+        set_worktype_workflow, sync_worktypes, transition_work_item, update_link_type,
+        update_work_item, update_worktype, update_worktype_attribute,
+    },
+    repositories::repository::{
+        LinkRepositoryTrait, WorkItemRepositoryTrait, WorkTypeRepositoryTrait,
+    },
 };
 
-pub fn create_routes(repository: Arc<dyn WorkTypeRepositoryTrait + Send + Sync>) -> Router {
-    Router::new()
+// Los handlers de work items y enlaces extraen del estado el repositorio que necesitan
+#[derive(Clone)]
+pub struct WorkItemState {
+    pub items: Arc<dyn WorkItemRepositoryTrait + Send + Sync>,
+    pub links: Arc<dyn LinkRepositoryTrait + Send + Sync>,
+}
+
+impl FromRef<WorkItemState> for Arc<dyn WorkItemRepositoryTrait + Send + Sync> {
+    fn from_ref(state: &WorkItemState) -> Self {
+        state.items.clone()
+    }
+}
+
+impl FromRef<WorkItemState> for Arc<dyn LinkRepositoryTrait + Send + Sync> {
+    fn from_ref(state: &WorkItemState) -> Self {
+        state.links.clone()
+    }
+}
+
+pub fn create_routes(
+    repository: Arc<dyn WorkTypeRepositoryTrait + Send + Sync>,
+    item_repository: Arc<dyn WorkItemRepositoryTrait + Send + Sync>,
+    link_repository: Arc<dyn LinkRepositoryTrait + Send + Sync>,
+) -> Router {
+    let work_types = Router::new()
         .route("/worktypes", get(list_worktypes).post(create_worktype))
-        .with_state(repository)
+        .route("/worktypes/export", get(export_worktypes))
+        .route("/worktypes/import", post(import_worktypes))
+        .route("/worktypes/sync", post(sync_worktypes))
+        .route(
+            "/worktypes/{id}",
+            get(get_worktype)
+                .put(update_worktype)
+                .delete(delete_worktype),
+        )
+        .route(
+            "/worktypes/{id}/attributes",
+            get(list_worktype_attributes).post(create_worktype_attribute),
+        )
+        .route(
+            "/worktypes/{id}/attributes/{attribute_id}",
+            get(get_worktype_attribute)
+                .put(update_worktype_attribute)
+                .delete(delete_worktype_attribute),
+        )
+        .route(
+            "/worktypes/{id}/attributes/{attribute_id}/migrate",
+            post(migrate_worktype_attribute),
+        )
+        .route("/worktypes/{id}/impact", post(analyze_worktype_impact))
+        .route("/worktypes/{id}/duplicate", post(duplicate_worktype))
+        .route("/worktypes/{id}/publish", post(publish_worktype))
+        .route("/worktypes/{id}/deprecate", post(deprecate_worktype))
+        .route("/worktypes/{id}/schema", get(get_worktype_schema))
+        .route("/worktypes/{id}/versions", get(list_worktype_versions))
+        .route(
+            "/worktypes/{id}/versions/{version}",
+            get(get_worktype_version),
+        )
+        .route(
+            "/worktypes/{id}/workflow",
+            get(get_worktype_workflow).put(set_worktype_workflow),
+        )
+        .route(
+            "/companies/{id}/worktypes",
+            get(list_company_worktypes).post(create_company_worktype),
+        )
+        .with_state(repository);
+
+    let work_items = Router::new()
+        .route(
+            "/worktypes/{id}/items",
+            get(list_work_items).post(create_work_item),
+        )
+        .route(
+            "/items/{id}",
+            get(get_work_item)
+                .put(update_work_item)
+                .delete(delete_work_item),
+        )
+        .route("/items/{id}/transition", post(transition_work_item))
+        .route("/items/{id}/history", get(get_work_item_history))
+        .route(
+            "/items/{id}/links",
+            get(list_work_item_links).post(add_work_item_link),
+        )
+        .route("/items/{id}/links/{link_id}", delete(remove_work_item_link))
+        .route("/link-types", get(list_link_types).post(create_link_type))
+        .route(
+            "/link-types/{id}",
+            get(get_link_type)
+                .put(update_link_type)
+                .delete(delete_link_type),
+        )
+        .with_state(WorkItemState {
+            items: item_repository,
+            links: link_repository,
+        });
+
+    work_types.merge(work_items)
 }
@@ -0,0 +1,129 @@
+use serde_json::{json, Map, Value};
+
+use crate::models::{DataType, WorkAttributeType, WorkType};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// Numeros tal y como los acepta `f64::from_str`
+const NUMBER_PATTERN: &str = r"^\s*[-+]?(\d+\.?\d*|\.\d+)([eE][-+]?\d+)?\s*$";
+
+impl WorkType {
+    // JSON Schema (draft 2020-12) del cuerpo con el que se crea o actualiza un
+    // work item, con los valores indexados por la clave de cada atributo y su
+    // nombre como `title`. Los valores viajan siempre como texto, asi que cada
+    // tipo de dato se traduce en `format`, `pattern` o `enum` sobre un string.
+    // Los atributos calculados no se pueden enviar y quedan fuera del esquema.
+    pub fn json_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required: Vec<&str> = Vec::new();
+
+        for attribute in self.attributes.iter().filter(|a| a.formula.is_none()) {
+            properties.insert(attribute.key.clone(), attribute.json_schema());
+            // Los valores por defecto se aplican antes de validar
+            if attribute.is_required && attribute.default_value.is_none() {
+                required.push(&attribute.key);
+            }
+        }
+
+        let mut schema = json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "title": self.title,
+            "type": "object",
+            "properties": {
+                "values": {
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false
+                }
+            },
+            "required": ["values"]
+        });
+        if let Some(description) = &self.description {
+            schema["description"] = json!(description);
+        }
+        schema
+    }
+}
+
+impl WorkAttributeType {
+    fn json_schema(&self) -> Value {
+        let mut schema = json!({ "title": self.name, "type": "string" });
+
+        match self.data_type {
+            DataType::StringType => {}
+            DataType::NumericType => schema["pattern"] = json!(NUMBER_PATTERN),
+            DataType::BooleanType => schema["enum"] = json!(["true", "false"]),
+            DataType::DateType => schema["format"] = json!("date"),
+            DataType::DateTimeType => schema["format"] = json!("date-time"),
+            DataType::EmailType => schema["format"] = json!("email"),
+            DataType::UrlType => schema["format"] = json!("uri"),
+            DataType::SingleSelectType => {
+                schema["enum"] = json!(self.options.as_deref().unwrap_or_default())
+            }
+            DataType::MultiSelectType => {
+                let options: Vec<String> = self
+                    .options
+                    .as_deref()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|option| escape_pattern(option))
+                    .collect();
+                let option = format!("({})", options.join("|"));
+                schema["pattern"] = json!(format!(
+                    r"^\s*{option}(\s*,\s*{option})*\s*$",
+                    option = option
+                ));
+            }
+            DataType::DecimalType { precision, scale } => {
+                let integer = match precision - scale {
+                    0 => "0+".to_string(),
+                    digits => format!(r"0*\d{{1,{}}}", digits),
+                };
+                schema["pattern"] =
+                    json!(format!(r"^\s*[-+]?{}(\.\d{{0,{}}})?\s*$", integer, scale));
+            }
+        }
+
+        if self.data_type.is_text() {
+            if let Some(min) = self.min_length {
+                schema["minLength"] = json!(min);
+            }
+            if let Some(max) = self.max_length {
+                schema["maxLength"] = json!(max);
+            }
+            if let Some(pattern) = &self.pattern {
+                schema["pattern"] = json!(pattern);
+            }
+        }
+
+        // JSON Schema no puede acotar numeros escritos como texto: el rango se
+        // describe para quien lea el esquema
+        let range = match (self.min_value, self.max_value) {
+            (Some(min), Some(max)) => Some(format!("Number between {} and {}", min, max)),
+            (Some(min), None) => Some(format!("Number greater than or equal to {}", min)),
+            (None, Some(max)) => Some(format!("Number less than or equal to {}", max)),
+            (None, None) => None,
+        };
+        if let Some(range) = range.filter(|_| self.data_type.is_numeric()) {
+            schema["description"] = json!(range);
+        }
+        if let Some(default) = &self.default_value {
+            schema["default"] = json!(default);
+        }
+
+        schema
+    }
+}
+
+// Escapa los caracteres especiales de las expresiones regulares de ECMA 262
+fn escape_pattern(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
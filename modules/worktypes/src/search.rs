@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DataType, WorkItem, WorkType};
+
+static SEARCH_LANGUAGE: OnceLock<SearchLanguage> = OnceLock::new();
+
+// Delimitadores de las palabras encontradas mientras se construyen los
+// fragmentos. Son caracteres de uso privado, que se quitan del texto antes de
+// marcarlo, y se cambian por <mark> y </mark> una vez escapado el HTML.
+pub const MARK_START: char = '\u{E000}';
+pub const MARK_STOP: char = '\u{E001}';
+
+// Idioma con el que PostgreSQL separa y normaliza las palabras de los
+// atributos de texto al indexarlos y al buscar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchLanguage {
+    #[default]
+    English,
+    Spanish,
+}
+
+// Work item encontrado por la busqueda de texto, con su relevancia y, por
+// clave, el valor completo de los atributos que coinciden, escapado como HTML
+// y con las palabras encontradas entre <mark> y </mark>
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub item: WorkItem,
+    pub rank: f32,
+    pub highlights: BTreeMap<String, String>,
+}
+
+impl FromStr for SearchLanguage {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "english" | "en" => Ok(SearchLanguage::English),
+            "spanish" | "es" => Ok(SearchLanguage::Spanish),
+            other => Err(AppError::Validation(format!(
+                "unknown search language: {} (use english or spanish)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SearchLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.regconfig())
+    }
+}
+
+impl SearchLanguage {
+    // Solo se puede fijar una vez, al arrancar y antes de atender peticiones
+    pub fn install(self) {
+        if SEARCH_LANGUAGE.set(self).is_err() {
+            tracing::warn!("The search language was already set, keeping the previous one");
+        }
+    }
+
+    pub fn get() -> SearchLanguage {
+        *SEARCH_LANGUAGE.get_or_init(SearchLanguage::default)
+    }
+
+    // Configuracion de busqueda de texto de PostgreSQL
+    pub fn regconfig(&self) -> &'static str {
+        match self {
+            SearchLanguage::English => "english",
+            SearchLanguage::Spanish => "spanish",
+        }
+    }
+}
+
+// Palabras de la busqueda en minusculas; sin ninguna no hay nada que buscar
+pub fn search_terms(q: &str) -> Result<Vec<String>, AppError> {
+    let terms: Vec<String> = words(q)
+        .into_iter()
+        .map(|(_, word)| word.to_lowercase())
+        .collect();
+    if terms.is_empty() {
+        return Err(AppError::Validation(
+            "q needs at least one word to search".to_string(),
+        ));
+    }
+    Ok(terms)
+}
+
+// Busqueda sencilla para el repositorio en memoria: el work item debe tener
+// todas las palabras en sus atributos de texto, sin tener en cuenta
+// mayusculas. La relevancia es la proporcion de palabras que coinciden.
+pub fn match_item(work_type: &WorkType, item: &WorkItem, terms: &[String]) -> Option<SearchHit> {
+    let texts: Vec<(&str, &str)> = item
+        .work_attributes
+        .iter()
+        .filter(|value| {
+            work_type
+                .attributes
+                .iter()
+                .any(|a| a.key == value.key && a.data_type == DataType::StringType)
+        })
+        .map(|value| (value.key.as_str(), value.value.as_str()))
+        .collect();
+
+    let all_words: Vec<String> = texts
+        .iter()
+        .flat_map(|(_, text)| words(text))
+        .map(|(_, word)| word.to_lowercase())
+        .collect();
+    if !terms.iter().all(|term| all_words.contains(term)) {
+        return None;
+    }
+
+    let matched = all_words.iter().filter(|word| terms.contains(word)).count();
+    let highlights: BTreeMap<String, String> = texts
+        .iter()
+        .filter_map(|(key, text)| highlight(text, terms).map(|h| (key.to_string(), h)))
+        .collect();
+    Some(SearchHit {
+        item: item.clone(),
+        rank: matched as f32 / all_words.len() as f32,
+        highlights,
+    })
+}
+
+fn highlight(text: &str, terms: &[String]) -> Option<String> {
+    let text: String = text
+        .chars()
+        .filter(|c| *c != MARK_START && *c != MARK_STOP)
+        .collect();
+    let mut marked = String::new();
+    let mut last = 0;
+    for (start, word) in words(&text) {
+        if terms.contains(&word.to_lowercase()) {
+            marked.push_str(&text[last..start]);
+            marked.push(MARK_START);
+            marked.push_str(word);
+            marked.push(MARK_STOP);
+            last = start + word.len();
+        }
+    }
+    marked.push_str(&text[last..]);
+    mark_html(&marked)
+}
+
+// Escapa el fragmento como HTML y cambia los delimitadores por <mark> y
+// </mark>. Sin ninguna palabra marcada no hay fragmento.
+pub fn mark_html(marked: &str) -> Option<String> {
+    if !marked.contains(MARK_START) {
+        return None;
+    }
+    let mut html = String::with_capacity(marked.len());
+    for c in marked.chars() {
+        match c {
+            MARK_START => html.push_str("<mark>"),
+            MARK_STOP => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    Some(html)
+}
+
+// Palabras del texto con su posicion
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+    words
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use common::error::AppError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::bundle::{plan_import, same_title, BundledWorkType, PlannedImport, WorkTypeBundle};
+use crate::models::{WorkType, Workflow};
+use crate::requests::{ConflictPolicy, CreateWorkAttributeType};
+
+// Diferencias entre el estado deseado y los tipos de trabajo globales. Con
+// `applied` a `false` es solo un plan y no se ha tocado nada.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPlan {
+    pub applied: bool,
+    pub changes: Vec<WorkTypeChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+// `work_type_id` falta en los tipos de trabajo que aun no se han creado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkTypeChange {
+    pub action: ChangeAction,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_type_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<AttributeChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeChange {
+    pub action: ChangeAction,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+// Paso de la sincronizacion. Los tipos de trabajo sin cambios (`change` a
+// `None`) no se tocan, pero sus hijos necesitan conocer su id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncStep {
+    pub import: PlannedImport,
+    pub change: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedSync {
+    pub plan: SyncPlan,
+    pub steps: Vec<SyncStep>,
+    // Tipos de trabajo que sobran, los hijos antes que sus padres
+    pub deletions: Vec<Uuid>,
+}
+
+// El documento tiene el formato de los paquetes y describe todos los tipos de
+// trabajo globales: los que coinciden por titulo se actualizan, los nuevos se
+// crean y los que no aparecen se borran
+pub fn plan_sync(
+    document: WorkTypeBundle,
+    existing: &[WorkType],
+    workflows: &HashMap<Uuid, Workflow>,
+) -> Result<PlannedSync, AppError> {
+    let desired: Vec<BundledWorkType> = document.work_types.clone();
+    let imports = plan_import(document, existing, ConflictPolicy::Overwrite)?;
+
+    let mut changes: Vec<WorkTypeChange> = Vec::new();
+    let mut steps: Vec<SyncStep> = Vec::new();
+    for import in imports {
+        let entry = desired
+            .iter()
+            .find(|entry| entry.title == import.title)
+            .ok_or_else(|| AppError::Internal(format!("'{}' lost while syncing", import.title)))?;
+
+        let change = match import
+            .existing_id
+            .and_then(|id| existing.iter().find(|wt| wt.id == id))
+        {
+            None => Some(WorkTypeChange {
+                action: ChangeAction::Create,
+                title: entry.title.clone(),
+                work_type_id: None,
+                fields: Vec::new(),
+                attributes: entry
+                    .attributes
+                    .iter()
+                    .map(|attribute| AttributeChange {
+                        action: ChangeAction::Create,
+                        name: attribute.name.clone(),
+                        fields: Vec::new(),
+                    })
+                    .collect(),
+            }),
+            Some(current) => {
+                let workflow = workflows.get(&current.id).cloned().unwrap_or_default();
+                diff_work_type(current, existing, &workflow, entry)
+            }
+        };
+
+        let change = change.map(|change| {
+            changes.push(change);
+            changes.len() - 1
+        });
+        steps.push(SyncStep { import, change });
+    }
+
+    let kept: Vec<Uuid> = steps
+        .iter()
+        .filter_map(|step| step.import.existing_id)
+        .collect();
+    let mut removed: Vec<(usize, &WorkType)> = existing
+        .iter()
+        .filter(|wt| !kept.contains(&wt.id))
+        .map(|wt| (depth(wt, existing), wt))
+        .collect();
+    removed.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    for (_, work_type) in &removed {
+        changes.push(WorkTypeChange {
+            action: ChangeAction::Delete,
+            title: work_type.title.clone(),
+            work_type_id: Some(work_type.id),
+            fields: Vec::new(),
+            attributes: Vec::new(),
+        });
+    }
+
+    Ok(PlannedSync {
+        plan: SyncPlan {
+            applied: false,
+            changes,
+        },
+        steps,
+        deletions: removed.iter().map(|(_, wt)| wt.id).collect(),
+    })
+}
+
+fn diff_work_type(
+    current: &WorkType,
+    existing: &[WorkType],
+    workflow: &Workflow,
+    desired: &BundledWorkType,
+) -> Option<WorkTypeChange> {
+    let mut fields: Vec<String> = Vec::new();
+    if current.title != desired.title {
+        fields.push("title".to_string());
+    }
+    if current.description != desired.description {
+        fields.push("description".to_string());
+    }
+    let parent = current
+        .parent_id
+        .and_then(|parent_id| existing.iter().find(|wt| wt.id == parent_id))
+        .map(|parent| parent.title.as_str());
+    let same_parent = match (parent, desired.parent.as_deref()) {
+        (Some(a), Some(b)) => same_title(a, b),
+        (a, b) => a == b,
+    };
+    if !same_parent {
+        fields.push("parent".to_string());
+    }
+    if workflow.to_create_request() != desired.workflow.clone().unwrap_or_default() {
+        fields.push("workflow".to_string());
+    }
+
+    let mut attributes: Vec<AttributeChange> = Vec::new();
+    let mut matched: Vec<Uuid> = Vec::new();
+    for wanted in &desired.attributes {
+        match current.matching_attribute(wanted) {
+            None => attributes.push(AttributeChange {
+                action: ChangeAction::Create,
+                name: wanted.name.clone(),
+                fields: Vec::new(),
+            }),
+            Some(attribute) => {
+                matched.push(attribute.id);
+                // Sin clave en el documento se conserva la del atributo
+                let wanted = CreateWorkAttributeType {
+                    key: Some(attribute.key.clone()),
+                    ..wanted.clone()
+                };
+                let fields = changed_fields(&attribute.to_create_request(), &wanted);
+                if !fields.is_empty() {
+                    attributes.push(AttributeChange {
+                        action: ChangeAction::Update,
+                        name: wanted.name.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    // Los atributos de sistema se conservan aunque el documento no los incluya
+    for attribute in current.own_attributes().filter(|a| !a.is_system) {
+        if !matched.contains(&attribute.id) {
+            attributes.push(AttributeChange {
+                action: ChangeAction::Delete,
+                name: attribute.name.clone(),
+                fields: Vec::new(),
+            });
+        }
+    }
+
+    if fields.is_empty() && attributes.is_empty() {
+        return None;
+    }
+    Some(WorkTypeChange {
+        action: ChangeAction::Update,
+        title: desired.title.clone(),
+        work_type_id: Some(current.id),
+        fields,
+        attributes,
+    })
+}
+
+// Campos de un atributo que cambian, comparando su forma serializada
+fn changed_fields(
+    current: &CreateWorkAttributeType,
+    desired: &CreateWorkAttributeType,
+) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(current)), Ok(serde_json::Value::Object(desired))) =
+        (serde_json::to_value(current), serde_json::to_value(desired))
+    else {
+        return Vec::new();
+    };
+
+    let mut fields: Vec<String> = current
+        .keys()
+        .chain(desired.keys())
+        .filter(|key| current.get(*key) != desired.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+fn depth(work_type: &WorkType, work_types: &[WorkType]) -> usize {
+    let mut depth = 0;
+    let mut current = work_type.parent_id;
+    while let Some(parent) = current.and_then(|id| work_types.iter().find(|wt| wt.id == id)) {
+        depth += 1;
+        current = parent.parent_id;
+    }
+    depth
+}
@@ -0,0 +1,112 @@
+use std::sync::OnceLock;
+
+use common::error::AppError;
+
+use crate::models::{DataType, WorkAttributeType};
+use crate::requests::CreateWorkAttributeType;
+use crate::validation::validate_attribute_request;
+
+static SYSTEM_ATTRIBUTES: OnceLock<SystemAttributes> = OnceLock::new();
+
+// Atributos que tienen todos los tipos de trabajo. Los que no heredan de otro
+// los tienen como propios; los derivados los reciben de su padre. No se pueden
+// eliminar, renombrar ni cambiar de tipo de dato, pero el resto de su
+// definicion (obligatorio, oculto, restricciones...) se puede ajustar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemAttributes {
+    attributes: Vec<CreateWorkAttributeType>,
+}
+
+impl Default for SystemAttributes {
+    fn default() -> Self {
+        Self {
+            attributes: vec![
+                system_attribute("Summary", DataType::StringType),
+                system_attribute("Description", DataType::StringType),
+            ],
+        }
+    }
+}
+
+impl SystemAttributes {
+    // Lista JSON de definiciones de atributo, como las de los tipos de trabajo
+    pub fn from_json(json: &str) -> Result<Self, AppError> {
+        let attributes: Vec<CreateWorkAttributeType> = serde_json::from_str(json)
+            .map_err(|e| AppError::Validation(format!("invalid system attributes: {}", e)))?;
+        for (i, attribute) in attributes.iter().enumerate() {
+            validate_attribute_request(attribute)?;
+            if attribute.formula.is_some() {
+                return Err(AppError::Validation(format!(
+                    "system attribute '{}' cannot be computed",
+                    attribute.name
+                )));
+            }
+            if attributes[..i].iter().any(|a| {
+                a.key() == attribute.key() || a.name.to_lowercase() == attribute.name.to_lowercase()
+            }) {
+                return Err(AppError::Validation(format!(
+                    "system attribute '{}' is defined more than once",
+                    attribute.name
+                )));
+            }
+        }
+        Ok(Self { attributes })
+    }
+
+    // Solo se puede fijar una vez, al arrancar y antes de atender peticiones
+    pub fn install(self) {
+        if SYSTEM_ATTRIBUTES.set(self).is_err() {
+            tracing::warn!("The system attributes were already set, keeping the previous ones");
+        }
+    }
+
+    pub fn get() -> &'static SystemAttributes {
+        SYSTEM_ATTRIBUTES.get_or_init(SystemAttributes::default)
+    }
+
+    // Marca los atributos propios que son de sistema y, si el tipo de trabajo
+    // no hereda de otro, añade al final los que le falten. Un atributo propio
+    // con la clave de uno de sistema debe conservar su tipo de dato.
+    pub fn merge(
+        &self,
+        inherits: bool,
+        mut attributes: Vec<WorkAttributeType>,
+    ) -> Result<Vec<WorkAttributeType>, AppError> {
+        for system in &self.attributes {
+            match attributes.iter_mut().find(|a| a.key == system.key()) {
+                Some(attribute) if attribute.data_type != system.data_type => {
+                    return Err(AppError::Validation(format!(
+                        "system attribute '{}' must be of type {}",
+                        system.name, system.data_type
+                    )))
+                }
+                Some(attribute) => attribute.is_system = true,
+                None if inherits => {}
+                None => attributes.push(WorkAttributeType {
+                    position: attributes.len() as i32,
+                    is_system: true,
+                    ..WorkAttributeType::from_create_request(system)
+                }),
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+fn system_attribute(name: &str, data_type: DataType) -> CreateWorkAttributeType {
+    CreateWorkAttributeType {
+        name: name.to_string(),
+        key: None,
+        data_type,
+        options: None,
+        is_required: false,
+        is_hidden: false,
+        min_value: None,
+        max_value: None,
+        min_length: None,
+        max_length: None,
+        pattern: None,
+        default_value: None,
+        formula: None,
+    }
+}
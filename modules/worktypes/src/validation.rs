@@ -0,0 +1,991 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::{DateTime, NaiveDate};
+use common::error::AppError;
+use regex::Regex;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    formula::{self, FormulaType},
+    models::{DataType, LinkType, WorkAttributeType, WorkItemLink, WorkType, WorkTypeStatus},
+    requests::{CreateLinkType, CreateWorkAttributeType, CreateWorkType, CreateWorkflow},
+};
+
+const MAX_KEY_LENGTH: usize = 100;
+
+// Problemas detectados al contrastar los valores de un work item con los
+// atributos de su tipo de trabajo, que se identifican por su clave
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueIssue {
+    Missing(String),
+    Invalid { key: String, reason: String },
+    Unknown(String),
+}
+
+impl fmt::Display for ValueIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueIssue::Missing(key) => write!(f, "missing required attribute '{}'", key),
+            ValueIssue::Invalid { key, reason } => {
+                write!(f, "invalid value for attribute '{}': {}", key, reason)
+            }
+            ValueIssue::Unknown(key) => write!(f, "unknown attribute '{}'", key),
+        }
+    }
+}
+
+impl DataType {
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            DataType::StringType | DataType::SingleSelectType | DataType::MultiSelectType => Ok(()),
+            DataType::NumericType => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(|_| ())
+                .ok_or_else(|| format!("'{}' is not a number", value)),
+            DataType::BooleanType => match value.trim().to_lowercase().as_str() {
+                "true" | "false" => Ok(()),
+                _ => Err(format!("'{}' is not a boolean", value)),
+            },
+            DataType::DateType => NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not a date (YYYY-MM-DD)", value)),
+            DataType::DateTimeType => DateTime::parse_from_rfc3339(value.trim())
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not an RFC 3339 datetime", value)),
+            DataType::EmailType => is_email(value.trim())
+                .then_some(())
+                .ok_or_else(|| format!("'{}' is not an email address", value)),
+            DataType::UrlType => Url::parse(value.trim())
+                .ok()
+                .filter(|url| url.has_host())
+                .map(|_| ())
+                .ok_or_else(|| format!("'{}' is not a URL", value)),
+            DataType::DecimalType { precision, scale } => {
+                validate_decimal(value.trim(), *precision, *scale)
+            }
+        }
+    }
+}
+
+impl WorkAttributeType {
+    pub fn validate_value(&self, value: &str) -> Result<(), String> {
+        self.data_type.validate(value)?;
+
+        if self.data_type.is_select() {
+            let options: &[String] = self.options.as_deref().unwrap_or_default();
+            let selected: Vec<&str> = match self.data_type {
+                DataType::MultiSelectType => split_multi_select(value),
+                _ => vec![value.trim()],
+            };
+            if let Some(invalid) = selected.iter().find(|s| !options.iter().any(|o| o == *s)) {
+                return Err(format!(
+                    "'{}' is not one of the options [{}]",
+                    invalid,
+                    options.join(", ")
+                ));
+            }
+        }
+
+        if self.data_type.is_numeric() {
+            let number: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{}' is not a number", value))?;
+            if let Some(min) = self.min_value.filter(|min| number < *min) {
+                return Err(format!("{} is lower than the minimum {}", number, min));
+            }
+            if let Some(max) = self.max_value.filter(|max| number > *max) {
+                return Err(format!("{} is greater than the maximum {}", number, max));
+            }
+        }
+
+        if self.data_type.is_text() {
+            let length = value.chars().count() as i32;
+            if let Some(min) = self.min_length.filter(|min| length < *min) {
+                return Err(format!("must have at least {} characters", min));
+            }
+            if let Some(max) = self.max_length.filter(|max| length > *max) {
+                return Err(format!("must have at most {} characters", max));
+            }
+            if let Some(pattern) = &self.pattern {
+                let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+                if !regex.is_match(value) {
+                    return Err(format!(
+                        "'{}' does not match the pattern {}",
+                        value, pattern
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Los valores de una seleccion multiple se separan por comas
+pub fn split_multi_select(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !value.chars().any(char::is_whitespace)
+                && domain.contains('.')
+                && domain
+                    .split('.')
+                    .all(|part| !part.is_empty() && !part.contains('@'))
+        }
+        None => false,
+    }
+}
+
+fn validate_decimal(value: &str, precision: u32, scale: u32) -> Result<(), String> {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return Err(format!("'{}' is not a decimal number", value));
+    }
+    let integer_digits = integer.trim_start_matches('0').len() as u32;
+    if fraction.len() as u32 > scale || integer_digits > precision - scale {
+        return Err(format!(
+            "'{}' does not fit in decimal({},{})",
+            value, precision, scale
+        ));
+    }
+    Ok(())
+}
+
+fn check_constraints(attribute: &CreateWorkAttributeType) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+    let name = &attribute.name;
+    let data_type = attribute.data_type;
+
+    if attribute.min_value.is_some() || attribute.max_value.is_some() {
+        if !data_type.is_numeric() {
+            errors.push(format!(
+                "attribute '{}' of type {} does not accept min_value/max_value",
+                name, data_type
+            ));
+        }
+        if let (Some(min), Some(max)) = (attribute.min_value, attribute.max_value) {
+            if min > max {
+                errors.push(format!(
+                    "min_value of attribute '{}' is greater than its max_value",
+                    name
+                ));
+            }
+        }
+    }
+
+    if attribute.min_length.is_some() || attribute.max_length.is_some() {
+        if !data_type.is_text() {
+            errors.push(format!(
+                "attribute '{}' of type {} does not accept min_length/max_length",
+                name, data_type
+            ));
+        }
+        if [attribute.min_length, attribute.max_length]
+            .iter()
+            .flatten()
+            .any(|length| *length < 0)
+        {
+            errors.push(format!(
+                "lengths of attribute '{}' cannot be negative",
+                name
+            ));
+        }
+        if let (Some(min), Some(max)) = (attribute.min_length, attribute.max_length) {
+            if min > max {
+                errors.push(format!(
+                    "min_length of attribute '{}' is greater than its max_length",
+                    name
+                ));
+            }
+        }
+    }
+
+    if let Some(pattern) = &attribute.pattern {
+        if !data_type.is_text() {
+            errors.push(format!(
+                "attribute '{}' of type {} does not accept a pattern",
+                name, data_type
+            ));
+        } else if let Err(e) = Regex::new(pattern) {
+            errors.push(format!(
+                "pattern of attribute '{}' is not valid: {}",
+                name, e
+            ));
+        }
+    }
+
+    // Solo tiene sentido comprobar el valor por defecto si el resto de la definicion es correcta
+    if let (Some(default_value), true) = (&attribute.default_value, errors.is_empty()) {
+        if let Err(reason) =
+            WorkAttributeType::from_create_request(attribute).validate_value(default_value)
+        {
+            errors.push(format!(
+                "default_value of attribute '{}' is not valid: {}",
+                name, reason
+            ));
+        }
+    }
+
+    errors
+}
+
+// Comprueba la definicion de un tipo de trabajo antes de persistirla
+pub fn validate_work_type_request(request: &CreateWorkType) -> Result<(), AppError> {
+    let mut errors: Vec<String> = request
+        .attributes
+        .iter()
+        .flat_map(attribute_errors)
+        .collect();
+
+    for (i, attribute) in request.attributes.iter().enumerate() {
+        let previous = &request.attributes[..i];
+        if previous
+            .iter()
+            .any(|a| a.name.to_lowercase() == attribute.name.to_lowercase())
+        {
+            errors.push(format!(
+                "attribute name '{}' is used more than once",
+                attribute.name
+            ));
+        }
+        if previous.iter().any(|a| a.key() == attribute.key()) {
+            errors.push(format!(
+                "attribute key '{}' is used more than once",
+                attribute.key()
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+pub fn validate_attribute_request(request: &CreateWorkAttributeType) -> Result<(), AppError> {
+    let errors = attribute_errors(request);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+fn attribute_errors(attribute: &CreateWorkAttributeType) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    if attribute.name.trim().is_empty() {
+        errors.push("attributes need a name".to_string());
+    } else if let Some(reason) = key_error(&attribute.key()) {
+        match &attribute.key {
+            Some(key) => errors.push(format!(
+                "key '{}' of attribute '{}' is not valid: {}",
+                key, attribute.name, reason
+            )),
+            None => errors.push(format!(
+                "attribute '{}' needs an explicit key, the one derived from its name is not valid: {}",
+                attribute.name, reason
+            )),
+        }
+    }
+
+    let options: &[String] = attribute.options.as_deref().unwrap_or_default();
+    if attribute.data_type.is_select() {
+        if options.is_empty() {
+            errors.push(format!(
+                "attribute '{}' of type {} needs a list of options",
+                attribute.name, attribute.data_type
+            ));
+        }
+        if options
+            .iter()
+            .any(|o| o.trim().is_empty() || o.contains(','))
+        {
+            errors.push(format!(
+                "options of attribute '{}' cannot be empty nor contain commas",
+                attribute.name
+            ));
+        }
+        let mut unique: Vec<&String> = options.iter().collect();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != options.len() {
+            errors.push(format!(
+                "options of attribute '{}' must be unique",
+                attribute.name
+            ));
+        }
+    } else if attribute.options.is_some() {
+        errors.push(format!(
+            "attribute '{}' of type {} does not accept options",
+            attribute.name, attribute.data_type
+        ));
+    }
+
+    errors.extend(check_constraints(attribute));
+
+    if let Some(formula) = &attribute.formula {
+        if !attribute.data_type.is_numeric() && attribute.data_type != DataType::StringType {
+            errors.push(format!(
+                "attribute '{}' of type {} cannot be computed",
+                attribute.name, attribute.data_type
+            ));
+        }
+        if attribute.is_required || attribute.default_value.is_some() {
+            errors.push(format!(
+                "computed attribute '{}' cannot be required nor have a default_value",
+                attribute.name
+            ));
+        }
+        if let Err(reason) = formula::parse(formula) {
+            errors.push(format!(
+                "formula of attribute '{}' is not valid: {}",
+                attribute.name, reason
+            ));
+        }
+    }
+
+    errors
+}
+
+// Las claves son identificadores: letras minusculas, digitos y guiones bajos
+pub(crate) fn key_error(key: &str) -> Option<String> {
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Some(format!(
+            "must have between 1 and {} characters",
+            MAX_KEY_LENGTH
+        ));
+    }
+    let valid = key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    (!valid).then(|| {
+        "must start with a lowercase letter and contain only lowercase letters, digits and underscores"
+            .to_string()
+    })
+}
+
+// Un tipo de trabajo solo puede heredar de uno global o de uno de su misma compañia
+pub fn validate_parent_scope(company_id: Option<&str>, parent: &WorkType) -> Result<(), AppError> {
+    match parent.company_id.as_deref() {
+        Some(owner) if Some(owner) != company_id => Err(AppError::Validation(format!(
+            "parent worktype {} belongs to another company",
+            parent.id
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// Un tipo de trabajo visible no puede heredar de un borrador
+pub fn validate_parent_status(status: WorkTypeStatus, parent: &WorkType) -> Result<(), AppError> {
+    if status != WorkTypeStatus::Draft && parent.status == WorkTypeStatus::Draft {
+        return Err(AppError::Validation(format!(
+            "a {} worktype cannot inherit from the draft worktype {}",
+            status, parent.id
+        )));
+    }
+    Ok(())
+}
+
+// Los nombres de los atributos ya resueltos, incluidos los heredados, no se
+// pueden repetir aunque cambien las mayusculas. Las claves no se repiten porque
+// la herencia ya las empareja.
+pub fn validate_attribute_names(work_type: &WorkType) -> Result<(), AppError> {
+    let attributes = &work_type.attributes;
+    for (i, attribute) in attributes.iter().enumerate() {
+        if attributes[..i]
+            .iter()
+            .any(|a| a.name.to_lowercase() == attribute.name.to_lowercase())
+        {
+            return Err(AppError::Validation(format!(
+                "attribute name '{}' is used more than once in worktype '{}'",
+                attribute.name, work_type.title
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Las formulas se comprueban sobre los atributos ya resueltos, que incluyen
+// los heredados. Solo pueden referirse a atributos no calculados.
+pub fn validate_formulas(work_type: &WorkType) -> Result<(), AppError> {
+    let types: HashMap<&str, FormulaType> = work_type
+        .attributes
+        .iter()
+        .filter(|a| a.formula.is_none())
+        .map(|a| (a.key.as_str(), FormulaType::of(a.data_type)))
+        .collect();
+    let mut errors: Vec<String> = Vec::new();
+
+    for attribute in &work_type.attributes {
+        let Some(formula) = &attribute.formula else {
+            continue;
+        };
+        let result = formula::parse(formula).and_then(|expr| {
+            if let Some(computed) = expr.references().into_iter().find(|r| {
+                work_type
+                    .attributes
+                    .iter()
+                    .any(|a| a.key == *r && a.formula.is_some())
+            }) {
+                return Err(format!("'{}' is a computed attribute", computed));
+            }
+            expr.infer(&types)
+        });
+
+        match result {
+            Err(reason) => errors.push(format!(
+                "formula of attribute '{}' is not valid: {}",
+                attribute.name, reason
+            )),
+            Ok(FormulaType::Text) if attribute.data_type.is_numeric() => errors.push(format!(
+                "formula of attribute '{}' gives text but the attribute is {}",
+                attribute.name, attribute.data_type
+            )),
+            Ok(_) => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+// Los valores se indexan por la clave de los atributos
+pub fn check_values(work_type: &WorkType, values: &HashMap<String, String>) -> Vec<ValueIssue> {
+    let mut issues: Vec<ValueIssue> = Vec::new();
+
+    for attribute in &work_type.attributes {
+        match values.get(&attribute.key) {
+            Some(_) if attribute.formula.is_some() => issues.push(ValueIssue::Invalid {
+                key: attribute.key.clone(),
+                reason: "is computed and cannot be set".to_string(),
+            }),
+            Some(value) => {
+                if let Err(reason) = attribute.validate_value(value) {
+                    issues.push(ValueIssue::Invalid {
+                        key: attribute.key.clone(),
+                        reason,
+                    });
+                }
+            }
+            None if attribute.is_required => {
+                issues.push(ValueIssue::Missing(attribute.key.clone()))
+            }
+            None => {}
+        }
+    }
+
+    let mut unknown: Vec<&String> = values
+        .keys()
+        .filter(|key| !work_type.attributes.iter().any(|a| &a.key == *key))
+        .collect();
+    unknown.sort();
+    issues.extend(
+        unknown
+            .into_iter()
+            .map(|key| ValueIssue::Unknown(key.clone())),
+    );
+
+    issues
+}
+
+pub fn validate_values(
+    work_type: &WorkType,
+    values: &HashMap<String, String>,
+) -> Result<(), AppError> {
+    let issues = check_values(work_type, values);
+    if issues.is_empty() {
+        return Ok(());
+    }
+
+    let message = issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join("; ");
+    Err(AppError::Validation(message))
+}
+
+pub fn validate_workflow_request(request: &CreateWorkflow) -> Result<(), AppError> {
+    let mut errors: Vec<String> = Vec::new();
+    let names: Vec<&String> = request.statuses.iter().map(|status| &status.name).collect();
+
+    match (&request.initial_status, request.statuses.is_empty()) {
+        (None, false) => errors.push("a workflow needs an initial status".to_string()),
+        (Some(initial), _) if !names.contains(&initial) => errors.push(format!(
+            "initial status '{}' is not a status of the workflow",
+            initial
+        )),
+        _ => {}
+    }
+
+    for (i, status) in request.statuses.iter().enumerate() {
+        if status.name.trim().is_empty() {
+            errors.push("status names cannot be empty".to_string());
+        }
+        if names[..i].contains(&&status.name) {
+            errors.push(format!(
+                "status '{}' is defined more than once",
+                status.name
+            ));
+        }
+        for target in &status.transitions {
+            if !names.contains(&target) {
+                errors.push(format!(
+                    "status '{}' has a transition to the unknown status '{}'",
+                    status.name, target
+                ));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+pub fn validate_link_type_request(request: &CreateLinkType) -> Result<(), AppError> {
+    let mut errors: Vec<String> = Vec::new();
+
+    for (field, value) in [
+        ("name", &request.name),
+        ("outward_name", &request.outward_name),
+        ("inward_name", &request.inward_name),
+    ] {
+        if value.trim().is_empty() {
+            errors.push(format!("{} cannot be empty", field));
+        } else if value.chars().count() > 100 {
+            errors.push(format!("{} cannot be longer than 100 characters", field));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+// `links` son enlaces existentes del tipo `link_type`. Basta con los que llegan
+// al destino y los que se alcanzan desde el siguiendo la direccion de salida.
+pub fn validate_link(
+    link_type: &LinkType,
+    source_id: Uuid,
+    target_id: Uuid,
+    links: &[WorkItemLink],
+) -> Result<(), AppError> {
+    if source_id == target_id {
+        return Err(AppError::Validation(
+            "a work item cannot be linked to itself".to_string(),
+        ));
+    }
+    if links
+        .iter()
+        .any(|link| link.source_id == source_id && link.target_id == target_id)
+    {
+        return Err(AppError::Validation(format!(
+            "work item {} already {} work item {}",
+            source_id, link_type.outward_name, target_id
+        )));
+    }
+    if !link_type.is_hierarchical {
+        return Ok(());
+    }
+
+    // En una jerarquia cada work item tiene como mucho un padre
+    if let Some(existing) = links.iter().find(|link| link.target_id == target_id) {
+        return Err(AppError::Validation(format!(
+            "work item {} is already {} work item {}",
+            target_id, link_type.inward_name, existing.source_id
+        )));
+    }
+
+    let mut visited: Vec<Uuid> = Vec::new();
+    let mut pending: Vec<Uuid> = vec![target_id];
+    while let Some(current) = pending.pop() {
+        if current == source_id {
+            return Err(AppError::Validation(format!(
+                "linking work item {} to {} would create a '{}' cycle",
+                source_id, target_id, link_type.name
+            )));
+        }
+        for link in links.iter().filter(|link| link.source_id == current) {
+            if !visited.contains(&link.target_id) {
+                visited.push(link.target_id);
+                pending.push(link.target_id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StatusCategory;
+    use crate::requests::CreateWorkflowStatus;
+
+    fn attribute(name: &str, data_type: DataType, is_required: bool) -> WorkAttributeType {
+        WorkAttributeType::new(name.to_string(), data_type, is_required, false)
+    }
+
+    fn attribute_request(name: &str, data_type: DataType) -> CreateWorkAttributeType {
+        CreateWorkAttributeType {
+            name: name.to_string(),
+            key: None,
+            data_type,
+            options: None,
+            is_required: false,
+            is_hidden: false,
+            min_value: None,
+            max_value: None,
+            min_length: None,
+            max_length: None,
+            pattern: None,
+            default_value: None,
+            formula: None,
+        }
+    }
+
+    fn attribute_error(request: CreateWorkAttributeType) -> String {
+        match validate_attribute_request(&request) {
+            Err(AppError::Validation(message)) => message,
+            other => panic!("{:?} should be rejected: {:?}", request, other),
+        }
+    }
+
+    fn bug() -> WorkType {
+        WorkType {
+            attributes: vec![
+                attribute("Summary", DataType::StringType, true),
+                attribute("Severity", DataType::NumericType, false),
+            ],
+            ..WorkType::default("Bug".to_string())
+        }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_values_accepts_valid_values() {
+        assert_eq!(check_values(&bug(), &values(&[("summary", "Crash")])), []);
+        assert_eq!(
+            check_values(&bug(), &values(&[("summary", "Crash"), ("severity", "3")])),
+            []
+        );
+    }
+
+    #[test]
+    fn test_check_values_reports_every_issue() {
+        let issues = check_values(
+            &bug(),
+            &values(&[("severity", "high"), ("zeta", "1"), ("alpha", "2")]),
+        );
+
+        assert_eq!(
+            issues,
+            [
+                ValueIssue::Missing("summary".to_string()),
+                ValueIssue::Invalid {
+                    key: "severity".to_string(),
+                    reason: "'high' is not a number".to_string(),
+                },
+                ValueIssue::Unknown("alpha".to_string()),
+                ValueIssue::Unknown("zeta".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_values_joins_the_issues() {
+        let result = validate_values(&bug(), &values(&[("Summary", "Crash")]));
+
+        assert!(matches!(
+            result,
+            Err(AppError::Validation(message))
+                if message == "missing required attribute 'summary'; unknown attribute 'Summary'"
+        ));
+    }
+
+    #[test]
+    fn test_data_types_validate_their_values() {
+        let valid = [
+            (DataType::NumericType, " 3.5 "),
+            (DataType::BooleanType, "TRUE"),
+            (DataType::DateType, "2024-02-29"),
+            (DataType::DateTimeType, "2024-02-29T10:00:00+01:00"),
+            (DataType::EmailType, "ana@example.com"),
+            (DataType::UrlType, "https://example.com/bugs/1"),
+            (DataType::DEFAULT_DECIMAL, "-12.50"),
+        ];
+        for (data_type, value) in valid {
+            assert_eq!(data_type.validate(value), Ok(()), "{} {}", data_type, value);
+        }
+
+        let invalid = [
+            (DataType::NumericType, "NaN", "'NaN' is not a number"),
+            (DataType::BooleanType, "yes", "'yes' is not a boolean"),
+            (
+                DataType::DateType,
+                "2023-02-29",
+                "'2023-02-29' is not a date (YYYY-MM-DD)",
+            ),
+            (
+                DataType::DateTimeType,
+                "2024-02-29 10:00",
+                "'2024-02-29 10:00' is not an RFC 3339 datetime",
+            ),
+            (
+                DataType::EmailType,
+                "ana@example",
+                "'ana@example' is not an email address",
+            ),
+            (DataType::UrlType, "mailto:ana", "'mailto:ana' is not a URL"),
+        ];
+        for (data_type, value, reason) in invalid {
+            assert_eq!(data_type.validate(value), Err(reason.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_decimals_must_fit_their_precision_and_scale() {
+        let decimal = DataType::DecimalType {
+            precision: 5,
+            scale: 2,
+        };
+
+        assert_eq!(decimal.validate("999.99"), Ok(()));
+        assert_eq!(decimal.validate("+007.5"), Ok(()));
+        assert_eq!(
+            decimal.validate("1000"),
+            Err("'1000' does not fit in decimal(5,2)".to_string())
+        );
+        assert_eq!(
+            decimal.validate("1.234"),
+            Err("'1.234' does not fit in decimal(5,2)".to_string())
+        );
+        assert_eq!(
+            decimal.validate("1e3"),
+            Err("'1e3' is not a decimal number".to_string())
+        );
+        assert_eq!(
+            decimal.validate(".5"),
+            Err("'.5' is not a decimal number".to_string())
+        );
+    }
+
+    #[test]
+    fn test_select_values_must_be_options() {
+        let options = Some(vec!["ui".to_string(), "api".to_string()]);
+        let single = WorkAttributeType {
+            options: options.clone(),
+            ..attribute("Area", DataType::SingleSelectType, false)
+        };
+        let multi = WorkAttributeType {
+            options,
+            ..attribute("Tags", DataType::MultiSelectType, false)
+        };
+
+        assert_eq!(single.validate_value("api"), Ok(()));
+        assert_eq!(
+            single.validate_value("ui,api"),
+            Err("'ui,api' is not one of the options [ui, api]".to_string())
+        );
+        assert_eq!(multi.validate_value("ui, api,"), Ok(()));
+        assert_eq!(
+            multi.validate_value("ui,db"),
+            Err("'db' is not one of the options [ui, api]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_values_respect_the_attribute_constraints() {
+        let severity = WorkAttributeType {
+            min_value: Some(1.0),
+            max_value: Some(5.0),
+            ..attribute("Severity", DataType::NumericType, false)
+        };
+        let code = WorkAttributeType {
+            min_length: Some(3),
+            max_length: Some(4),
+            pattern: Some("^[A-Z]+$".to_string()),
+            ..attribute("Code", DataType::StringType, false)
+        };
+
+        assert_eq!(severity.validate_value("5"), Ok(()));
+        assert_eq!(
+            severity.validate_value("0.5"),
+            Err("0.5 is lower than the minimum 1".to_string())
+        );
+        assert_eq!(
+            severity.validate_value("6"),
+            Err("6 is greater than the maximum 5".to_string())
+        );
+        assert_eq!(
+            code.validate_value("ÁBC"),
+            Err("'ÁBC' does not match the pattern ^[A-Z]+$".to_string())
+        );
+        assert_eq!(code.validate_value("ABCD"), Ok(()));
+        assert_eq!(
+            code.validate_value("AB"),
+            Err("must have at least 3 characters".to_string())
+        );
+        assert_eq!(
+            code.validate_value("ABCDE"),
+            Err("must have at most 4 characters".to_string())
+        );
+    }
+
+    #[test]
+    fn test_constraints_must_suit_the_data_type() {
+        assert_eq!(
+            attribute_error(CreateWorkAttributeType {
+                min_length: Some(1),
+                pattern: Some("^a".to_string()),
+                ..attribute_request("Severity", DataType::NumericType)
+            }),
+            "attribute 'Severity' of type numeric does not accept min_length/max_length; \
+             attribute 'Severity' of type numeric does not accept a pattern"
+        );
+        assert_eq!(
+            attribute_error(CreateWorkAttributeType {
+                min_value: Some(1.0),
+                ..attribute_request("Summary", DataType::StringType)
+            }),
+            "attribute 'Summary' of type string does not accept min_value/max_value"
+        );
+    }
+
+    #[test]
+    fn test_constraints_must_be_consistent() {
+        assert_eq!(
+            attribute_error(CreateWorkAttributeType {
+                min_value: Some(5.0),
+                max_value: Some(1.0),
+                ..attribute_request("Severity", DataType::NumericType)
+            }),
+            "min_value of attribute 'Severity' is greater than its max_value"
+        );
+        assert_eq!(
+            attribute_error(CreateWorkAttributeType {
+                min_length: Some(-1),
+                max_length: Some(-2),
+                ..attribute_request("Code", DataType::StringType)
+            }),
+            "lengths of attribute 'Code' cannot be negative; \
+             min_length of attribute 'Code' is greater than its max_length"
+        );
+        assert!(attribute_error(CreateWorkAttributeType {
+            pattern: Some("[".to_string()),
+            ..attribute_request("Code", DataType::StringType)
+        })
+        .starts_with("pattern of attribute 'Code' is not valid: "));
+    }
+
+    #[test]
+    fn test_default_values_must_satisfy_the_constraints() {
+        assert!(validate_attribute_request(&CreateWorkAttributeType {
+            max_value: Some(5.0),
+            default_value: Some("3".to_string()),
+            ..attribute_request("Severity", DataType::NumericType)
+        })
+        .is_ok());
+        assert_eq!(
+            attribute_error(CreateWorkAttributeType {
+                max_value: Some(5.0),
+                default_value: Some("8".to_string()),
+                ..attribute_request("Severity", DataType::NumericType)
+            }),
+            "default_value of attribute 'Severity' is not valid: 8 is greater than the maximum 5"
+        );
+    }
+
+    #[test]
+    fn test_workflow_requests_must_be_consistent() {
+        let status = |name: &str, transitions: &[&str]| CreateWorkflowStatus {
+            name: name.to_string(),
+            category: StatusCategory::Todo,
+            transitions: transitions.iter().map(|t| t.to_string()).collect(),
+        };
+        let valid = CreateWorkflow {
+            initial_status: Some("open".to_string()),
+            statuses: vec![status("open", &["done"]), status("done", &["open"])],
+        };
+        let invalid = CreateWorkflow {
+            initial_status: Some("new".to_string()),
+            statuses: vec![
+                status("open", &["closed"]),
+                status("open", &[]),
+                status(" ", &[]),
+            ],
+        };
+
+        assert!(validate_workflow_request(&valid).is_ok());
+        assert!(validate_workflow_request(&CreateWorkflow::default()).is_ok());
+        assert!(matches!(
+            validate_workflow_request(&invalid),
+            Err(AppError::Validation(message)) if message
+                == "initial status 'new' is not a status of the workflow; \
+                    status 'open' has a transition to the unknown status 'closed'; \
+                    status 'open' is defined more than once; \
+                    status names cannot be empty"
+        ));
+        assert!(matches!(
+            validate_workflow_request(&CreateWorkflow {
+                initial_status: None,
+                ..valid
+            }),
+            Err(AppError::Validation(message)) if message == "a workflow needs an initial status"
+        ));
+    }
+
+    #[test]
+    fn test_validate_parent_scope() {
+        let global = WorkType::default("Issue".to_string());
+        let acme = WorkType {
+            company_id: Some("acme".to_string()),
+            ..WorkType::default("Incident".to_string())
+        };
+
+        assert!(validate_parent_scope(None, &global).is_ok());
+        assert!(validate_parent_scope(Some("acme"), &global).is_ok());
+        assert!(validate_parent_scope(Some("acme"), &acme).is_ok());
+        for company_id in [None, Some("globex")] {
+            assert!(matches!(
+                validate_parent_scope(company_id, &acme),
+                Err(AppError::Validation(message))
+                    if message == format!("parent worktype {} belongs to another company", acme.id)
+            ));
+        }
+    }
+}